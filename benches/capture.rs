@@ -0,0 +1,91 @@
+//! Benchmarks for the capture functions, covering both the best-case path of
+//! supplied context and the worst-case path of regex parsing
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tagzen::music::{SingleSong, SongOptions};
+use tagzen::tv::{cap_episode, cap_season, Capture, CaptureOptions, Context};
+
+/// Representative set of tv filenames to benchmark with
+const TV_NAMES: &[&str] = &[
+    "hello s01 e02 hi.mp4",
+    "xs01e20.epc",
+    "SEASON3EPISODE4",
+    "The.Show.Name.S01E02.1080p.WEB.H264-GROUP.mkv",
+    "Show Season 10 Episode 200 Subtitles.en.srt",
+];
+
+/// Representative set of song filenames to benchmark with
+const SONG_NAMES: &[&str] = &[
+    "song.mp3",
+    "Artist - Title.mp3",
+    "Artist — Some Long Song Title (Live).flac",
+];
+
+fn bench_regex(c: &mut Criterion) {
+    c.bench_function("cap_episode", |b| {
+        b.iter(|| {
+            for name in TV_NAMES {
+                let _ = cap_episode(black_box(name));
+            }
+        })
+    });
+    c.bench_function("cap_season", |b| {
+        b.iter(|| {
+            for name in TV_NAMES {
+                let _ = cap_season(black_box(name));
+            }
+        })
+    });
+}
+
+fn bench_capture(c: &mut Criterion) {
+    let empty = Context {
+        episode: None,
+        season: None,
+    };
+    let full = Context {
+        episode: Some(2),
+        season: Some(1),
+    };
+    let options = CaptureOptions::default();
+
+    c.bench_function("capture_regex", |b| {
+        b.iter(|| {
+            for name in TV_NAMES {
+                let _ = Capture::new(black_box(name.to_string()), &empty, &options);
+            }
+        })
+    });
+    c.bench_function("capture_context", |b| {
+        b.iter(|| {
+            for name in TV_NAMES {
+                let _ = Capture::new(black_box(name.to_string()), &full, &options);
+            }
+        })
+    });
+}
+
+fn bench_song(c: &mut Criterion) {
+    c.bench_function("song_inferred", |b| {
+        b.iter(|| {
+            for name in SONG_NAMES {
+                let _ = SingleSong::new(black_box(name), None, None, &SongOptions::default());
+            }
+        })
+    });
+    c.bench_function("song_context", |b| {
+        b.iter(|| {
+            for name in SONG_NAMES {
+                let _ = SingleSong::new(
+                    black_box(name),
+                    "Artist".to_string(),
+                    "Album".to_string(),
+                    &SongOptions::default(),
+                );
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_regex, bench_capture, bench_song);
+criterion_main!(benches);
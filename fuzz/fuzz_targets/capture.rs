@@ -0,0 +1,61 @@
+//! Throws arbitrary names at the whole parsing surface, which should never panic
+//! no matter the input. Run with `cargo +nightly fuzz run capture` from the root
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use regex::Regex;
+use tagzen::movie::Movie;
+use tagzen::music::{SingleSong, SongOptions};
+use tagzen::tv::{self, Capture, CaptureOptions, Context, Inspection};
+use tagzen::utils::{self, TemplateValue};
+
+fuzz_target!(|data: &[u8]| {
+    let name = String::from_utf8_lossy(data).into_owned();
+    let every_option = CaptureOptions {
+        bare_episode: true,
+        detect_year: true,
+        word_numbers: true,
+        absolute: true,
+        lenient: true,
+        basename: true,
+        default_ext: Some(".mkv".to_string()),
+        dash_se: true,
+        ..Default::default()
+    };
+
+    for options in &[CaptureOptions::default(), every_option] {
+        if let Ok(cap) = Capture::new(name.clone(), &Context::default(), options) {
+            let _ = cap.render();
+            let _ = cap.render_template(&name);
+        }
+    }
+    let _ = Inspection::new(name.clone());
+    if let Some(found) = Regex::new(r"(?s).*").unwrap().find(&name) {
+        let _ = tv::cap_num(found);
+    }
+    let _ = tv::cap_sxe(&name);
+    let _ = tv::cap_episodes(&name);
+    let _ = tv::cap_title(&name);
+    let _ = tv::cap_episode_title(&name);
+
+    for options in &[
+        SongOptions::default(),
+        SongOptions {
+            split_artists: true,
+            preserve_dashes: true,
+            default_ext: None,
+            dj: true,
+            max_len: None,
+        },
+    ] {
+        if let Ok(song) = SingleSong::new(&name, None, None, options) {
+            let _ = song.render_template(&name);
+        }
+    }
+    let _ = Movie::new(name.clone());
+
+    let _ = utils::cap_filename_ext(&name);
+    let _ = utils::cap_airdate(&name);
+    let _ = utils::render_template(&name, |_| Some(TemplateValue::Number(Some(usize::MAX))));
+});
@@ -0,0 +1,125 @@
+//! Administrative endpoints for controlling a running instance, guarded by a
+//! token set using [ADMIN_TOKEN_ENV]
+
+use crate::config::AppConfig;
+use crate::utils::ResponseModel;
+use rocket::request::{self, FromRequest, Request};
+use rocket::{Outcome, State};
+use std::time::Duration;
+use std::{process, thread};
+
+/// Environment variable containing the token required in the `Authorization`
+/// header of admin endpoints, which are all disabled if unset or empty. Read
+/// into [AppConfig::admin_token]
+pub const ADMIN_TOKEN_ENV: &str = "TAGZEN_ADMIN_TOKEN";
+
+/// Time given for the response of [shutdown] to be sent before exiting
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+
+/// Raw `Authorization` header of a request, which may be missing, checked
+/// against [ADMIN_TOKEN_ENV] using [authorized]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Authorization(pub Option<String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Authorization {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        Outcome::Success(Authorization(
+            request.headers().get_one("Authorization").map(String::from),
+        ))
+    }
+}
+
+/// Checks if an `Authorization` `header` matches the `expected` token, either
+/// directly or with a `Bearer ` prefix. No header or token is never authorized
+pub fn authorized(header: Option<&str>, expected: Option<&str>) -> bool {
+    match (header, expected) {
+        (Some(header), Some(expected)) if !expected.is_empty() => {
+            let token = header.trim();
+            let token = token.strip_prefix("Bearer ").unwrap_or(token).as_bytes();
+
+            token.len() == expected.len()
+                && token
+                    .iter()
+                    .zip(expected.as_bytes())
+                    .fold(0, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+        }
+        _ => false,
+    }
+}
+
+/// Gives help for how to use the [shutdown] path
+#[get("/admin/shutdown")]
+pub fn shutdown_help() -> &'static str {
+    "ENDPOINT POST /admin/shutdown\n\n\nAbout\n    Exits this instance shortly after responding, for controlled restarts. This\n    isn't graceful, so any requests still being tagged such as a large batch\n    are cut off. Requires an `Authorization` header containing the token set in the\n    `TAGZEN_ADMIN_TOKEN` environment variable (optionally prefixed by `Bearer`),\n    otherwise a 401 is returned. Disabled entirely if the variable is unset."
+}
+
+/// Shuts down the server once the response has been sent, if [authorized].
+/// Rocket 0.4 has no shutdown handle so the process exits after a short grace
+/// period instead, cutting off any other requests in flight, see
+/// [SHUTDOWN_GRACE]
+#[post("/admin/shutdown")]
+pub fn shutdown(auth: Authorization, config: State<AppConfig>) -> ResponseModel<()> {
+    if !authorized(auth.0.as_deref(), config.admin_token.as_deref()) {
+        return ResponseModel::basic(401, "Unauthorized");
+    }
+
+    thread::spawn(|| {
+        thread::sleep(SHUTDOWN_GRACE);
+        process::exit(0)
+    });
+
+    ResponseModel::basic(200, "Shutting down")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_checks() {
+        assert!(authorized(Some("secret"), Some("secret")));
+        assert!(authorized(Some("Bearer secret"), Some("secret")));
+        assert!(!authorized(Some("wrong"), Some("secret")));
+        assert!(!authorized(Some("Bearer secre"), Some("secret")));
+        assert!(!authorized(None, Some("secret")));
+        assert!(!authorized(Some(""), Some("")));
+        assert!(!authorized(Some("secret"), None));
+    }
+
+    #[test]
+    fn shutdown_unauthorized() {
+        use rocket::http::{Header, Status};
+
+        let client = |admin_token: Option<&str>| {
+            rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(AppConfig {
+                        admin_token: admin_token.map(String::from),
+                        ..Default::default()
+                    })
+                    .mount("/", routes![shutdown]),
+            )
+            .expect("Could not make client")
+        };
+        let enabled = client(Some("secret"));
+
+        let response = enabled
+            .post("/admin/shutdown")
+            .header(Header::new("Authorization", "Bearer wrong"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let response = enabled.post("/admin/shutdown").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        let disabled = client(None);
+        let response = disabled
+            .post("/admin/shutdown")
+            .header(Header::new("Authorization", "Bearer secret"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+}
@@ -0,0 +1,295 @@
+//! Bounded caches of recent captures so re-submitted input skips parsing, with
+//! single episodes sized using [CACHE_SIZE_ENV] and whole batches sized using
+//! [BATCH_CACHE_SIZE_ENV] alongside [BATCH_CACHE_TTL_ENV]
+
+use crate::tv::routes::EpisodeQuery;
+use crate::tv::{Capture, CaptureError};
+use lru::LruCache;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Environment variable which may contain the maximum number of captures kept
+/// in the [CaptureCache], with `0` disabling it, read into
+/// [AppConfig::cache_size](crate::config::AppConfig::cache_size)
+pub const CACHE_SIZE_ENV: &str = "TAGZEN_CACHE_SIZE";
+
+/// Default maximum number of captures kept in the [CaptureCache]
+pub const DEFAULT_CACHE_SIZE: usize = 1024;
+
+/// Environment variable which may contain the maximum number of whole batch
+/// results kept in the [BatchCache], with `0` disabling it, read into
+/// [AppConfig::batch_cache_size](crate::config::AppConfig::batch_cache_size)
+pub const BATCH_CACHE_SIZE_ENV: &str = "TAGZEN_BATCH_CACHE_SIZE";
+
+/// Default maximum number of whole batch results kept in the [BatchCache]
+pub const DEFAULT_BATCH_CACHE_SIZE: usize = 64;
+
+/// Environment variable which may contain the number of seconds a whole batch
+/// result is kept in the [BatchCache] for, read into
+/// [AppConfig::batch_cache_ttl](crate::config::AppConfig::batch_cache_ttl)
+pub const BATCH_CACHE_TTL_ENV: &str = "TAGZEN_BATCH_CACHE_TTL";
+
+/// Default number of seconds a whole batch result is kept in the [BatchCache]
+pub const DEFAULT_BATCH_CACHE_TTL: usize = 300;
+
+/// Result of a single capture as stored inside of the [CaptureCache]
+type CaptureResult = Result<Capture, CaptureError>;
+
+/// Result of a whole batch as stored inside of the [BatchCache]
+type BatchResult = Result<Vec<Capture>, CaptureError>;
+
+/// Least recently used cache of capture results keyed by the full query used to
+/// make them, managed by rocket for the [episode](crate::tv::routes::episode)
+/// path
+#[derive(Debug)]
+pub struct CaptureCache {
+    /// Cached results, which is [None] if the cache is disabled
+    captures: Option<Mutex<LruCache<EpisodeQuery, CaptureResult>>>,
+}
+
+impl CaptureCache {
+    /// Creates a new empty cache holding up to `size` captures, with a `size` of
+    /// `0` disabling caching entirely
+    pub fn new(size: usize) -> Self {
+        Self {
+            captures: if size == 0 {
+                None
+            } else {
+                Some(Mutex::new(LruCache::new(size)))
+            },
+        }
+    }
+
+    /// Gets the cached result of a `query`, marking it as recently used
+    pub fn get(&self, query: &EpisodeQuery) -> Option<CaptureResult> {
+        self.captures
+            .as_ref()?
+            .lock()
+            .unwrap()
+            .get(&Self::key(query))
+            .cloned()
+    }
+
+    /// Caches the `result` of a `query`, evicting the least recently used
+    /// result if full
+    pub fn put(&self, query: &EpisodeQuery, result: CaptureResult) {
+        if let Some(captures) = &self.captures {
+            captures.lock().unwrap().put(Self::key(query), result);
+        }
+    }
+
+    /// Normalizes a `query` into a key by removing args which don't change the
+    /// resulting capture, only how it's serialized
+    fn key(query: &EpisodeQuery) -> EpisodeQuery {
+        EpisodeQuery {
+            compact: None,
+            fields: None,
+            all: None,
+            echo: None,
+            format: None,
+            ..query.clone()
+        }
+    }
+}
+
+impl Default for CaptureCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_SIZE)
+    }
+}
+
+/// Single batch result stored inside of a [BatchCache]
+#[derive(Debug)]
+struct CachedBatch {
+    /// When this result was cached
+    cached: Instant,
+
+    /// Names the result was captured from, compared on lookup so colliding
+    /// hashes are never mixed up
+    names: Vec<String>,
+
+    /// Cached result of the batch
+    result: BatchResult,
+}
+
+/// Least recently used cache of whole batch results keyed by a hash of the
+/// names sent, so re-submitting an identical directory listing skips parsing.
+/// Results expire after a time to live, and it's managed by rocket for the
+/// [batch](crate::tv::routes::batch) path
+#[derive(Debug)]
+pub struct BatchCache {
+    /// Time each result is cached for
+    ttl: Duration,
+
+    /// Cached results, which is [None] if the cache is disabled
+    results: Option<Mutex<LruCache<u64, CachedBatch>>>,
+}
+
+impl BatchCache {
+    /// Creates a new empty cache holding up to `size` results for `ttl` each,
+    /// with a `size` of `0` disabling caching entirely
+    pub fn new(size: usize, ttl: Duration) -> Self {
+        Self {
+            ttl,
+            results: if size == 0 {
+                None
+            } else {
+                Some(Mutex::new(LruCache::new(size)))
+            },
+        }
+    }
+
+    /// Gets the cached result of capturing `names`, giving [None] if it isn't
+    /// cached or has expired
+    pub fn get(&self, names: &[String]) -> Option<BatchResult> {
+        let mut results = self.results.as_ref()?.lock().unwrap();
+        let key = Self::key(names);
+
+        match results.get(&key) {
+            Some(cached) if cached.cached.elapsed() < self.ttl && cached.names == names => {
+                Some(cached.result.clone())
+            }
+            Some(_) => {
+                results.pop(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches the `result` of capturing `names`, evicting the least recently
+    /// used result if full
+    pub fn put(&self, names: &[String], result: BatchResult) {
+        if let Some(results) = &self.results {
+            results.lock().unwrap().put(
+                Self::key(names),
+                CachedBatch {
+                    cached: Instant::now(),
+                    names: names.to_vec(),
+                    result,
+                },
+            );
+        }
+    }
+
+    /// Hashes the entire list of `names` into a key
+    fn key(names: &[String]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        names.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Default for BatchCache {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_BATCH_CACHE_SIZE,
+            Duration::from_secs(DEFAULT_BATCH_CACHE_TTL as u64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tv::{CaptureOptions, Context};
+
+    fn query(name: &str) -> EpisodeQuery {
+        EpisodeQuery {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn capture(name: &str) -> CaptureResult {
+        Capture::new(
+            name.to_string(),
+            &Context::default(),
+            &CaptureOptions::default(),
+        )
+    }
+
+    #[test]
+    fn hit_and_miss() {
+        let cache = CaptureCache::new(2);
+
+        assert_eq!(cache.get(&query("Show S01E02.mkv")), None);
+        cache.put(&query("Show S01E02.mkv"), capture("Show S01E02.mkv"));
+        assert_eq!(
+            cache.get(&query("Show S01E02.mkv")),
+            Some(capture("Show S01E02.mkv"))
+        );
+
+        let compact = EpisodeQuery {
+            compact: Some(true),
+            ..query("Show S01E02.mkv")
+        };
+        assert!(cache.get(&compact).is_some());
+
+        let context = EpisodeQuery {
+            season: Some(5),
+            ..query("Show S01E02.mkv")
+        };
+        assert_eq!(cache.get(&context), None);
+    }
+
+    #[test]
+    fn bounded() {
+        let cache = CaptureCache::new(2);
+
+        for name in &["a S01E01.mkv", "b S01E02.mkv", "c S01E03.mkv"] {
+            cache.put(&query(name), capture(name));
+        }
+
+        assert_eq!(cache.get(&query("a S01E01.mkv")), None);
+        assert!(cache.get(&query("c S01E03.mkv")).is_some());
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = CaptureCache::new(0);
+
+        cache.put(&query("Show S01E02.mkv"), capture("Show S01E02.mkv"));
+        assert_eq!(cache.get(&query("Show S01E02.mkv")), None);
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn batch(names: &[String]) -> BatchResult {
+        names.iter().map(|name| capture(name)).collect()
+    }
+
+    #[test]
+    fn batch_hit_and_miss() {
+        let cache = BatchCache::new(2, Duration::from_secs(60));
+        let listing = names(&["Show S01E01.mkv", "Show S01E02.mkv"]);
+
+        assert_eq!(cache.get(&listing), None);
+        cache.put(&listing, batch(&listing));
+        assert_eq!(cache.get(&listing), Some(batch(&listing)));
+
+        let reordered = names(&["Show S01E02.mkv", "Show S01E01.mkv"]);
+        assert_eq!(cache.get(&reordered), None);
+
+        for name in &["a S01E01.mkv", "b S01E02.mkv"] {
+            cache.put(&names(&[name]), batch(&names(&[name])));
+        }
+        assert_eq!(cache.get(&listing), None);
+    }
+
+    #[test]
+    fn batch_expired_and_disabled() {
+        let expired = BatchCache::new(2, Duration::from_secs(0));
+        let disabled = BatchCache::new(0, Duration::from_secs(60));
+        let listing = names(&["Show S01E01.mkv"]);
+
+        for cache in &[expired, disabled] {
+            cache.put(&listing, batch(&listing));
+            assert_eq!(cache.get(&listing), None);
+        }
+    }
+}
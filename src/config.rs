@@ -0,0 +1,227 @@
+//! Configuration of a running instance loaded once from environment variables at
+//! startup, managed by rocket for handlers to read their defaults from
+//!
+//! # Precedence
+//!
+//! Query args sent with a request always win, followed by the environment
+//! variables read into [AppConfig], then the built-in defaults of each handler.
+//! The quality tags from [QUALITY_TAGS_ENV](crate::utils::QUALITY_TAGS_ENV) are
+//! loaded separately as they're also used without a server. The site prefixes
+//! from [SITE_PREFIXES_ENV] are loaded here and then set as the
+//! [SITE_PREFIXES](crate::utils::SITE_PREFIXES) at startup
+
+use crate::admin::ADMIN_TOKEN_ENV;
+use crate::cache::{
+    BATCH_CACHE_SIZE_ENV, BATCH_CACHE_TTL_ENV, CACHE_SIZE_ENV, DEFAULT_BATCH_CACHE_SIZE,
+    DEFAULT_BATCH_CACHE_TTL, DEFAULT_CACHE_SIZE,
+};
+use crate::utils::{
+    compile_site_prefixes, read_site_prefixes, DEFAULT_MAX_NAME_LEN, SITE_PREFIXES_ENV,
+};
+use std::{env, fmt};
+
+/// Environment variable which may contain the extension assumed for names
+/// without one when no `default_ext` query arg is given, such as `.mkv`
+pub const DEFAULT_EXT_ENV: &str = "TAGZEN_DEFAULT_EXT";
+
+/// Environment variable which may contain the page size of batch responses when
+/// no `limit` query arg is given
+pub const BATCH_LIMIT_ENV: &str = "TAGZEN_BATCH_LIMIT";
+
+/// Environment variable which may contain the maximum length in characters of
+/// names, over which they're rejected before tagging
+pub const MAX_NAME_LEN_ENV: &str = "TAGZEN_MAX_NAME_LEN";
+
+/// Error from an environment variable which couldn't be parsed into an
+/// [AppConfig]
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConfigError {
+    /// Name of the environment variable
+    pub var: &'static str,
+
+    /// Invalid value of the environment variable
+    pub value: String,
+
+    /// What the value was expected to be, such as `a number`
+    pub expected: &'static str,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid value '{}' for {}, expected {}",
+            self.value, self.var, self.expected
+        )
+    }
+}
+
+/// Instance-wide configuration, see the [module](self) docs for how it's
+/// overridden per request
+#[derive(Debug, PartialEq, Clone)]
+pub struct AppConfig {
+    /// Token required by admin endpoints, which are disabled if [None], from
+    /// [ADMIN_TOKEN_ENV]
+    pub admin_token: Option<String>,
+
+    /// Maximum number of captures kept in the
+    /// [CaptureCache](crate::cache::CaptureCache), from [CACHE_SIZE_ENV]
+    pub cache_size: usize,
+
+    /// Extension assumed for names without one, from [DEFAULT_EXT_ENV]
+    pub default_ext: Option<String>,
+
+    /// Page size of batch responses, returning everything if [None], from
+    /// [BATCH_LIMIT_ENV]
+    pub batch_limit: Option<usize>,
+
+    /// Maximum number of whole batch results kept in the
+    /// [BatchCache](crate::cache::BatchCache), from [BATCH_CACHE_SIZE_ENV]
+    pub batch_cache_size: usize,
+
+    /// Seconds each whole batch result is kept in the
+    /// [BatchCache](crate::cache::BatchCache) for, from [BATCH_CACHE_TTL_ENV]
+    pub batch_cache_ttl: usize,
+
+    /// Maximum length in characters of names, see
+    /// [check_name_len](crate::utils::check_name_len), from [MAX_NAME_LEN_ENV]
+    pub max_name_len: usize,
+
+    /// Uncompiled regexes of site prefixes read from the file at
+    /// [SITE_PREFIXES_ENV], or the built-in ones if unset, see
+    /// [read_site_prefixes]
+    pub site_prefixes: Vec<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            admin_token: None,
+            cache_size: DEFAULT_CACHE_SIZE,
+            default_ext: None,
+            batch_limit: None,
+            batch_cache_size: DEFAULT_BATCH_CACHE_SIZE,
+            batch_cache_ttl: DEFAULT_BATCH_CACHE_TTL,
+            max_name_len: DEFAULT_MAX_NAME_LEN,
+            site_prefixes: read_site_prefixes(None::<&str>).unwrap_or_default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Loads the configuration from environment variables, with unset or empty
+    /// variables keeping their defaults
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(|var| env::var(var).ok())
+    }
+
+    /// Loads the configuration using `get` to read each variable, with unset or
+    /// empty variables keeping their defaults
+    pub fn from_vars(get: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let get = |var| get(var).filter(|value| !value.trim().is_empty());
+        let parse = |var: &'static str| {
+            get(var)
+                .map(|value| {
+                    value.trim().parse().map_err(|_| ConfigError {
+                        var,
+                        value: value.clone(),
+                        expected: "a number",
+                    })
+                })
+                .transpose()
+        };
+        let defaults = Self::default();
+        let site_prefixes = match get(SITE_PREFIXES_ENV) {
+            Some(path) => read_site_prefixes(Some(&path))
+                .ok()
+                .filter(|patterns| compile_site_prefixes(patterns).is_ok())
+                .ok_or(ConfigError {
+                    var: SITE_PREFIXES_ENV,
+                    value: path,
+                    expected: "a readable file of valid regexes",
+                })?,
+            None => defaults.site_prefixes.clone(),
+        };
+
+        Ok(Self {
+            admin_token: get(ADMIN_TOKEN_ENV),
+            cache_size: parse(CACHE_SIZE_ENV)?.unwrap_or(defaults.cache_size),
+            default_ext: get(DEFAULT_EXT_ENV),
+            batch_limit: parse(BATCH_LIMIT_ENV)?,
+            batch_cache_size: parse(BATCH_CACHE_SIZE_ENV)?.unwrap_or(defaults.batch_cache_size),
+            batch_cache_ttl: parse(BATCH_CACHE_TTL_ENV)?.unwrap_or(defaults.batch_cache_ttl),
+            max_name_len: parse(MAX_NAME_LEN_ENV)?.unwrap_or(defaults.max_name_len),
+            site_prefixes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(vars: &[(&str, &str)]) -> Result<AppConfig, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(var, value)| (var.to_string(), value.to_string()))
+            .collect();
+
+        AppConfig::from_vars(|var| vars.get(var).cloned())
+    }
+
+    #[test]
+    fn from_vars() {
+        assert_eq!(config(&[]), Ok(AppConfig::default()));
+        assert_eq!(
+            config(&[
+                (ADMIN_TOKEN_ENV, "secret"),
+                (CACHE_SIZE_ENV, " 16 "),
+                (DEFAULT_EXT_ENV, ".mkv"),
+                (BATCH_LIMIT_ENV, "50"),
+                (BATCH_CACHE_SIZE_ENV, "0"),
+                (BATCH_CACHE_TTL_ENV, "60"),
+                (MAX_NAME_LEN_ENV, "256"),
+            ]),
+            Ok(AppConfig {
+                admin_token: Some("secret".to_string()),
+                cache_size: 16,
+                default_ext: Some(".mkv".to_string()),
+                batch_limit: Some(50),
+                batch_cache_size: 0,
+                batch_cache_ttl: 60,
+                max_name_len: 256,
+                ..AppConfig::default()
+            })
+        );
+        assert_eq!(config(&[(ADMIN_TOKEN_ENV, "")]), Ok(AppConfig::default()));
+        assert_eq!(
+            config(&[(BATCH_LIMIT_ENV, "lots")]),
+            Err(ConfigError {
+                var: BATCH_LIMIT_ENV,
+                value: "lots".to_string(),
+                expected: "a number"
+            })
+        );
+    }
+
+    #[test]
+    fn site_prefixes_file() {
+        let path = std::env::temp_dir().join("tagzen_config_site_prefixes_test.txt");
+        let path_str = path.to_str().unwrap();
+        std::fs::write(&path, "(?i)rarbg[ ._-]+\n").unwrap();
+
+        let loaded = config(&[(SITE_PREFIXES_ENV, path_str)]);
+        std::fs::write(&path, "(unclosed").unwrap();
+        let invalid = config(&[(SITE_PREFIXES_ENV, path_str)]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            loaded.map(|config| config.site_prefixes),
+            Ok(vec!["(?i)rarbg[ ._-]+".to_string()])
+        );
+        assert_eq!(invalid.unwrap_err().var, SITE_PREFIXES_ENV);
+        assert!(config(&[(SITE_PREFIXES_ENV, "/nonexistent/prefixes.txt")]).is_err());
+        assert!(!AppConfig::default().site_prefixes.is_empty());
+    }
+}
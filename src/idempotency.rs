@@ -0,0 +1,381 @@
+//! Caching of batch responses by a client-supplied `Idempotency-Key` header so
+//! retried requests don't process the same batch twice
+
+use crate::utils::ResponseModel;
+use rocket::http::{ContentType, Status};
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::{self, Responder, Response};
+use rocket::Outcome;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Header containing the idempotency key sent by clients
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header set to `true` on responses replayed from an [IdempotencyCache]
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// Default time a response is cached for in an [IdempotencyCache]
+const IDEMPOTENCY_TTL: Duration = Duration::from_secs(300);
+
+/// Maximum number of responses kept in an [IdempotencyCache] at once, past
+/// which the oldest are evicted
+const MAX_IDEMPOTENCY_KEYS: usize = 1024;
+
+/// Maximum length of an idempotency key in bytes, longer keys are ignored
+const MAX_KEY_LEN: usize = 255;
+
+/// Optional `Idempotency-Key` header of a request
+#[derive(Debug, PartialEq, Clone)]
+pub struct IdempotencyKey(pub Option<String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for IdempotencyKey {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        Outcome::Success(IdempotencyKey(
+            request
+                .headers()
+                .get_one(IDEMPOTENCY_KEY_HEADER)
+                .map(str::trim)
+                .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+                .map(String::from),
+        ))
+    }
+}
+
+/// Serialized response which may have been replayed from an [IdempotencyCache]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Idempotent {
+    /// Http status code of the response
+    pub status: u16,
+
+    /// Serialized body of the response, which is json unless [Idempotent::content_type]
+    /// says otherwise
+    pub body: String,
+
+    /// Content type of [Idempotent::body]
+    pub content_type: ContentType,
+
+    /// If this response was replayed from the cache instead of being processed
+    pub replayed: bool,
+}
+
+impl Idempotent {
+    /// Creates a fresh response which wasn't replayed with a raw `body` of the
+    /// given `content_type`, such as csv
+    pub fn raw(status: u16, body: String, content_type: ContentType) -> Self {
+        Self {
+            status,
+            body,
+            content_type,
+            replayed: false,
+        }
+    }
+}
+
+impl<T: Serialize> From<ResponseModel<T>> for Idempotent {
+    /// Serializes a fresh json response which wasn't replayed
+    fn from(response: ResponseModel<T>) -> Self {
+        Idempotent::raw(
+            response.status,
+            serde_json::to_string(&response).expect("Could not serialize response"),
+            ContentType::JSON,
+        )
+    }
+}
+
+impl<'r> Responder<'r> for Idempotent {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        Response::build()
+            .status(Status::from_code(self.status).unwrap())
+            .header(self.content_type)
+            .raw_header(REPLAYED_HEADER, self.replayed.to_string())
+            .sized_body(Cursor::new(self.body))
+            .ok()
+    }
+}
+
+/// Single response stored inside of an [IdempotencyCache]
+#[derive(Debug, PartialEq, Clone)]
+struct CachedResponse {
+    /// When this response was cached, or started processing if still in flight
+    cached: Instant,
+
+    /// Hash of the request body this response was given for, so a key reused
+    /// with a different body can be rejected
+    request: u64,
+
+    /// Finished response, which is [None] whilst the request is still being
+    /// processed so concurrent retries don't process it twice
+    response: Option<Idempotent>,
+}
+
+/// In-memory cache of responses keyed by route and idempotency key, expiring
+/// after a set time to live. Managed by rocket and shared between batch routes
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    /// Time each response is cached for
+    ttl: Duration,
+
+    /// Cached responses keyed by route and idempotency key
+    entries: Mutex<HashMap<(String, String), CachedResponse>>,
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(IDEMPOTENCY_TTL)
+    }
+}
+
+/// Hashes the body of a request for [CachedResponse::request]
+fn hash_request(request: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    request.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Removes the in-flight marker of a key if processing its response panicked,
+/// so retries aren't rejected until the marker expires
+struct InFlight<'a> {
+    /// Cache the marker was inserted into
+    cache: &'a IdempotencyCache,
+
+    /// Key of the marker, taken once the response has been cached
+    key: Option<(String, String)>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            if let Ok(mut entries) = self.cache.entries.lock() {
+                entries.remove(&key);
+            }
+        }
+    }
+}
+
+impl IdempotencyCache {
+    /// Creates a new empty cache with responses expiring after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replays the cached response of `route` for the given `key` if one hasn't
+    /// expired, otherwise processes a new response using `process` and caches it.
+    /// Requests without a key are always processed and never cached. Reusing a
+    /// key with a different `request` body gives a 422, and retrying whilst the
+    /// first request is still being processed gives a 409
+    pub fn respond<T: Serialize>(
+        &self,
+        route: &str,
+        key: &IdempotencyKey,
+        request: &impl Hash,
+        process: impl FnOnce() -> ResponseModel<T>,
+    ) -> Idempotent {
+        self.respond_raw(route, key, request, || process().into())
+    }
+
+    /// Replays or processes a response like [IdempotencyCache::respond], but with
+    /// `process` giving an already serialized response such as from
+    /// [Idempotent::raw]
+    pub fn respond_raw(
+        &self,
+        route: &str,
+        key: &IdempotencyKey,
+        request: &impl Hash,
+        process: impl FnOnce() -> Idempotent,
+    ) -> Idempotent {
+        let key = match &key.0 {
+            Some(key) => (route.to_string(), key.clone()),
+            None => return process(),
+        };
+        let request = hash_request(request);
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let ttl = self.ttl;
+
+            entries.retain(|_, cached| cached.cached.elapsed() < ttl);
+            match entries.get(&key) {
+                Some(cached) if cached.request != request => {
+                    return ResponseModel::<()>::basic(
+                        422,
+                        "Idempotency key was already used for a different request",
+                    )
+                    .into()
+                }
+                Some(CachedResponse {
+                    response: Some(response),
+                    ..
+                }) => {
+                    return Idempotent {
+                        replayed: true,
+                        ..response.clone()
+                    }
+                }
+                Some(_) => {
+                    return ResponseModel::<()>::basic(
+                        409,
+                        "A request with this idempotency key is still being processed",
+                    )
+                    .into()
+                }
+                None => (),
+            }
+
+            if entries.len() >= MAX_IDEMPOTENCY_KEYS {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.cached)
+                    .map(|(key, _)| key.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(
+                key.clone(),
+                CachedResponse {
+                    cached: Instant::now(),
+                    request,
+                    response: None,
+                },
+            );
+        }
+
+        let mut in_flight = InFlight {
+            cache: self,
+            key: Some(key),
+        };
+        let response = process();
+
+        self.entries.lock().unwrap().insert(
+            in_flight.key.take().unwrap(),
+            CachedResponse {
+                cached: Instant::now(),
+                request,
+                response: Some(response.clone()),
+            },
+        );
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> IdempotencyKey {
+        IdempotencyKey(Some(key.to_string()))
+    }
+
+    #[test]
+    fn replays_cached() {
+        let cache = IdempotencyCache::default();
+        let mut processed = 0;
+
+        let first = cache.respond("/tv/batch", &key("a"), &["a.mkv"], || {
+            processed += 1;
+            ResponseModel::new(200, "Success", 1)
+        });
+        let second = cache.respond("/tv/batch", &key("a"), &["a.mkv"], || {
+            processed += 1;
+            ResponseModel::new(200, "Success", 2)
+        });
+
+        assert_eq!(processed, 1);
+        assert!(!first.replayed);
+        assert!(second.replayed);
+        assert_eq!(first.body, second.body);
+        assert_eq!(
+            second.body,
+            r#"{"status":200,"msg":"Success","body":1,"schema_version":1}"#
+        );
+    }
+
+    /// Checks if a response for `route` and `key` is replayed from `cache`
+    fn replayed(cache: &IdempotencyCache, route: &str, key: IdempotencyKey) -> bool {
+        cache
+            .respond(route, &key, &(), || ResponseModel::new(200, "Success", 0))
+            .replayed
+    }
+
+    #[test]
+    fn separate_keys() {
+        let cache = IdempotencyCache::default();
+
+        assert!(!replayed(&cache, "/tv/batch", key("a")));
+        assert!(replayed(&cache, "/tv/batch", key("a")));
+        assert!(!replayed(&cache, "/tv/batch", key("b")));
+        assert!(!replayed(&cache, "/tv/season", key("a")));
+        assert!(!replayed(&cache, "/tv/batch", IdempotencyKey(None)));
+        assert!(!replayed(&cache, "/tv/batch", IdempotencyKey(None)));
+    }
+
+    #[test]
+    fn replays_raw() {
+        let cache = IdempotencyCache::default();
+        let respond = || {
+            cache.respond_raw("/tv/batch", &key("a"), &(), || {
+                Idempotent::raw(200, "a,b\r\n".to_string(), ContentType::CSV)
+            })
+        };
+
+        assert!(!respond().replayed);
+
+        let replayed = respond();
+
+        assert!(replayed.replayed);
+        assert_eq!(replayed.content_type, ContentType::CSV);
+        assert_eq!(replayed.body, "a,b\r\n");
+    }
+
+    #[test]
+    fn expired() {
+        let cache = IdempotencyCache::new(Duration::from_secs(0));
+
+        assert!(!replayed(&cache, "/tv/batch", key("a")));
+        assert!(!replayed(&cache, "/tv/batch", key("a")));
+    }
+
+    #[test]
+    fn different_request() {
+        let cache = IdempotencyCache::default();
+        let respond = |names: &[&str]| {
+            cache.respond("/tv/batch", &key("a"), &names, || {
+                ResponseModel::new(200, "Success", 0)
+            })
+        };
+
+        assert_eq!(respond(&["a.mkv"]).status, 200);
+        assert_eq!(respond(&["b.mkv"]).status, 422);
+        assert!(respond(&["a.mkv"]).replayed);
+    }
+
+    #[test]
+    fn in_flight() {
+        let cache = IdempotencyCache::default();
+        let retried = cache.respond("/tv/batch", &key("a"), &(), || {
+            let retry = cache.respond("/tv/batch", &key("a"), &(), || {
+                ResponseModel::new(200, "Success", 1)
+            });
+
+            ResponseModel::new(200, "Success", retry.status)
+        });
+
+        assert_eq!(
+            retried.body,
+            r#"{"status":200,"msg":"Success","body":409,"schema_version":1}"#
+        );
+        assert!(replayed(&cache, "/tv/batch", key("a")));
+    }
+}
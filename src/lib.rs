@@ -0,0 +1,36 @@
+//! Tagging logic behind the tagzen api microservice, containing the tv, movie and
+//! music tagging alongside their routes for mounting
+//!
+//! # Features
+//!
+//! The routes and everything else needing rocket are behind the default `server`
+//! feature, with the routes of each media type kept in its own `routes` module
+//! such as [tv::routes]. Depending on this crate with `default-features = false` gives just
+//! the pure tagging functions, without pulling in rocket or needing nightly.
+//!
+//! These pure functions also build for `wasm32-unknown-unknown`, with the
+//! optional `wasm` feature adding javascript bindings in the `wasm` module.
+
+#![cfg_attr(feature = "server", feature(proc_macro_hygiene, decl_macro))]
+
+#[cfg(feature = "server")]
+#[macro_use]
+extern crate rocket;
+
+#[cfg(feature = "server")]
+pub mod admin;
+#[cfg(feature = "server")]
+pub mod cache;
+#[cfg(feature = "server")]
+pub mod config;
+#[cfg(feature = "server")]
+pub mod idempotency;
+pub mod metrics;
+pub mod movie;
+pub mod music;
+pub mod parse;
+pub mod ready;
+pub mod tv;
+pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
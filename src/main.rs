@@ -6,6 +6,41 @@
 //! will be outputted inside of `target/release` which can be optionally stripped
 //! and ran as a static binary. All help infomation is contained inside of the api
 //! itself, so just visit the index page once it's up and running 👐
+//!
+//! # Configuration
+//!
+//! The quality tags recognised when tagging (e.g. `1080p` or `x264`) may be
+//! overridden by setting the `TAGZEN_QUALITY_TAGS` environment variable to the
+//! path of a newline-delimited file of tags, otherwise the built-in defaults are
+//! used.
+//!
+//! Watermark prefixes of download sites such as the `www.Site.com - ` of
+//! `www.Site.com - Show.S01E02.mkv` are removed before tagging, which may be
+//! overridden by setting `TAGZEN_SITE_PREFIXES` to the path of a
+//! newline-delimited file of regexes matched at the start of each name.
+//!
+//! Recent `/tv/episode` captures are cached, holding up to 1024 by default
+//! which can be changed by setting `TAGZEN_CACHE_SIZE`, with `0` disabling it.
+//! Whole `/tv/batch` results are cached by the list of names sent, holding up to
+//! 64 results for 300 seconds each by default, which can be changed by setting
+//! `TAGZEN_BATCH_CACHE_SIZE` and `TAGZEN_BATCH_CACHE_TTL` (in seconds).
+//!
+//! Setting `TAGZEN_ADMIN_TOKEN` enables the `POST /admin/shutdown` endpoint,
+//! which requires this token in the `Authorization` header to exit the running
+//! instance shortly after responding. This isn't graceful, so any requests
+//! still being tagged are cut off.
+//!
+//! Setting `TAGZEN_DEFAULT_EXT` (e.g. `.mkv`) gives names without an extension
+//! a default one on `/tv/episode`, `/movie/film` and `/music/song`, and
+//! `TAGZEN_BATCH_LIMIT` sets the page size of `/tv/batch`.
+//!
+//! Names longer than 1024 characters are rejected with a 400 before tagging so
+//! no regex is ran over huge inputs, which can be changed by setting
+//! `TAGZEN_MAX_NAME_LEN`.
+//!
+//! All of these are read once at startup. Query args sent with a request always
+//! take precedence over environment variables, which in turn take precedence
+//! over the built-in defaults.
 
 #![feature(proc_macro_hygiene, decl_macro)]
 
@@ -15,30 +50,93 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[macro_use]
 extern crate rocket;
 
-mod music;
-mod tv;
-mod utils;
+use std::time::Duration;
+use tagzen::{admin, cache, config, idempotency, metrics, movie, music, parse, ready, tv, utils};
 
 #[get("/")]
 fn index() -> String {
-    format!("ROUTE /\n\n\nAbout\n    Microservice api for tagging television shows and movies for use publically\n    for free, forever. Created by https://ogriffiths.com. Help is available for\n    each route and endpoint on GET access. Running on v{} currently with the\n    foss repository contained inside of https://github.com/owez/tagzen/.\n\n\nChild routes/endpoints\n    - /tv: Television show tagging, allowing single episode or seasonal tagging\n    - /music: Music tagging for single songs or albums", VERSION)
+    format!("ROUTE /\n\n\nAbout\n    Microservice api for tagging television shows and movies for use publically\n    for free, forever. Created by https://ogriffiths.com. Help is available for\n    each route and endpoint on GET access. Running on v{} currently with the\n    foss repository contained inside of https://github.com/owez/tagzen/. Every\n    json response includes a `schema_version`, bumped on breaking field changes.\n\n\nChild routes/endpoints\n    - /tv: Television show tagging, allowing single episode or seasonal tagging\n    - /movie: Movie tagging, detecting release years and editions\n    - /music: Music tagging for single songs or albums\n    - /parse: Mixed media tagging, detecting if names are tv, movies or music\n    - /metrics: Prometheus-style request and parse counters\n    - /ready: Readiness check, responding with a 503 if tagging is broken\n    - /ext: Extension of a single name", VERSION)
 }
 
 fn main() {
+    once_cell::sync::Lazy::force(&utils::QUALITY_TAGS);
+    let config = config::AppConfig::from_env().expect("Could not load config");
+    utils::SITE_PREFIXES
+        .set(
+            utils::compile_site_prefixes(&config.site_prefixes)
+                .expect("Could not load site prefixes"),
+        )
+        .expect("Site prefixes were already loaded");
+
     rocket::ignite()
         .attach(rocket_contrib::helmet::SpaceHelmet::default())
+        .attach(metrics::RequestCounter)
+        .manage(metrics::Metrics::default())
+        .manage(idempotency::IdempotencyCache::default())
+        .manage(cache::CaptureCache::new(config.cache_size))
+        .manage(cache::BatchCache::new(
+            config.batch_cache_size,
+            Duration::from_secs(config.batch_cache_ttl as u64),
+        ))
+        .manage(config)
+        .register(catchers![utils::bad_request])
         .mount(
             "/",
             routes![
                 index,
-                tv::help,
-                tv::episode_help,
-                tv::episode,
-                tv::season_help,
-                tv::season,
-                music::help,
-                music::song_help,
-                music::song
+                metrics::metrics,
+                ready::ready,
+                admin::shutdown_help,
+                admin::shutdown,
+                utils::ext_help,
+                utils::ext,
+                parse::parse_help,
+                parse::parse_names,
+                tv::routes::help,
+                tv::routes::episode_help,
+                tv::routes::episode,
+                tv::routes::season_help,
+                tv::routes::season,
+                tv::routes::batch_help,
+                tv::routes::batch,
+                tv::routes::group_help,
+                tv::routes::group,
+                tv::routes::dedup_help,
+                tv::routes::dedup,
+                tv::routes::completeness_help,
+                tv::routes::completeness_check,
+                tv::routes::summary_help,
+                tv::routes::summary,
+                tv::routes::match_help,
+                tv::routes::match_titles,
+                tv::routes::inspect_help,
+                tv::routes::inspect,
+                tv::routes::build_help,
+                tv::routes::build,
+                tv::routes::reparse_help,
+                tv::routes::reparse,
+                tv::routes::se_help,
+                tv::routes::se,
+                tv::routes::stream_help,
+                tv::routes::stream,
+                tv::routes::rename_plan_help,
+                tv::routes::rename_plan,
+                tv::routes::plex_help,
+                tv::routes::plex,
+                tv::routes::lint_help,
+                tv::routes::lint,
+                movie::routes::help,
+                movie::routes::film_help,
+                movie::routes::film,
+                music::routes::help,
+                music::routes::song_help,
+                music::routes::song,
+                music::routes::album_help,
+                music::routes::album,
+                music::routes::path_help,
+                music::routes::path,
+                music::routes::normalize_help,
+                music::routes::normalize
             ],
         )
         .launch();
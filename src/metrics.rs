@@ -0,0 +1,332 @@
+//! Request and parse counters exposed in the Prometheus text exposition format
+
+use crate::tv::CaptureError;
+#[cfg(feature = "server")]
+use rocket::fairing::{Fairing, Info, Kind};
+#[cfg(feature = "server")]
+use rocket::request::Request;
+#[cfg(feature = "server")]
+use rocket::response::Response;
+#[cfg(feature = "server")]
+use rocket::State;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+/// Upper bounds of the batch size histogram buckets, with an implicit `+Inf`
+/// bucket after the last
+const BATCH_SIZE_BUCKETS: [usize; 7] = [1, 5, 10, 50, 100, 500, 1000];
+
+/// Counters shared between all routes, managed by rocket and attached as a
+/// fairing to count requests per route
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Total requests keyed by method and route path
+    requests: RwLock<BTreeMap<(String, String), AtomicUsize>>,
+
+    /// Total successfully captured files
+    parse_successes: AtomicUsize,
+
+    /// Total failed captures from [CaptureError::NoSeasonRegex]
+    no_season_regex: AtomicUsize,
+
+    /// Total failed captures from [CaptureError::NoEpisodeRegex]
+    no_episode_regex: AtomicUsize,
+
+    /// Total failed captures from [CaptureError::OutOfRange]
+    out_of_range: AtomicUsize,
+
+    /// Total failed captures from [CaptureError::TooLong]
+    too_long: AtomicUsize,
+
+    /// Total captures served from the [CaptureCache](crate::cache::CaptureCache)
+    cache_hits: AtomicUsize,
+
+    /// Total captures not found in the [CaptureCache](crate::cache::CaptureCache)
+    cache_misses: AtomicUsize,
+
+    /// Total batches served from the [BatchCache](crate::cache::BatchCache)
+    batch_cache_hits: AtomicUsize,
+
+    /// Total batches not found in the [BatchCache](crate::cache::BatchCache)
+    batch_cache_misses: AtomicUsize,
+
+    /// Non-cumulative counts per bucket of [BATCH_SIZE_BUCKETS] plus `+Inf`
+    batch_buckets: [AtomicUsize; BATCH_SIZE_BUCKETS.len() + 1],
+
+    /// Sum of all recorded batch sizes
+    batch_sum: AtomicUsize,
+
+    /// Total recorded batches
+    batch_count: AtomicUsize,
+}
+
+impl Metrics {
+    /// Counts a single request to the route found by its `method` and `path`
+    pub fn record_request(&self, method: &str, path: &str) {
+        let key = (method.to_string(), path.to_string());
+
+        if let Some(count) = self.requests.read().unwrap().get(&key) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        self.requests
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts `successes` captured files, alongside a failure if `error` is given
+    pub fn record_parses(&self, successes: usize, error: Option<&CaptureError>) {
+        self.parse_successes.fetch_add(successes, Ordering::Relaxed);
+
+        match error {
+            Some(CaptureError::NoSeasonRegex) => {
+                self.no_season_regex.fetch_add(1, Ordering::Relaxed)
+            }
+            Some(CaptureError::NoEpisodeRegex) => {
+                self.no_episode_regex.fetch_add(1, Ordering::Relaxed)
+            }
+            Some(CaptureError::OutOfRange { .. }) => {
+                self.out_of_range.fetch_add(1, Ordering::Relaxed)
+            }
+            Some(CaptureError::TooLong(_)) => self.too_long.fetch_add(1, Ordering::Relaxed),
+            None => 0,
+        };
+    }
+
+    /// Counts a single capture result, see [Metrics::record_parses]
+    pub fn record_parse<T>(&self, result: &Result<T, CaptureError>) {
+        match result {
+            Ok(_) => self.record_parses(1, None),
+            Err(err) => self.record_parses(0, Some(err)),
+        }
+    }
+
+    /// Counts a single capture cache lookup, which was a `hit` or a miss
+    pub fn record_cache(&self, hit: bool) {
+        if hit {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts a single whole batch cache lookup, which was a `hit` or a miss
+    pub fn record_batch_cache(&self, hit: bool) {
+        if hit {
+            self.batch_cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.batch_cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds a batch of `size` files to the batch size histogram
+    pub fn record_batch(&self, size: usize) {
+        let bucket = BATCH_SIZE_BUCKETS
+            .iter()
+            .position(|bound| size <= *bound)
+            .unwrap_or(BATCH_SIZE_BUCKETS.len());
+
+        self.batch_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.batch_sum.fetch_add(size, Ordering::Relaxed);
+        self.batch_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders all counters in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP tagzen_requests_total Total requests handled per route\n");
+        out.push_str("# TYPE tagzen_requests_total counter\n");
+        for ((method, path), count) in self.requests.read().unwrap().iter() {
+            writeln!(
+                out,
+                "tagzen_requests_total{{method=\"{}\",route=\"{}\"}} {}",
+                method,
+                path,
+                count.load(Ordering::Relaxed)
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP tagzen_parse_successes_total Total successfully captured files\n");
+        out.push_str("# TYPE tagzen_parse_successes_total counter\n");
+        writeln!(
+            out,
+            "tagzen_parse_successes_total {}",
+            self.parse_successes.load(Ordering::Relaxed)
+        )
+        .unwrap();
+
+        out.push_str("# HELP tagzen_parse_failures_total Total failed captures per error\n");
+        out.push_str("# TYPE tagzen_parse_failures_total counter\n");
+        for (error, count) in &[
+            ("no_season_regex", &self.no_season_regex),
+            ("no_episode_regex", &self.no_episode_regex),
+            ("out_of_range", &self.out_of_range),
+            ("too_long", &self.too_long),
+        ] {
+            writeln!(
+                out,
+                "tagzen_parse_failures_total{{error=\"{}\"}} {}",
+                error,
+                count.load(Ordering::Relaxed)
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP tagzen_capture_cache_total Capture cache lookups per result\n");
+        out.push_str("# TYPE tagzen_capture_cache_total counter\n");
+        for (result, count) in &[("hit", &self.cache_hits), ("miss", &self.cache_misses)] {
+            writeln!(
+                out,
+                "tagzen_capture_cache_total{{result=\"{}\"}} {}",
+                result,
+                count.load(Ordering::Relaxed)
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP tagzen_batch_cache_total Whole batch cache lookups per result\n");
+        out.push_str("# TYPE tagzen_batch_cache_total counter\n");
+        for (result, count) in &[
+            ("hit", &self.batch_cache_hits),
+            ("miss", &self.batch_cache_misses),
+        ] {
+            writeln!(
+                out,
+                "tagzen_batch_cache_total{{result=\"{}\"}} {}",
+                result,
+                count.load(Ordering::Relaxed)
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP tagzen_batch_size Number of files per batch request\n");
+        out.push_str("# TYPE tagzen_batch_size histogram\n");
+        let mut cumulative = 0;
+        for (ind, count) in self.batch_buckets.iter().enumerate() {
+            cumulative += count.load(Ordering::Relaxed);
+            let bound = BATCH_SIZE_BUCKETS
+                .get(ind)
+                .map(|bound| bound.to_string())
+                .unwrap_or_else(|| "+Inf".to_string());
+
+            writeln!(
+                out,
+                "tagzen_batch_size_bucket{{le=\"{}\"}} {}",
+                bound, cumulative
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "tagzen_batch_size_sum {}",
+            self.batch_sum.load(Ordering::Relaxed)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "tagzen_batch_size_count {}",
+            self.batch_count.load(Ordering::Relaxed)
+        )
+        .unwrap();
+
+        out
+    }
+}
+
+/// Fairing counting every request into the managed [Metrics] by its route
+#[cfg(feature = "server")]
+#[derive(Debug, Default)]
+pub struct RequestCounter;
+
+#[cfg(feature = "server")]
+impl Fairing for RequestCounter {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request counter",
+            kind: Kind::Response,
+        }
+    }
+
+    fn on_response(&self, request: &Request, _: &mut Response) {
+        if let Some(metrics) = request.guard::<State<Metrics>>().succeeded() {
+            match request.route() {
+                Some(route) => metrics.record_request(route.method.as_str(), route.uri.path()),
+                None => metrics.record_request(request.method().as_str(), "unmatched"),
+            }
+        }
+    }
+}
+
+/// Gives all counters in the Prometheus text exposition format
+#[cfg(feature = "server")]
+#[get("/metrics")]
+pub fn metrics(metrics: State<Metrics>) -> String {
+    metrics.render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_counters() {
+        let metrics = Metrics::default();
+
+        metrics.record_request("POST", "/tv/episode");
+        metrics.record_request("POST", "/tv/episode");
+        metrics.record_parse::<()>(&Ok(()));
+        metrics.record_parse::<()>(&Err(CaptureError::NoSeasonRegex));
+        metrics.record_parses(3, Some(&CaptureError::NoEpisodeRegex));
+
+        let out = metrics.render();
+
+        assert!(out.contains("tagzen_requests_total{method=\"POST\",route=\"/tv/episode\"} 2\n"));
+        assert!(out.contains("tagzen_parse_successes_total 4\n"));
+        assert!(out.contains("tagzen_parse_failures_total{error=\"no_season_regex\"} 1\n"));
+        assert!(out.contains("tagzen_parse_failures_total{error=\"no_episode_regex\"} 1\n"));
+    }
+
+    #[test]
+    fn cache_counters() {
+        let metrics = Metrics::default();
+
+        metrics.record_cache(true);
+        metrics.record_cache(false);
+        metrics.record_cache(false);
+        metrics.record_batch_cache(true);
+
+        let out = metrics.render();
+
+        assert!(out.contains("tagzen_capture_cache_total{result=\"hit\"} 1\n"));
+        assert!(out.contains("tagzen_capture_cache_total{result=\"miss\"} 2\n"));
+        assert!(out.contains("tagzen_batch_cache_total{result=\"hit\"} 1\n"));
+        assert!(out.contains("tagzen_batch_cache_total{result=\"miss\"} 0\n"));
+    }
+
+    #[test]
+    fn batch_histogram() {
+        let metrics = Metrics::default();
+
+        metrics.record_batch(1);
+        metrics.record_batch(7);
+        metrics.record_batch(5000);
+
+        let out = metrics.render();
+
+        assert!(out.contains("tagzen_batch_size_bucket{le=\"1\"} 1\n"));
+        assert!(out.contains("tagzen_batch_size_bucket{le=\"5\"} 1\n"));
+        assert!(out.contains("tagzen_batch_size_bucket{le=\"10\"} 2\n"));
+        assert!(out.contains("tagzen_batch_size_bucket{le=\"1000\"} 2\n"));
+        assert!(out.contains("tagzen_batch_size_bucket{le=\"+Inf\"} 3\n"));
+        assert!(out.contains("tagzen_batch_size_sum 5008\n"));
+        assert!(out.contains("tagzen_batch_size_count 3\n"));
+    }
+}
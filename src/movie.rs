@@ -0,0 +1,226 @@
+//! Movie tagging for title and year based tagging, alongside any edition
+
+/// Regex for capturing known movie editions, case insensitive
+const EDITION_REGEX: &str = r"(?i)\b(director'?s[ ._-]cut|extended([ ._-](cut|edition))?|unrated|theatrical([ ._-](cut|edition))?|remastered)\b";
+
+/// Every built-in regex of this module, compiled by [ready](crate::ready) to
+/// check that none are broken
+pub(crate) const REGEXES: &[&str] = &[EDITION_REGEX];
+
+use crate::utils::{
+    cap_disc, cap_filename_ext, cap_tags, cap_year, clean_input, format_name, strip_site_prefix,
+};
+
+use regex::Regex;
+use serde::Serialize;
+
+/// Attempts to capture an edition of a given `filename` using regex, returning
+/// the start of the match alongside the canonical edition name
+fn cap_edition(filename: &str) -> Option<(usize, String)> {
+    let found = Regex::new(EDITION_REGEX)
+        .expect("Could not make edition regex")
+        .find(filename)?;
+    let lower = found.as_str().to_lowercase();
+
+    let edition = if lower.starts_with("director") {
+        "Director's Cut"
+    } else if lower.starts_with("extended") {
+        "Extended"
+    } else if lower.starts_with("unrated") {
+        "Unrated"
+    } else if lower.starts_with("theatrical") {
+        "Theatrical"
+    } else {
+        "Remastered"
+    };
+
+    Some((found.start(), edition.to_string()))
+}
+
+/// A single movie that is pretty printed for tagging, used in the
+/// [film](routes::film) path
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Movie {
+    /// Original, pre-parsed file_path provided
+    pub file_path: String,
+
+    /// Human readable title of the movie, without the [Movie::year],
+    /// [Movie::edition] or any quality tags
+    pub title: String,
+
+    /// Optional release year of the movie
+    pub year: Option<usize>,
+
+    /// Optional edition of the movie such as `Extended` or `Director's Cut`
+    pub edition: Option<String>,
+
+    /// Optional file extension found from parsing [Movie::file_path]
+    pub ext: Option<String>,
+
+    /// Quality tags found in the filename such as `1080p` or `x264`
+    pub tags: Vec<String>,
+
+    /// Optional part number of movies split over multiple files, e.g. the `1`
+    /// in `Movie.CD1.avi`, see [cap_disc]
+    pub part: Option<usize>,
+
+    /// Completed/rendered name, using [Movie::title] alongside [Movie::year] and
+    /// [Movie::edition] like `Title (2019) - Extended`, suffixed by any
+    /// [Movie::part] like `Title (2019) - Part 1`
+    pub render: String,
+}
+
+impl Movie {
+    /// Creates a new [Movie] from a given `file_path`, which is cleaned up using
+    /// [clean_input] first
+    pub fn new(file_path: String) -> Self {
+        let file_path = clean_input(file_path);
+        let (filename, ext) = cap_filename_ext(&file_path);
+        let filename = strip_site_prefix(filename);
+        let (filename, part, _) = cap_disc(filename);
+
+        let year = cap_year(&filename);
+        let edition = cap_edition(&filename);
+        let tags = cap_tags(&filename);
+
+        let cut = year
+            .and_then(|year| filename.find(&year.to_string()))
+            .into_iter()
+            .chain(edition.as_ref().map(|(start, _)| *start))
+            .chain(tags.first().and_then(|tag| filename.find(tag.as_str())))
+            .min()
+            .unwrap_or_else(|| filename.len());
+        let title = format_name(filename[..cut].trim_end_matches(|c| "([{ ._-".contains(c)));
+        let edition = edition.map(|(_, edition)| edition);
+
+        let mut render = title.clone();
+        if let Some(year) = year {
+            render.push_str(&format!(" ({})", year));
+        }
+        if let Some(edition) = &edition {
+            render.push_str(&format!(" - {}", edition));
+        }
+        if let Some(part) = part {
+            render.push_str(&format!(" - Part {}", part));
+        }
+
+        Self {
+            file_path,
+            title,
+            year,
+            edition,
+            ext,
+            tags,
+            part,
+            render,
+        }
+    }
+}
+
+/// Rocket routes for tagging movies, alongside their help
+#[cfg(feature = "server")]
+pub mod routes {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::utils::{check_name_len, ext_or_default, parse_case, ResponseModel};
+    use rocket::State;
+
+    /// Gives help by providing available endpoints (to [film])
+    #[get("/movie")]
+    pub fn help() -> &'static str {
+        "ROUTE /movie\n\n\nAbout\n    Allows tagging of movies with a title + year methodology of tagging, also\n    detecting editions such as `Extended` or `Director's Cut`.\n\n\nChild routes/endpoints\n    - /film: Tags a single movie file"
+    }
+
+    /// Gives help for how to use the [film] path
+    #[get("/movie/film")]
+    pub fn film_help() -> &'static str {
+        "ENDPOINT POST /movie/film?<name>&<case>&<default_ext>&<echo>\n\n\nAbout\n    Tags a single movie by it's required `name` into the title, release year and\n    edition, rendering them like `Title (2019) - Extended`. The render may be\n    recased using `case` set to either `kebab`, `snake`, `title` or `original`\n    (default). Movies split over multiple files such as `Movie.CD1.avi` give a\n    `part` number, rendered like `Title - Part 1`. Names\n    without an extension may be given a `default_ext` such as `.mkv`, which\n    never overrides an existing one. Passing `echo=true` gives the query args\n    sent back in an `input` object."
+    }
+
+    /// Tags a single movie file into a title, year and edition
+    #[post("/movie/film?<name>&<case>&<default_ext>&<echo>")]
+    pub fn film(
+        name: String,
+        case: Option<String>,
+        default_ext: Option<String>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Movie> {
+        let input = serde_json::json!({ "name": name, "case": case, "default_ext": default_ext });
+        if let Err(err) = check_name_len(&name, Some(config.max_name_len)) {
+            return ResponseModel::basic(400, err);
+        }
+        let case = match parse_case(case) {
+            Ok(case) => case,
+            Err(err) => return ResponseModel::basic(400, err),
+        };
+
+        let mut movie = Movie::new(name);
+        movie.render = case.apply(&movie.render);
+        movie.ext = ext_or_default(
+            movie.ext,
+            default_ext
+                .as_deref()
+                .or_else(|| config.default_ext.as_deref()),
+        );
+
+        ResponseModel::new(200, "Success", movie).echo(echo.unwrap_or(false), &input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edition_variants() {
+        let movies = [
+            ("Movie.2019.Directors.Cut.mkv", "Director's Cut"),
+            ("Movie (2019) [Extended].mkv", "Extended"),
+            ("Movie 2019 Unrated 1080p.mkv", "Unrated"),
+            ("Movie.2019.Theatrical.Cut.mkv", "Theatrical"),
+            ("Movie (2019) Remastered.mkv", "Remastered"),
+            ("Movie 2019 Director's Cut.mkv", "Director's Cut"),
+        ];
+
+        for (name, edition) in movies.iter() {
+            let movie = Movie::new(name.to_string());
+
+            assert_eq!(movie.title, "Movie");
+            assert_eq!(movie.year, Some(2019));
+            assert_eq!(movie.edition, Some(edition.to_string()));
+            assert_eq!(movie.render, format!("Movie (2019) - {}", edition));
+        }
+    }
+
+    #[test]
+    fn edition_none() {
+        let movie = Movie::new("The.Big.Movie.2019.1080p.BluRay.x264.mkv".to_string());
+
+        assert_eq!(movie.title, "The Big Movie");
+        assert_eq!(movie.year, Some(2019));
+        assert_eq!(movie.edition, None);
+        assert_eq!(movie.ext, Some(".mkv".to_string()));
+        assert_eq!(movie.render, "The Big Movie (2019)");
+    }
+
+    #[test]
+    fn parts() {
+        let movie = Movie::new("Movie.CD1.avi".to_string());
+
+        assert_eq!(movie.part, Some(1));
+        assert_eq!(movie.title, "Movie");
+        assert_eq!(movie.render, "Movie - Part 1");
+
+        let movie = Movie::new("The.Movie.2004.CD2.XviD.avi".to_string());
+
+        assert_eq!(movie.part, Some(2));
+        assert_eq!(movie.title, "The Movie");
+        assert_eq!(movie.render, "The Movie (2004) - Part 2");
+
+        let movie = Movie::new("Movie (2019).mkv".to_string());
+
+        assert_eq!(movie.part, None);
+        assert_eq!(movie.render, "Movie (2019)");
+    }
+}
@@ -1,10 +1,181 @@
 //! Music file tagging for an artist and song approch, favouring em dashes
 
-use crate::utils::{cap_filename_ext, format_name, ResponseModel};
+use crate::tv::split_basename;
+use crate::utils::{
+    cap_disc, cap_filename_ext, check_name_len, clean_input, ext_or_default, format_name,
+    format_name_with, render_template, DashMode, NameTooLong, TemplateError, TemplateValue,
+};
 
-use serde::Serialize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-/// A single song that is pretty printed for tagging, used in the [song] path
+/// Separators which may already be present inside of a filename between the
+/// artist and song name, in order of precedence
+const ARTIST_SEPARATORS: [&str; 2] = [" — ", " - "];
+
+/// Splits an existing artist from the song name of a `filename` if it contains
+/// one of the [ARTIST_SEPARATORS], returning the artist and song name in order
+fn split_artist(filename: &str) -> Option<(String, String)> {
+    ARTIST_SEPARATORS.iter().find_map(|sep| {
+        let mut split = filename.splitn(2, sep);
+        let artist = split.next()?.trim();
+        let name = split.next()?.trim();
+
+        if artist.is_empty() || name.is_empty() {
+            None
+        } else {
+            Some((artist.to_string(), name.to_string()))
+        }
+    })
+}
+
+/// Regex for capturing a leading track number such as `01 - `, `3. ` or `01 `,
+/// requiring either a separator or zero padding so `99 Problems` isn't a track
+const TRACK_REGEX: &str = r"^\s*([0-9]{1,3}\s*[.\-_]|0[0-9]{1,2}\s)\s*";
+
+/// Attempts to capture a leading track number from a `filename`, returning the
+/// filename without it alongside the track number found
+fn cap_track(filename: String) -> (String, Option<usize>) {
+    let captures = Regex::new(TRACK_REGEX)
+        .expect("Could not make track regex")
+        .captures(&filename);
+
+    match captures {
+        Some(captures) if captures.get(0).unwrap().end() < filename.len() => (
+            filename[captures.get(0).unwrap().end()..].to_string(),
+            Some(
+                captures[1]
+                    .trim_end_matches(|c: char| !c.is_ascii_digit())
+                    .parse()
+                    .unwrap(),
+            ),
+        ),
+        _ => (filename, None),
+    }
+}
+
+/// Regex for capturing bracketed remix info such as `(Someone Remix)` or
+/// `[Radio Edit]`, which must end in remix, mix, edit or bootleg
+const REMIX_REGEX: &str = r"(?i)\s*[\[(]([^\[\]()]*\b(?:remix|mix|edit|bootleg))\s*[\])]";
+
+/// Attempts to capture bracketed remix info from a `filename`, returning the
+/// filename without it alongside the remix info found, see [REMIX_REGEX]
+fn cap_remix(filename: String) -> (String, Option<String>) {
+    let captures = match Regex::new(REMIX_REGEX)
+        .expect("Could not make remix regex")
+        .captures(&filename)
+    {
+        Some(captures) => captures,
+        None => return (filename, None),
+    };
+    let found = captures.get(0).unwrap();
+    let rest = format!("{}{}", &filename[..found.start()], &filename[found.end()..]);
+
+    (
+        rest.trim().to_string(),
+        Some(captures[1].trim().to_string()),
+    )
+}
+
+/// Regex for capturing a bracketed tempo used by DJs such as `(128 BPM)` or
+/// `[90bpm]`, case insensitive
+const BPM_REGEX: &str = r"(?i)\s*[\[(]\s*([0-9]{2,3})\s*bpm\s*[\])]";
+
+/// Regex for capturing a bracketed musical key used by DJs such as `(Am)`,
+/// `(F#)` or `[Bbm]`, which is case sensitive to avoid matching words like `(a)`
+const KEY_REGEX: &str = r"\s*[\[(]\s*([A-G][#b]?m?)\s*[\])]";
+
+/// Removes the first match of a `regex` from a `filename`, returning the
+/// filename without it alongside the first capture group
+fn cap_bracketed(filename: String, regex: &str) -> (String, Option<String>) {
+    let captures = match Regex::new(regex)
+        .expect("Could not make bracketed regex")
+        .captures(&filename)
+    {
+        Some(captures) => captures,
+        None => return (filename, None),
+    };
+    let found = captures.get(0).unwrap();
+    let rest = format!("{}{}", &filename[..found.start()], &filename[found.end()..]);
+
+    (rest.trim().to_string(), Some(captures[1].to_string()))
+}
+
+/// Attempts to capture the tempo and musical key DJs tag tracks with from a
+/// `filename`, such as `Track (128 BPM) (Am)`, returning the filename without
+/// them alongside the bpm and key found, see [BPM_REGEX] and [KEY_REGEX]
+fn cap_dj(filename: String) -> (String, Option<u16>, Option<String>) {
+    let (filename, bpm) = cap_bracketed(filename, BPM_REGEX);
+    let (filename, key) = cap_bracketed(filename, KEY_REGEX);
+
+    (filename, bpm.and_then(|bpm| bpm.parse().ok()), key)
+}
+
+/// Regex for splitting multiple artists apart, case insensitive
+const ARTISTS_SPLIT_REGEX: &str = r"(?i)\s*(,|&|\band\b)\s*";
+
+/// Every built-in regex of this module, compiled by [ready](crate::ready) to
+/// check that none are broken
+pub(crate) const REGEXES: &[&str] = &[
+    TRACK_REGEX,
+    REMIX_REGEX,
+    BPM_REGEX,
+    KEY_REGEX,
+    ARTISTS_SPLIT_REGEX,
+];
+
+/// Splits a single `artist` into multiple artists on commas, ampersands and the
+/// word "and", e.g. `A, B & C` becomes `A`, `B` and `C`
+fn split_artists(artist: &str) -> Vec<String> {
+    Regex::new(ARTISTS_SPLIT_REGEX)
+        .expect("Could not make artists split regex")
+        .split(artist)
+        .map(str::trim)
+        .filter(|artist| !artist.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Joins multiple `artists` for rendering, using commas and an ampersand before
+/// the last artist, e.g. `A, B & C`
+fn join_artists(artists: &[String]) -> String {
+    match artists.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} & {}", rest.join(", "), last),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
+
+/// Optional behaviours for creating a [SingleSong], all of which are disabled by
+/// default
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SongOptions {
+    /// Splits the artist into multiple [SingleSong::artists] on commas,
+    /// ampersands and "and". This is opt-in as some band names legitimately
+    /// contain these, such as "Hall & Oates"
+    pub split_artists: bool,
+
+    /// Keeps dashes flanked by spaces inside of the song name, so `Title - Live`
+    /// isn't collapsed into `Title Live`, see [DashMode::Preserve]
+    pub preserve_dashes: bool,
+
+    /// Extension assumed for names without one, see [ext_or_default]
+    pub default_ext: Option<String>,
+
+    /// Captures the [SingleSong::bpm] and [SingleSong::key] DJs tag tracks with,
+    /// such as `Track (128 BPM) (Am)`. This is opt-in as normal songs may have
+    /// bracketed parts which look like keys, such as `(A)`
+    pub dj: bool,
+
+    /// Maximum length in characters of names, over which they're rejected
+    /// before any regex is ran, defaulting to
+    /// [DEFAULT_MAX_NAME_LEN](crate::utils::DEFAULT_MAX_NAME_LEN), see
+    /// [check_name_len]
+    pub max_len: Option<usize>,
+}
+
+/// A single song that is pretty printed for tagging, used in the
+/// [song](routes::song) path
 #[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct SingleSong {
     /// Original file path of this song before being formatted
@@ -18,78 +189,918 @@ pub struct SingleSong {
     render: String,
 
     /// File extension of the song file (if any), stemming from [Song::file_path]
+    /// with its original case kept for case-sensitive filesystems
     ext: Option<String>,
 
-    /// Optional artist if found. If not, this will default to "unknown" artist
+    /// Lowercase version of [SingleSong::ext] such as `.flac` for `.FLAC`, for
+    /// comparing extensions consistently
+    ext_normalized: Option<String>,
+
+    /// Optional artist if found. If not, this will default to "unknown" artist.
+    /// When multiple artists are found, this is the first of [Song::artists]
     artist: Option<String>,
 
+    /// All artists found, which is either empty, just [Song::artist] or many
+    /// artists if [SongOptions::split_artists] was used
+    artists: Vec<String>,
+
     /// Optional album name if found. If not, it will be removed from [Song::render]
     /// all together
     album: Option<String>,
+
+    /// Optional track number found at the start of the filename, e.g. the `1` in
+    /// `01 - Title.mp3`
+    track: Option<usize>,
+
+    /// Optional disc number found in the filename, e.g. the `2` in `CD2` or the
+    /// `1` in `Disc 1 of 2`
+    disc: Option<usize>,
+
+    /// Optional total number of discs found alongside [SingleSong::disc], e.g.
+    /// the `2` in `Disc 1 of 2`
+    disc_total: Option<usize>,
+
+    /// Optional remix info found in brackets, e.g. the `Someone Remix` in
+    /// `Title (Someone Remix).mp3`, which is removed from [SingleSong::name] but
+    /// kept in [SingleSong::render]
+    remix: Option<String>,
+
+    /// Optional tempo found in brackets when [SongOptions::dj] is used, e.g. the
+    /// `128` in `Track (128 BPM).mp3`, which is removed from [SingleSong::name]
+    bpm: Option<u16>,
+
+    /// Optional musical key found in brackets when [SongOptions::dj] is used,
+    /// e.g. the `Am` in `Track (Am).mp3`, which is removed from [SingleSong::name]
+    key: Option<String>,
 }
 
 impl SingleSong {
     /// Creates a new [Song] from given arguments. The `artist` and/or `albumn`
     /// will overwrite their respective positions in the [Song] structure
+    ///
+    /// If no `artist` is given but the filename already contains an artist
+    /// separated by an em dash or hyphen, e.g. `Artist — Title.mp3`, then the
+    /// artist will be taken from the filename instead. The filepath is cleaned up
+    /// using [clean_input] first, giving an error if it's longer than
+    /// [SongOptions::max_len]
     pub fn new(
         file_path: impl AsRef<str>,
         artist: impl Into<Option<String>>,
         album: impl Into<Option<String>>,
-    ) -> Self {
-        let artist = artist.into();
+        options: &SongOptions,
+    ) -> Result<Self, NameTooLong> {
+        check_name_len(file_path.as_ref(), options.max_len)?;
         let album = album.into();
 
-        let (filename, ext) = cap_filename_ext(file_path.as_ref());
+        let file_path = clean_input(file_path);
+        let (filename, ext) = cap_filename_ext(&file_path);
+        let ext = ext_or_default(ext, options.default_ext.as_deref());
+        let (filename, disc, disc_total) = cap_disc(filename);
+        let (filename, track) = cap_track(filename);
+        let (filename, bpm, key) = if options.dj {
+            cap_dj(filename)
+        } else {
+            (filename, None, None)
+        };
+        let (filename, remix) = cap_remix(filename);
+        let (artist, filename) = match artist.into() {
+            Some(artist) => (Some(artist), filename),
+            None => match split_artist(&filename) {
+                Some((artist, name)) => (Some(artist), name),
+                None => (None, filename),
+            },
+        };
 
-        let name = format_name(filename);
+        let artists = match &artist {
+            Some(artist) if options.split_artists => split_artists(artist),
+            Some(artist) => vec![artist.clone()],
+            None => Vec::new(),
+        };
+        let artist = artists.first().cloned();
 
-        let render = format!(
-            "{}{} — {}",
-            match &artist {
-                Some(a) => format_name(a),
-                None => "Unknown artist".to_string(),
+        let name = format_name_with(
+            filename,
+            if options.preserve_dashes {
+                DashMode::Preserve
+            } else {
+                DashMode::Collapse
             },
-            match &album {
-                Some(album) => if album != &name {
-                    format!(" — {}", format_name(album))
-                } else {
-                    String::new()
-                },
-                None => String::new(),
-            },
-            name
         );
 
-        Self {
-            file_path: file_path.as_ref().to_string(),
+        let mut segments = vec![if artists.is_empty() {
+            "Unknown artist".to_string()
+        } else {
+            join_artists(&artists.iter().map(format_name).collect::<Vec<_>>())
+        }];
+        segments.extend(album.as_ref().map(format_name));
+        segments.push(match &remix {
+            Some(remix) => format!("{} ({})", name, remix),
+            None => name.clone(),
+        });
+        segments.retain(|segment| !segment.trim().is_empty());
+        segments.dedup_by(|a, b| a.trim().to_lowercase() == b.trim().to_lowercase());
+
+        let render = segments
+            .iter()
+            .map(|segment| segment.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join(" — ");
+
+        Ok(Self {
+            file_path,
             name,
             render,
+            ext_normalized: ext.as_ref().map(|ext| ext.to_lowercase()),
             ext,
             artist,
+            artists,
             album,
-        }
+            track,
+            disc,
+            disc_total,
+            remix,
+            bpm,
+            key,
+        })
+    }
+
+    /// Creates a new [SingleSong] from a `file_path` inside of an `Artist/Album`
+    /// directory structure such as `Artist/Album/01 Track.mp3`, taking the
+    /// artist from the grandparent directory and the album from the parent
+    /// directory unless given explicitly. Only the basename is tagged as a
+    /// song, see [SingleSong::new]
+    pub fn from_path(
+        file_path: impl AsRef<str>,
+        artist: Option<String>,
+        album: Option<String>,
+        options: &SongOptions,
+    ) -> Result<Self, NameTooLong> {
+        check_name_len(file_path.as_ref(), options.max_len)?;
+        let file_path = clean_input(file_path);
+        let (dirs, basename) = split_basename(&file_path);
+        let mut parents = dirs.iter().rev().map(|dir| dir.trim().to_string());
+        let parent = parents.next();
+        let grandparent = parents.next();
+
+        Ok(Self {
+            file_path: file_path.clone(),
+            ..Self::new(basename, artist.or(grandparent), album.or(parent), options)?
+        })
+    }
+
+    /// Gets the value of a template placeholder for use in [render_template],
+    /// returning [None] if `name` isn't a known placeholder
+    fn placeholder(&self, name: &str) -> Option<TemplateValue> {
+        Some(match name {
+            "name" => TemplateValue::Text(Some(self.name.clone())),
+            "render" => TemplateValue::Text(Some(self.render.clone())),
+            "ext" => TemplateValue::Text(self.ext.clone()),
+            "ext_normalized" => TemplateValue::Text(self.ext_normalized.clone()),
+            "artist" => TemplateValue::Text(self.artist.as_ref().map(format_name)),
+            "album" => TemplateValue::Text(self.album.as_ref().map(format_name)),
+            "track" => TemplateValue::Number(self.track),
+            "disc" => TemplateValue::Number(self.disc),
+            "disc_total" => TemplateValue::Number(self.disc_total),
+            "remix" => TemplateValue::Text(self.remix.clone()),
+            "bpm" => TemplateValue::Number(self.bpm.map(usize::from)),
+            "key" => TemplateValue::Text(self.key.clone()),
+            _ => return None,
+        })
+    }
+
+    /// Renders this song into a `template` such as `{artist} - {track:02} -
+    /// {name}{ext}`, see [render_template] for more infomation
+    pub fn render_template(&self, template: &str) -> Result<String, TemplateError> {
+        render_template(template, |name| self.placeholder(name))
     }
 }
 
-/// Gives help by providing available endpoints (to [song] and [album])
-#[get("/music")]
-pub fn help() -> &'static str {
-    "ROUTE /music\n\n\nAbout\n    Allows music tagging with a static/strong artist + albumn + song methodoloy\n    of tagging. Formatting uses an em dash to differentiate these layers.\n\nChild routes/endpoints\n    - /song: Tags a single song and allows optional context for artist/album"
+/// Single track of an [Album], given as either a plain name or an object with
+/// its own context overriding the shared album, e.g. for compilations where
+/// each track has a different artist
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AlbumTrack {
+    /// Plain name using the shared [Album::artist] and [Album::album]
+    Name(String),
+
+    /// Name with its own `artist`, `album` and/or `track` number, with missing
+    /// fields falling back to the shared album or the filename
+    Override {
+        name: String,
+        artist: Option<String>,
+        album: Option<String>,
+        track: Option<usize>,
+    },
 }
 
-/// Gives help for how to use the [song] path
-#[get("/music/song")]
-pub fn song_help() -> &'static str {
-    "POST /music/song?<name>&<album>&<artist>\n\n\nAbout\n    Tags a single song path into the typical artist + album + song view. Some\n    optional url parameters may be passed like `album` and `artist` in order to\n    give explicit context for tagging the song."
+impl From<String> for AlbumTrack {
+    fn from(name: String) -> Self {
+        AlbumTrack::Name(name)
+    }
 }
 
-/// Tags a single song into a song, album and artist. This is typically used for
-/// playlists where songs are not in any exact order
-#[post("/music/song?<name>&<album>&<artist>")]
-pub fn song(
-    name: String,
-    album: Option<String>,
-    artist: Option<String>,
-) -> ResponseModel<SingleSong> {
-    ResponseModel::new(200, "Success", SingleSong::new(name, artist, album))
+/// Album input for the [album](routes::album) rocket path
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Album {
+    /// Names for tracks, corrosponding to [SingleSong::file_path], which may
+    /// override the shared context individually, see [AlbumTrack]
+    pub tracks: Vec<AlbumTrack>,
+
+    /// Artist shared by every track without its own
+    pub artist: Option<String>,
+
+    /// Album name shared by every track without its own
+    pub album: Option<String>,
+}
+
+impl Album {
+    /// Tags every track of this album in order, giving an error if any name is
+    /// too long, see [SingleSong::new]
+    pub fn capture(self, options: &SongOptions) -> Result<Vec<SingleSong>, NameTooLong> {
+        let (artist, album) = (self.artist, self.album);
+
+        self.tracks
+            .into_iter()
+            .map(|track| match track {
+                AlbumTrack::Name(name) => {
+                    SingleSong::new(name, artist.clone(), album.clone(), options)
+                }
+                AlbumTrack::Override {
+                    name,
+                    artist: track_artist,
+                    album: track_album,
+                    track,
+                } => {
+                    let mut song = SingleSong::new(
+                        name,
+                        track_artist.or_else(|| artist.clone()),
+                        track_album.or_else(|| album.clone()),
+                        options,
+                    )?;
+                    song.track = track.or(song.track);
+                    Ok(song)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Input for the [normalize](routes::normalize) rocket path
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Normalize {
+    /// Template to render each track into, see [SingleSong::render_template]
+    pub template: String,
+
+    /// Names of each track to normalize, corrosponding to [SingleSong::file_path]
+    pub tracks: Vec<String>,
+}
+
+/// Rocket routes for tagging music, alongside their help and query args
+#[cfg(feature = "server")]
+pub mod routes {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::utils::{parse_case, JsonBody, ResponseModel};
+    use rocket::request::LenientForm;
+    use rocket::State;
+
+    /// Gives help by providing available endpoints (to [song] and [album])
+    #[get("/music")]
+    pub fn help() -> &'static str {
+        "ROUTE /music\n\n\nAbout\n    Allows music tagging with a static/strong artist + albumn + song methodoloy\n    of tagging. Formatting uses an em dash to differentiate these layers.\n\nChild routes/endpoints\n    - /song: Tags a single song and allows optional context for artist/album\n    - /album: Tags many songs sharing an album, allowing per-track context\n    - /path: Tags a single song using its artist and album directories\n    - /normalize: Renames many songs into a consistent template"
+    }
+
+    /// Gives help for how to use the [song] path
+    #[get("/music/song")]
+    pub fn song_help() -> &'static str {
+        "POST /music/song?<name>&<album>&<artist>&<split_artists>&<preserve_dashes>&<default_ext>&<dj>&<case>&<compact>&<echo>\n\n\nAbout\n    Tags a single song path into the typical artist + album + song view. Some\n    optional url parameters may be passed like `album` and `artist` in order to\n    give explicit context for tagging the song. If no `artist` is given, it will\n    be inferred from names in the form of `Artist - Title` when possible.\n    Passing `split_artists=true` splits collaborations such as `A, B & C` into\n    multiple artists, and `preserve_dashes=true` keeps spaced dashes in the\n    song name like `Title - Live`. Names without an extension may be given a\n    `default_ext` such as `.mp3`, which never overrides an existing one. DJ\n    tags such as `(128 BPM)` and `(Am)` are captured into `bpm` and `key` when\n    passing `dj=true`. The `render` may be recased using `case`\n    set to either `kebab`, `snake`, `title` or `original` (default), and\n    `compact=true` omits `null` fields such as a missing `album`. Passing\n    `echo=true` gives the query args sent back in an `input` object.\n\n    Responses include an `ETag` header, with requests sending a matching\n    `If-None-Match` header given an empty 304 response instead."
+    }
+
+    /// Query args accepted by the [song] path
+    #[derive(Debug, PartialEq, Clone, FromForm, Serialize)]
+    pub struct SongQuery {
+        /// Name of the file to tag, equates to [SingleSong::file_path]
+        pub name: String,
+
+        /// Explicit album context, see [SingleSong::new]
+        pub album: Option<String>,
+
+        /// Explicit artist context, see [SingleSong::new]
+        pub artist: Option<String>,
+
+        /// See [SongOptions::split_artists]
+        pub split_artists: Option<bool>,
+
+        /// See [SongOptions::preserve_dashes]
+        pub preserve_dashes: Option<bool>,
+
+        /// See [SongOptions::default_ext]
+        pub default_ext: Option<String>,
+
+        /// See [SongOptions::dj]
+        pub dj: Option<bool>,
+
+        /// Case to render in, see [parse_case]
+        pub case: Option<String>,
+
+        /// Omits `null` fields from the response, see [ResponseModel::compact]
+        pub compact: Option<bool>,
+
+        /// Echoes these query args back in the response, see [ResponseModel::echo]
+        pub echo: Option<bool>,
+    }
+
+    /// Tags a single song into a song, album and artist. This is typically used for
+    /// playlists where songs are not in any exact order
+    #[post("/music/song?<query..>")]
+    pub fn song(
+        query: LenientForm<SongQuery>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<SingleSong> {
+        let query = query.into_inner();
+        let input = query.clone();
+        let case = match parse_case(query.case) {
+            Ok(case) => case,
+            Err(err) => return ResponseModel::basic(400, err),
+        };
+        let options = SongOptions {
+            split_artists: query.split_artists.unwrap_or(false),
+            preserve_dashes: query.preserve_dashes.unwrap_or(false),
+            default_ext: query.default_ext.or_else(|| config.default_ext.clone()),
+            dj: query.dj.unwrap_or(false),
+            max_len: Some(config.max_name_len),
+        };
+
+        let mut song = match SingleSong::new(query.name, query.artist, query.album, &options) {
+            Ok(song) => song,
+            Err(err) => return ResponseModel::basic(400, err),
+        };
+        song.render = case.apply(&song.render);
+
+        ResponseModel::new(200, "Success", song)
+            .compact(query.compact.unwrap_or(false))
+            .echo(query.echo.unwrap_or(false), &input)
+            .etag(true)
+    }
+
+    /// Gives help for how to use the [path] path
+    #[get("/music/path")]
+    pub fn path_help() -> &'static str {
+        "ENDPOINT POST /music/path?<name>&<artist>&<album>&<echo>\n\n\nAbout\n    Tags a single song by it's full path `name` inside of an artist and album\n    directory structure like `Artist/Album/01 Track.mp3`, in the same way as\n    `/music/song`. The artist is taken from the grandparent directory and the\n    album from the parent directory, with explicit `artist` or `album` query\n    args taking precedence. Both `/` and `\\` are treated as separators.\n    Passing `echo=true` gives the query args sent back in an `input` object,\n    like `/music/song`."
+    }
+
+    /// Tags a single song using its artist and album directories, see
+    /// [SingleSong::from_path]
+    #[post("/music/path?<name>&<artist>&<album>&<echo>")]
+    pub fn path(
+        name: String,
+        artist: Option<String>,
+        album: Option<String>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<SingleSong> {
+        let input = serde_json::json!({ "name": name, "artist": artist, "album": album });
+        let options = SongOptions {
+            default_ext: config.default_ext.clone(),
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+
+        let response = match SingleSong::from_path(name, artist, album, &options) {
+            Ok(song) => ResponseModel::new(200, "Success", song),
+            Err(err) => ResponseModel::basic(400, err),
+        };
+
+        response.echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [album] path
+    #[get("/music/album")]
+    pub fn album_help() -> &'static str {
+        "ENDPOINT POST /music/album?<echo>\n\n\nAbout\n    Tags many songs sharing an optional `artist` and `album`, in the same way as\n    `/music/song`. Each track is either a plain name or an object with its own\n    `name` alongside an `artist`, `album` and/or `track` number overriding the\n    shared context, which is useful for compilations where each track has a\n    different artist. Passing `echo=true` gives the album sent back as\n    `input.body`.\n\n\nExample JSON\n    {\n        \"album\": \"Compilation\",\n        \"tracks\": [\n            \"01 - Artist - Title.mp3\",\n            {\"name\": \"Song.mp3\", \"artist\": \"Other\", \"track\": 2}\n        ]\n    }"
+    }
+
+    /// Multiple songs sharing an album, each of which may override the shared
+    /// context, see [Album]
+    #[post("/music/album?<echo>", format = "json", data = "<album>")]
+    pub fn album(
+        album: JsonBody<Album>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Vec<SingleSong>> {
+        let input = serde_json::json!({ "body": album.0 });
+        let options = SongOptions {
+            default_ext: config.default_ext.clone(),
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+
+        let response = match album.into_inner().capture(&options) {
+            Ok(songs) => ResponseModel::new(200, "Success", songs),
+            Err(err) => ResponseModel::basic(400, err),
+        };
+
+        response.echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [normalize] path
+    #[get("/music/normalize")]
+    pub fn normalize_help() -> &'static str {
+        "ENDPOINT POST /music/normalize?<echo>\n\n\nAbout\n    Normalizes many song names into a single consistent `template`, substituting\n    placeholders for each song. Available placeholders are `{artist}`, `{album}`,\n    `{track}`, `{disc}`, `{disc_total}`, `{remix}`, `{name}`, `{ext}`,\n    `{ext_normalized}` and `{render}`, with numbers being paddable like\n    `{track:02}`. The `template` and tracks sent are given back as `input.body`\n    when passing `echo=true`.\n\n\nExample JSON\n    {\n        \"template\": \"{artist} - {track:02} - {name}{ext}\",\n        \"tracks\": [\n            \"01 Artist - Title.mp3\",\n            \"02. Other - Song.flac\"\n        ]\n    }"
+    }
+
+    /// Normalizes many song names into a single template, see [Normalize]
+    #[post("/music/normalize?<echo>", format = "json", data = "<req>")]
+    pub fn normalize(
+        req: JsonBody<Normalize>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Vec<String>> {
+        let input = serde_json::json!({ "body": req.0 });
+        let req = req.into_inner();
+        let options = SongOptions {
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+        let mut names = Vec::with_capacity(req.tracks.len());
+
+        for track in req.tracks {
+            let song = match SingleSong::new(track, None, None, &options) {
+                Ok(song) => song,
+                Err(err) => return ResponseModel::basic(400, err),
+            };
+
+            names.push(match song.render_template(&req.template) {
+                Ok(name) => name,
+                Err(err) => return ResponseModel::basic(400, err),
+            })
+        }
+
+        ResponseModel::new(200, "Success", names).echo(echo.unwrap_or(false), &input)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn path_echo() {
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(AppConfig::default())
+                    .mount("/", routes![path]),
+            )
+            .expect("Could not make client");
+            let mut response = client
+                .post("/music/path?name=Artist/Album/01%20Track.mp3&album=Other&echo=true")
+                .dispatch();
+            let body: serde_json::Value =
+                serde_json::from_str(&response.body_string().unwrap()).unwrap();
+
+            assert_eq!(body["input"]["name"], "Artist/Album/01 Track.mp3");
+            assert_eq!(body["input"]["album"], "Other");
+            assert_eq!(body["input"]["artist"], serde_json::Value::Null);
+        }
+
+        #[test]
+        fn song_etag() {
+            use rocket::http::{Header, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(AppConfig::default())
+                    .mount("/", routes![song]),
+            )
+            .expect("Could not make client");
+            let url = "/music/song?name=Artist%20-%20Title.mp3";
+
+            let first = client.post(url).dispatch();
+            let tag = first.headers().get_one("ETag").unwrap().to_string();
+            assert_eq!(first.status(), Status::Ok);
+
+            let cached = client
+                .post(url)
+                .header(Header::new("If-None-Match", tag.clone()))
+                .dispatch();
+            assert_eq!(cached.status(), Status::NotModified);
+            assert_eq!(cached.headers().get_one("ETag"), Some(tag.as_str()));
+
+            let changed = client
+                .post("/music/song?name=Other.mp3")
+                .header(Header::new("If-None-Match", tag))
+                .dispatch();
+            assert_eq!(changed.status(), Status::Ok);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DEFAULT_MAX_NAME_LEN;
+
+    #[test]
+    fn existing_em_dash() {
+        let song =
+            SingleSong::new("Artist — Title.mp3", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!(song.artist, Some("Artist".to_string()));
+        assert_eq!(song.name, "Title");
+        assert_eq!(song.ext, Some(".mp3".to_string()));
+        assert_eq!(song.render, "Artist — Title");
+    }
+
+    #[test]
+    fn existing_hyphen() {
+        let song =
+            SingleSong::new("Artist - Title.mp3", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!(song.artist, Some("Artist".to_string()));
+        assert_eq!(song.name, "Title");
+    }
+
+    #[test]
+    fn existing_hyphen_first_only() {
+        let song = SingleSong::new(
+            "Artist - Title - Live.mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.artist, Some("Artist".to_string()));
+        assert_eq!(song.name, "Title Live");
+    }
+
+    #[test]
+    fn existing_hyphen_preserved() {
+        let options = SongOptions {
+            preserve_dashes: true,
+            ..Default::default()
+        };
+        let song = SingleSong::new("Artist - Title - Live.mp3", None, None, &options).unwrap();
+
+        assert_eq!(song.artist, Some("Artist".to_string()));
+        assert_eq!(song.name, "Title - Live");
+        assert_eq!(song.render, "Artist — Title - Live");
+    }
+
+    #[test]
+    fn self_titled_album() {
+        let song = SingleSong::new(
+            "Artist - Song.mp3",
+            None,
+            "Artist".to_string(),
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.render, "Artist — Song");
+
+        let song = SingleSong::new(
+            "Artist - Song.mp3",
+            None,
+            "Album".to_string(),
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.render, "Artist — Album — Song");
+
+        let song = SingleSong::new(
+            "Artist - Song.mp3",
+            None,
+            "Song".to_string(),
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.render, "Artist — Song");
+    }
+
+    #[test]
+    fn disc_of_total() {
+        let song = SingleSong::new(
+            "Album Disc 1 of 2 - Track.mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!((song.disc, song.disc_total), (Some(1), Some(2)));
+        assert_eq!(song.name, "Track");
+
+        let song = SingleSong::new(
+            "Artist - Title (Disc 2 of 3).mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!((song.disc, song.disc_total), (Some(2), Some(3)));
+        assert_eq!(song.render, "Artist — Title");
+    }
+
+    #[test]
+    fn render_whitespace() {
+        let song = SingleSong::new(
+            "   Title\t .mp3",
+            "Artist\t  ".to_string(),
+            "\t ".to_string(),
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.render, "Artist — Title");
+
+        let song = SingleSong::new(
+            "Some \t Title.mp3",
+            " Some\u{a0} Artist ".to_string(),
+            "  The \t Album".to_string(),
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.render, "Some Artist — The Album — Some Title");
+    }
+
+    #[test]
+    fn ext_case() {
+        let song =
+            SingleSong::new("Artist - Title.FLAC", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!(song.ext, Some(".FLAC".to_string()));
+        assert_eq!(song.ext_normalized, Some(".flac".to_string()));
+
+        let song = SingleSong::new("Artist - Title", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!(song.ext, None);
+        assert_eq!(song.ext_normalized, None);
+    }
+
+    #[test]
+    fn remixes() {
+        let song = SingleSong::new(
+            "Artist - Title (Deadmau5 Remix).mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.remix, Some("Deadmau5 Remix".to_string()));
+        assert_eq!(song.name, "Title");
+        assert_eq!(song.render, "Artist — Title (Deadmau5 Remix)");
+
+        let song = SingleSong::new(
+            "Title [Radio Edit].mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.remix, Some("Radio Edit".to_string()));
+        assert_eq!(song.name, "Title");
+
+        let song = SingleSong::new(
+            "Title (Mixed Feelings).mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.remix, None);
+    }
+
+    #[test]
+    fn disc_plain() {
+        let song =
+            SingleSong::new("CD2 - 03 - Title.mp3", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!((song.disc, song.disc_total), (Some(2), None));
+        assert_eq!(song.track, Some(3));
+        assert_eq!(song.name, "Title");
+
+        let song =
+            SingleSong::new("ABCD2 - Title.mp3", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!(song.disc, None);
+    }
+
+    #[test]
+    fn no_separator() {
+        let song =
+            SingleSong::new("Artist-Title.mp3", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!(song.artist, None);
+        assert_eq!(song.render, "Unknown artist — Artist Title");
+    }
+
+    #[test]
+    fn existing_separator_explicit_artist() {
+        let song = SingleSong::new(
+            "Artist — Title.mp3",
+            "Other".to_string(),
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.artist, Some("Other".to_string()));
+        assert_eq!(song.name, "Artist — Title");
+    }
+
+    #[test]
+    fn dj_tags() {
+        let options = SongOptions {
+            dj: true,
+            ..Default::default()
+        };
+        let song = SingleSong::new("Track (128 BPM) (Am).mp3", None, None, &options).unwrap();
+
+        assert_eq!((song.bpm, song.key.as_deref()), (Some(128), Some("Am")));
+        assert_eq!(song.name, "Track");
+
+        let song =
+            SingleSong::new("Artist - Track [F#] (90bpm).mp3", None, None, &options).unwrap();
+
+        assert_eq!((song.bpm, song.key.as_deref()), (Some(90), Some("F#")));
+        assert_eq!(song.render, "Artist — Track");
+
+        let song = SingleSong::new("Track (Am).mp3", None, None, &SongOptions::default()).unwrap();
+
+        assert_eq!((song.bpm, song.key), (None, None));
+        assert_eq!(song.name, "Track (Am)");
+    }
+
+    #[test]
+    fn multiple_artists_split() {
+        let options = SongOptions {
+            split_artists: true,
+            ..Default::default()
+        };
+        let song = SingleSong::new("A, B and C & D - Title.mp3", None, None, &options).unwrap();
+
+        assert_eq!(song.artist, Some("A".to_string()));
+        assert_eq!(song.artists, vec!["A", "B", "C", "D"]);
+        assert_eq!(song.render, "A, B, C & D — Title");
+
+        let song = SingleSong::new("Artist1 & Artist2 - Title.mp3", None, None, &options).unwrap();
+
+        assert_eq!(song.artists, vec!["Artist1", "Artist2"]);
+        assert_eq!(song.render, "Artist1 & Artist2 — Title");
+    }
+
+    #[test]
+    fn multiple_artists_unsplit() {
+        let song = SingleSong::new(
+            "Hall & Oates - Maneater.mp3",
+            None,
+            None,
+            &SongOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.artist, Some("Hall & Oates".to_string()));
+        assert_eq!(song.artists, vec!["Hall & Oates"]);
+        assert_eq!(song.render, "Hall & Oates — Maneater");
+    }
+
+    #[test]
+    fn track_leading() {
+        let song =
+            SingleSong::new("01 - Artist - Title.mp3", None, None, &Default::default()).unwrap();
+
+        assert_eq!(song.track, Some(1));
+        assert_eq!(song.artist, Some("Artist".to_string()));
+        assert_eq!(song.name, "Title");
+
+        let song = SingleSong::new("1999.mp3", None, None, &Default::default()).unwrap();
+
+        assert_eq!(song.track, None);
+        assert_eq!(song.name, "1999");
+
+        let song = SingleSong::new("99 Problems.mp3", None, None, &Default::default()).unwrap();
+
+        assert_eq!(song.track, None);
+        assert_eq!(song.name, "99 Problems");
+
+        let song = SingleSong::new("07 Title.mp3", None, None, &Default::default()).unwrap();
+
+        assert_eq!(song.track, Some(7));
+        assert_eq!(song.name, "Title");
+    }
+
+    #[test]
+    fn album_overrides() {
+        let album: Album = serde_json::from_str(
+            r#"{
+                "artist": "Various",
+                "album": "Compilation",
+                "tracks": [
+                    "01 - Title.mp3",
+                    {"name": "Song.mp3", "artist": "Other", "track": 2}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let songs = album.capture(&SongOptions::default()).unwrap();
+
+        assert_eq!(songs[0].artist, Some("Various".to_string()));
+        assert_eq!(songs[0].track, Some(1));
+        assert_eq!(songs[0].render, "Various — Compilation — Title");
+        assert_eq!(songs[1].artist, Some("Other".to_string()));
+        assert_eq!(songs[1].album, Some("Compilation".to_string()));
+        assert_eq!(songs[1].track, Some(2));
+        assert_eq!(songs[1].render, "Other — Compilation — Song");
+    }
+
+    #[test]
+    fn too_long() {
+        let name = format!("Artist - {}.mp3", "a".repeat(2048));
+        let options = SongOptions {
+            max_len: Some(4096),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            SingleSong::new(&name, None, None, &Default::default()),
+            Err(NameTooLong {
+                len: 2061,
+                max: DEFAULT_MAX_NAME_LEN
+            })
+        );
+        assert!(SingleSong::from_path(&name, None, None, &Default::default()).is_err());
+        assert_eq!(
+            SingleSong::new(&name, None, None, &options).unwrap().artist,
+            Some("Artist".to_string())
+        );
+    }
+
+    #[test]
+    fn song_paths() {
+        let song = SingleSong::from_path(
+            "Daft Punk/Discovery/01 One More Time.mp3",
+            None,
+            None,
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(song.file_path, "Daft Punk/Discovery/01 One More Time.mp3");
+        assert_eq!(song.artist, Some("Daft Punk".to_string()));
+        assert_eq!(song.album, Some("Discovery".to_string()));
+        assert_eq!(song.track, Some(1));
+        assert_eq!(song.render, "Daft Punk — Discovery — One More Time");
+
+        let song = SingleSong::from_path(
+            "Daft Punk/Discovery/01 One More Time.mp3",
+            Some("Other".to_string()),
+            None,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(song.render, "Other — Discovery — One More Time");
+
+        let song = SingleSong::from_path(
+            "Discovery\\Artist - Title.mp3",
+            None,
+            None,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(song.artist, Some("Artist".to_string()));
+        assert_eq!(song.album, Some("Discovery".to_string()));
+    }
+
+    #[test]
+    fn normalize_template() {
+        let song =
+            SingleSong::new("3. Artist - Title.MP3", None, None, &Default::default()).unwrap();
+
+        assert_eq!(
+            song.render_template("{artist} - {track:02} - {name}{ext}"),
+            Ok("Artist - 03 - Title.MP3".to_string())
+        );
+        assert_eq!(
+            song.render_template("{name} {year}"),
+            Err(TemplateError::UnknownPlaceholder("year".to_string()))
+        );
+    }
+
+    #[test]
+    fn control_characters() {
+        let song =
+            SingleSong::new("Artist - Title.mp3\r\n", None, None, &Default::default()).unwrap();
+
+        assert_eq!(song.file_path, "Artist - Title.mp3");
+        assert_eq!(song.ext, Some(".mp3".to_string()));
+        assert_eq!(song.name, "Title");
+    }
 }
@@ -0,0 +1,164 @@
+//! Combined tagging of mixed media names, classifying each name as a tv episode,
+//! movie or song before running the matching parser
+
+/// File extensions of audio files, which are always classified as music by
+/// [classify] regardless of their name
+const AUDIO_EXTS: &[&str] = &[
+    ".mp3", ".flac", ".m4a", ".aac", ".ogg", ".opus", ".wav", ".wma", ".alac", ".aiff",
+];
+
+#[cfg(feature = "server")]
+use crate::config::AppConfig;
+#[cfg(feature = "server")]
+use crate::metrics::Metrics;
+use crate::movie::Movie;
+use crate::music::{SingleSong, SongOptions};
+use crate::tv::{Capture, CaptureOptions, Context};
+use crate::utils::{cap_filename_ext, check_name_len, NameTooLong};
+#[cfg(feature = "server")]
+use crate::utils::{JsonBody, ResponseModel};
+
+#[cfg(feature = "server")]
+use rocket::State;
+use serde::Serialize;
+
+/// Kind of media a name was classified as by [classify]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    /// Tv episode, parsed into a [Capture]
+    Tv,
+
+    /// Movie, parsed into a [Movie]
+    Movie,
+
+    /// Song, parsed into a [SingleSong]
+    Music,
+}
+
+/// Classifies a `name` as music if it has one of the [AUDIO_EXTS], otherwise as
+/// a tv episode if a season and episode can be captured from it, falling back
+/// to a movie
+pub fn classify(name: &str) -> Result<MediaKind, NameTooLong> {
+    parse(name.to_string(), None).map(|parsed| parsed.kind())
+}
+
+/// Result of parsing a single name of unknown kind, serialized as an object with
+/// the `kind` of media alongside its `capture`
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(tag = "kind", content = "capture", rename_all = "lowercase")]
+pub enum Parsed {
+    /// Tv episode, see [Capture::new]
+    Tv(Capture),
+
+    /// Movie, see [Movie::new]
+    Movie(Movie),
+
+    /// Song, see [SingleSong::new]
+    Music(SingleSong),
+}
+
+impl Parsed {
+    /// Gives the [MediaKind] of this result
+    pub fn kind(&self) -> MediaKind {
+        match self {
+            Parsed::Tv(_) => MediaKind::Tv,
+            Parsed::Movie(_) => MediaKind::Movie,
+            Parsed::Music(_) => MediaKind::Music,
+        }
+    }
+}
+
+/// Classifies then parses a single `name` using the default options of each
+/// parser, see [classify] for how the kind is decided. Names longer than
+/// `max_len`, or the [DEFAULT_MAX_NAME_LEN](crate::utils::DEFAULT_MAX_NAME_LEN)
+/// if not given, give an error
+pub fn parse(name: String, max_len: Option<usize>) -> Result<Parsed, NameTooLong> {
+    check_name_len(&name, max_len)?;
+
+    let is_audio = cap_filename_ext(&name).1.map_or(false, |ext| {
+        AUDIO_EXTS.contains(&ext.to_lowercase().as_str())
+    });
+
+    if is_audio {
+        let options = SongOptions {
+            max_len,
+            ..Default::default()
+        };
+
+        return SingleSong::new(&name, None, None, &options).map(Parsed::Music);
+    }
+
+    let options = CaptureOptions {
+        max_len,
+        ..Default::default()
+    };
+
+    match Capture::new(name.clone(), &Context::default(), &options) {
+        Ok(cap) => Ok(Parsed::Tv(cap)),
+        Err(_) => Ok(Parsed::Movie(Movie::new(name))),
+    }
+}
+
+/// Gives help for how to use the [parse_names] path
+#[cfg(feature = "server")]
+#[get("/parse")]
+pub fn parse_help() -> &'static str {
+    "ENDPOINT POST /parse?<echo>\n\n\nAbout\n    Tags an array of names from a mixed media folder, classifying each as `tv`,\n    `movie` or `music` before tagging it with the matching parser. Names with an\n    audio extension such as `.mp3` are music, names with a season and episode\n    are tv, and everything else is a movie. Each result is an object with the\n    detected `kind` alongside the `capture` given by `/tv/episode`,\n    `/movie/film` or `/music/song` respectively. Passing `echo=true` gives the\n    names sent back as `input.body`.\n\n\nExample JSON\n    [\n        \"Show S01E02.mkv\",\n        \"Movie (2019).mkv\",\n        \"Artist - Title.mp3\"\n    ]"
+}
+
+/// Multiple names of unknown kind, each classified then parsed, see [parse]
+#[cfg(feature = "server")]
+#[post("/parse?<echo>", format = "json", data = "<names>")]
+pub fn parse_names(
+    names: JsonBody<Vec<String>>,
+    echo: Option<bool>,
+    metrics: State<Metrics>,
+    config: State<AppConfig>,
+) -> ResponseModel<Vec<Parsed>> {
+    let input = serde_json::json!({ "body": names.0 });
+    let names = names.into_inner();
+    metrics.record_batch(names.len());
+
+    let response = match names
+        .into_iter()
+        .map(|name| parse(name, Some(config.max_name_len)))
+        .collect()
+    {
+        Ok(parsed) => ResponseModel::new(200, "Success", parsed),
+        Err(err) => ResponseModel::basic(400, err),
+    };
+
+    response.echo(echo.unwrap_or(false), &input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_kinds() {
+        assert_eq!(classify("Show S01E02.mkv"), Ok(MediaKind::Tv));
+        assert_eq!(classify("Movie (2019) 1080p.mkv"), Ok(MediaKind::Movie));
+        assert_eq!(classify("Artist - Episode 5.MP3"), Ok(MediaKind::Music));
+        assert_eq!(classify("no extension"), Ok(MediaKind::Movie));
+        assert!(classify(&format!("{}.mp3", "a".repeat(2048))).is_err());
+    }
+
+    #[test]
+    fn tagged_union() {
+        let parsed = serde_json::to_value(vec![
+            parse("Show S01E02.mkv".to_string(), None).unwrap(),
+            parse("Movie (2019).mkv".to_string(), None).unwrap(),
+            parse("Artist - Title.flac".to_string(), None).unwrap(),
+        ])
+        .unwrap();
+
+        assert_eq!(parsed[0]["kind"], "tv");
+        assert_eq!(parsed[0]["capture"]["se_string"], "S01E02");
+        assert_eq!(parsed[1]["kind"], "movie");
+        assert_eq!(parsed[1]["capture"]["year"], 2019);
+        assert_eq!(parsed[2]["kind"], "music");
+        assert_eq!(parsed[2]["capture"]["artist"], "Artist");
+    }
+}
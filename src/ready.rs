@@ -0,0 +1,112 @@
+//! Readiness checking of a running instance, making sure tagging actually works
+//! before an orchestrator routes traffic to it
+
+#[cfg(feature = "server")]
+use crate::config::AppConfig;
+use crate::movie::Movie;
+use crate::tv::{Capture, Context};
+#[cfg(feature = "server")]
+use crate::utils::ResponseModel;
+use crate::utils::{compile_site_prefixes, QUALITY_TAGS};
+use crate::{movie, music, tv, utils};
+
+use regex::Regex;
+#[cfg(feature = "server")]
+use rocket::State;
+
+/// Name of a tv episode captured by [check], alongside the expected season,
+/// episode and title
+const SAMPLE_EPISODE: (&str, usize, usize, &str) = ("Show S01E02 1080p.mkv", 1, 2, "Show");
+
+/// Name of a movie captured by [check], alongside the expected year and title
+const SAMPLE_MOVIE: (&str, usize, &str) = ("Movie (2019) 1080p.mkv", 2019, "Movie");
+
+/// Checks that this instance can tag names by compiling every built-in regex
+/// and the `site_prefixes` in use, forcing the [QUALITY_TAGS] to load and then
+/// capturing a sample episode and movie, giving a message of what went wrong if
+/// anything fails. Site prefixes stripping the start of the sample episode are
+/// rejected, as they would eat the title of every name
+pub fn check(site_prefixes: &[String]) -> Result<(), String> {
+    for regex in utils::REGEXES
+        .iter()
+        .chain(tv::REGEXES)
+        .chain(movie::REGEXES)
+        .chain(music::REGEXES)
+    {
+        Regex::new(regex).map_err(|err| format!("Could not compile regex: {}", err))?;
+    }
+
+    if QUALITY_TAGS.is_empty() {
+        return Err("No quality tags are loaded".to_string());
+    }
+
+    let (name, season, episode, title) = SAMPLE_EPISODE;
+    for (regex, pattern) in compile_site_prefixes(site_prefixes)
+        .map_err(|err| format!("Could not compile site prefix: {}", err))?
+        .iter()
+        .zip(site_prefixes)
+    {
+        if regex.find(name).map_or(false, |found| found.end() > 0) {
+            return Err(format!(
+                "Site prefix `{}` strips the start of the sample episode",
+                pattern
+            ));
+        }
+    }
+
+    match Capture::new(name.to_string(), &Context::default(), &Default::default()) {
+        Ok(cap) if (cap.season, cap.episode, cap.title.as_str()) == (season, episode, title) => (),
+        Ok(cap) => return Err(format!("Sample episode captured wrongly as {:?}", cap)),
+        Err(err) => return Err(format!("Could not capture sample episode: {}", err)),
+    }
+
+    let (name, year, title) = SAMPLE_MOVIE;
+    let movie = Movie::new(name.to_string());
+    if (movie.year, movie.title.as_str()) != (Some(year), title) {
+        return Err(format!("Sample movie captured wrongly as {:?}", movie));
+    }
+
+    Ok(())
+}
+
+/// Readiness of this instance, responding with a 503 if [check] fails so no
+/// traffic is routed to a broken instance
+#[cfg(feature = "server")]
+#[get("/ready")]
+pub fn ready(config: State<AppConfig>) -> ResponseModel<()> {
+    match check(&config.site_prefixes) {
+        Ok(()) => ResponseModel::basic(200, "Ready"),
+        Err(err) => ResponseModel::basic(503, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ready_check() {
+        let defaults = utils::read_site_prefixes(None::<&str>).unwrap();
+
+        assert_eq!(check(&defaults), Ok(()));
+        assert_eq!(check(&[]), Ok(()));
+        assert!(check(&["(unclosed".to_string()]).is_err());
+        assert!(check(&["(?i)show ".to_string()]).is_err());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn ready_route() {
+        use rocket::http::Status;
+
+        let client = rocket::local::Client::new(
+            rocket::ignite()
+                .manage(AppConfig::default())
+                .mount("/", routes![ready]),
+        )
+        .expect("Could not make client");
+        let response = client.get("/ready").dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+    }
+}
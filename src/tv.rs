@@ -1,59 +1,629 @@
 //! TV tagging for season-episode based tagging
 
-/// Regex for capturing episodes, global and case insensitive
+/// Regex for capturing episodes, global and case insensitive. Matches directly
+/// following a letter are skipped by [episode_markers] as the regex crate has
+/// no lookbehind
 const EPISODE_REGEX: &str = r"(?i)(e(p(isode)?)? *[0-9]+){1}";
 
+/// Regex for capturing multiple episodes joined by range or combination
+/// separators, such as `e01-e03`, `e01 to e03`, `e01 & e04` or `e01+e02`
+const EPISODES_REGEX: &str = r"(?i)e(p(isode)?)? *[0-9]+( *(-|to|&|\+) *e(p(isode)?)? *[0-9]+)+";
+
+/// Regex for capturing multiple episodes written back to back after a single
+/// season such as `S01E02E03`, case insensitive and used in [cap_compact_episodes]
+const COMPACT_EPISODES_REGEX: &str = r"(?i)s[0-9]+((?:e[0-9]+){2,})\b";
+
+/// Regex for each separator and episode inside of a match from [EPISODES_REGEX]
+const EPISODES_PART_REGEX: &str = r"(?i)(?:(-|to|&|\+) *)?e(?:p(?:isode)?)? *([0-9]+)";
+
+/// Maximum number of episodes a range from [EPISODES_REGEX] may expand into,
+/// past which only the ends of the range are kept
+const MAX_EPISODE_RANGE: usize = 100;
+
 /// Regex for capturing episodes, global and case insensitive
 const SEASON_REGEX: &str = r"(?i)(s(eason)? *[0-9]+){1}";
 
+/// Regex for capturing a combined season then episode such as `S01E02`, not
+/// inside of a word
+const SXE_REGEX: &str = r"(?i)(?:^|[^a-z0-9])s([0-9]+)[ ._-]*e([0-9]+)";
+
+/// Regex for capturing a reversed combined episode then season such as `E02S01`,
+/// not inside of a word
+const EXS_REGEX: &str = r"(?i)(?:^|[^a-z0-9])e([0-9]+)[ ._-]*s([0-9]+)(?:[^0-9]|$)";
+
+/// Template used by [Capture::render], see [Capture::render_template]
+pub const DEFAULT_RENAME_TEMPLATE: &str = "{title} - S{season:02}E{episode:02}{ext}";
+
+/// Resolution quality tags ranked from best to worst, compared case
+/// insensitively in [quality_rank]
+const RESOLUTION_RANKING: &[&str] = &["2160P", "4K", "1080P", "720P", "576P", "480P"];
+
+/// Source quality tags ranked from best to worst, compared case insensitively
+/// in [quality_rank] after [RESOLUTION_RANKING]
+const SOURCE_RANKING: &[&str] = &[
+    "BLURAY", "WEB-DL", "WEBRIP", "WEB", "BDRIP", "BRRIP", "HDTV", "DVDRIP",
+];
+
+/// Regex for capturing CJK episode markers such as `第5話` or `第5集`, after
+/// fullwidth digits are normalized using [normalize_fullwidth]
+const CJK_EPISODE_REGEX: &str = r"第\s*([0-9]+)\s*[話话集]";
+
+/// Regex for capturing CJK season markers such as `第2季` or `第2期`, after
+/// fullwidth digits are normalized using [normalize_fullwidth]
+const CJK_SEASON_REGEX: &str = r"第\s*([0-9]+)\s*[季期]";
+
 /// Gets numbers from a known regex such as [EPISODE_REGEX] or [SEASON_REGEX]
 const NUMBER_REGEX: &str = r"[0-9]+";
 
-use crate::utils::{cap_filename_ext, format_name, ResponseModel};
+/// Word numbers from zero to nineteen, with the index being their value
+const WORD_UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+/// Word numbers for the tens from twenty, with `(index + 2) * 10` being their value
+const WORD_TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Regex for capturing standalone numbers, used for [cap_bare_episode]
+const BARE_NUMBER_REGEX: &str = r"\b[0-9]+\b";
+
+/// Regex for capturing a three or four digit number run together with the text
+/// before it such as the `105` of `Show105`, needing at least two letters
+/// before it so codecs like `x264` aren't captured, used for [cap_merged_se]
+const RUN_TOGETHER_REGEX: &str = r"\p{L}{2}([0-9]{3,4})(?:[^0-9\p{L}]|$)";
+
+/// Regex for the start of the first season or episode marker not inside of a
+/// word, used to cut the title from a filename in [cap_title]
+const TITLE_END_REGEX: &str = r"(?i)(?:^|[^a-z0-9])(s(eason)? *[0-9]+|e(p(isode)?)? *[0-9]+)";
+
+/// Regex for a season or episode marker directly following a previous marker,
+/// used to skip all markers such as `S01.E02-E03` in [cap_episode_title]
+const NEXT_MARKER_REGEX: &str = r"(?i)^[ ._-]*(s(eason)? *[0-9]+|e(p(isode)?)? *[0-9]+)";
+
+/// Regex for each token of a filename split on dots, spaces, underscores and
+/// brackets, used to find quality tags in [cap_episode_title]
+const TOKEN_REGEX: &str = r"[^.\s_\[\](){}]+";
+
+/// Regex for capturing an absolute episode number following a spaced dash as in
+/// anime releases such as `Show - 13` or `Show S2 - 13`
+const ABSOLUTE_REGEX: &str = r"\s-\s*([0-9]{1,4})\b";
+
+/// Regex for capturing a season then episode as two bare numbers following
+/// spaced dashes such as `Show - 1 - 2`, used in [cap_dash_se]
+const DASH_SE_REGEX: &str = r"\s-\s*([0-9]{1,3})\s*-\s*([0-9]{1,4})\b";
+
+/// Regex for capturing the part of a single episode split into multiple files
+/// such as `pt1` or `Part 2`, case insensitive and used in [cap_part]
+const PART_REGEX: &str = r"(?i)\b(?:pt|part)[ ._-]*([0-9]{1,2})\b";
+
+/// Regex for the version of a re-release directly after a number such as the
+/// `v2` of `Show - 05v2`, case insensitive and used in [cap_release_version]
+const RELEASE_VERSION_REGEX: &str = r"(?i)[0-9](v([0-9]{1,2}))(?:[^a-z0-9]|$)";
+
+/// Regex for a season pack marker, being a season followed by `Complete` or
+/// `Full` such as `S01 Complete`, a `Complete Series` or a season range such as
+/// `S01-S03`, used in [cap_season_pack]
+const SEASON_PACK_REGEX: &str = r"(?i)(?:^|[^a-z0-9])(s(?:eason)?[ ._]*[0-9]+[ ._-]*(?:complete|full)|(?:complete|full)[ ._-]*(?:season|series)|s(?:eason)?[ ._]*[0-9]+-s?(?:eason)?[ ._]*[0-9]+)(?:[^a-z0-9]|$)";
+
+/// Every built-in regex of this module, compiled by [ready](crate::ready) to
+/// check that none are broken
+pub(crate) const REGEXES: &[&str] = &[
+    EPISODE_REGEX,
+    EPISODES_REGEX,
+    COMPACT_EPISODES_REGEX,
+    EPISODES_PART_REGEX,
+    SEASON_REGEX,
+    SXE_REGEX,
+    EXS_REGEX,
+    CJK_EPISODE_REGEX,
+    CJK_SEASON_REGEX,
+    NUMBER_REGEX,
+    BARE_NUMBER_REGEX,
+    RUN_TOGETHER_REGEX,
+    TITLE_END_REGEX,
+    NEXT_MARKER_REGEX,
+    TOKEN_REGEX,
+    ABSOLUTE_REGEX,
+    DASH_SE_REGEX,
+    PART_REGEX,
+    RELEASE_VERSION_REGEX,
+    SEASON_PACK_REGEX,
+];
+
+/// Default columns of csv responses from the [batch](routes::batch) path when
+/// no fields are chosen, see [to_csv](crate::utils::to_csv)
+pub const CSV_FIELDS: &[&str] = &["file_path", "title", "season", "episode", "ext"];
+
+/// Serialized fields of a [Capture] which may be chosen using the `fields` query
+/// arg, see [parse_capture_fields]
+pub const CAPTURE_FIELDS: &[&str] = &[
+    "file_path",
+    "name",
+    "title",
+    "episode_title",
+    "ext",
+    "sub_language",
+    "episode",
+    "episode_source",
+    "episode_raw",
+    "episodes",
+    "part",
+    "release_version",
+    "season",
+    "season_source",
+    "season_raw",
+    "parsed",
+    "year",
+    "tags",
+    "container",
+    "video_codec",
+    "audio_codec",
+    "audio_channels",
+    "platform",
+    "group",
+    "se_string",
+    "airdate",
+    "crc",
+    "blocks",
+    "is_sample",
+    "is_pack",
+];
+
+/// Rules checked by [lint_name], all of which are checked by default and may be
+/// chosen using the `rules` query arg of the [lint](routes::lint) path
+pub const LINT_RULES: &[&str] = &[
+    "unparsed",
+    "missing_season",
+    "inconsistent_padding",
+    "stray_tags",
+    "non_ascii",
+];
+
+/// Maximum `total` number of episodes accepted by the
+/// [completeness_check](routes::completeness_check) path, past which a 400 is
+/// given instead
+pub const MAX_COMPLETENESS_TOTAL: usize = 10_000;
+
+/// Minimum [similarity] of a parsed title to a candidate for [match_title] to
+/// match it, below which no match is given
+pub const MIN_MATCH_SCORE: f64 = 0.8;
+
+/// Maximum number of candidates accepted by the
+/// [match_titles](routes::match_titles) path, past which a 400 is given instead
+pub const MAX_MATCH_CANDIDATES: usize = 1000;
+
+use crate::metrics::Metrics;
+use crate::utils::{
+    cap_airdate, cap_audio_channels, cap_codecs, cap_container, cap_crc, cap_filename_ext,
+    cap_group, cap_leading_group, cap_platform, cap_sub_language, cap_tags, cap_year,
+    check_name_len, clean_input, escape_xml, ext_or_default, format_name, is_sample,
+    normalize_fullwidth, pad_num, parse_brackets, parse_fields, render_template, similarity,
+    strip_site_prefix, Airdate, NameTooLong, ResponseModel, Selected, TemplateError, TemplateValue,
+    QUALITY_TAGS,
+};
 
 use regex::{Match, Regex};
-use rocket_contrib::json::Json;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::Infallible;
 use std::fmt;
+use std::io::{self, BufRead, Cursor, Read};
 
-/// Finds captured number from a given [Match]; ensure regex passed has numbers
-fn cap_num(captured: Match<'_>) -> usize {
-    Regex::new(NUMBER_REGEX)
+/// Finds captured number from a given [Match], giving [None] if it contains no
+/// number or the number is too large to fit into a [usize]. Numbers are only
+/// ever parsed as base-10, so those written with a radix prefix such as the hex
+/// `0x1A` of a custom regex also give [None] rather than being misread as `0`
+pub fn cap_num(captured: Match<'_>) -> Option<usize> {
+    let captured = captured.as_str();
+    let found = Regex::new(NUMBER_REGEX)
         .expect("Could not make number regex")
-        .find(captured.as_str())
-        .unwrap()
-        .as_str()
-        .parse()
-        .unwrap()
+        .find(captured)?;
+    let mut rest = captured[found.end()..].chars();
+    let has_radix = found.as_str() == "0"
+        && matches!(
+            rest.next(),
+            Some('x') | Some('X') | Some('o') | Some('O') | Some('b') | Some('B')
+        )
+        && rest.next().map_or(false, |c| c.is_ascii_alphanumeric());
+
+    if has_radix {
+        None
+    } else {
+        found.as_str().parse().ok()
+    }
 }
 
-/// Attempts to capture episode number of a given `filename` using regex
-fn cap_episode(filename: &str) -> Result<usize, CaptureError> {
-    match Regex::new(EPISODE_REGEX)
+/// Finds every [EPISODE_REGEX] marker of a given `filename` at a word boundary,
+/// skipping markers directly following a letter so the `e 5` of `Maze 5` isn't
+/// an episode, whilst the `E02` of `S01E02` still is
+fn episode_markers(filename: &str) -> Vec<Match<'_>> {
+    Regex::new(EPISODE_REGEX)
         .expect("Could not make episode regex")
-        .find(filename)
-    {
-        Some(episode) => Ok(cap_num(episode)),
+        .find_iter(filename)
+        .filter(|found| {
+            !filename[..found.start()]
+                .chars()
+                .next_back()
+                .map_or(false, |c| c.is_alphabetic())
+        })
+        .collect()
+}
+
+/// Attempts to capture episode number of a given `filename` using regex
+pub fn cap_episode(filename: &str) -> Result<usize, CaptureError> {
+    match episode_markers(filename).into_iter().next() {
+        Some(episode) => cap_num(episode).ok_or(CaptureError::NoEpisodeRegex),
         None => Err(CaptureError::NoEpisodeRegex),
     }
 }
 
 /// Attempts to capture season number of a given `filename` using regex
-fn cap_season(filename: &str) -> Result<usize, CaptureError> {
+pub fn cap_season(filename: &str) -> Result<usize, CaptureError> {
     match Regex::new(SEASON_REGEX)
         .expect("Could not make season regex")
         .find(filename)
     {
-        Some(season) => Ok(cap_num(season)),
+        Some(season) => cap_num(season).ok_or(CaptureError::NoSeasonRegex),
         None => Err(CaptureError::NoSeasonRegex),
     }
 }
 
+/// Attempts to capture a combined season and episode of a given `filename` such
+/// as `S01E02`, also trying the reversed `E02S01` ordering, giving the season
+/// and episode in that order. This is preferred over [cap_season] and
+/// [cap_episode] as it can't be confused by letters inside of words
+pub fn cap_sxe(filename: &str) -> Option<(usize, usize)> {
+    let parse = |captures: regex::Captures<'_>, season: usize, episode: usize| {
+        Some((
+            captures[season].parse().ok()?,
+            captures[episode].parse().ok()?,
+        ))
+    };
+
+    match Regex::new(SXE_REGEX)
+        .expect("Could not make sxe regex")
+        .captures(filename)
+    {
+        Some(captures) => parse(captures, 1, 2),
+        None => parse(
+            Regex::new(EXS_REGEX)
+                .expect("Could not make exs regex")
+                .captures(filename)?,
+            2,
+            1,
+        ),
+    }
+}
+
+/// Attempts to capture multiple episodes written back to back after a single
+/// season of a given `filename`, so `S01E02E03` gives `[2, 3]`. These are listed
+/// rather than expanded like ranges, see [COMPACT_EPISODES_REGEX]
+pub fn cap_compact_episodes(filename: &str) -> Option<Vec<usize>> {
+    let captures = Regex::new(COMPACT_EPISODES_REGEX)
+        .expect("Could not make compact episodes regex")
+        .captures(filename)?;
+
+    captures[1]
+        .split(|c| c == 'e' || c == 'E')
+        .filter(|num| !num.is_empty())
+        .map(|num| num.parse().ok())
+        .collect()
+}
+
+/// Attempts to capture multiple episodes of a given `filename` using regex,
+/// expanding ranges joined by `-` or `to` and listing episodes joined by `&` or
+/// `+`, so `e01 to e03` gives `[1, 2, 3]` and `e01 & e04` gives `[1, 4]`.
+/// Episodes written back to back like `S01E02E03` are found first using
+/// [cap_compact_episodes]
+pub fn cap_episodes(filename: &str) -> Option<Vec<usize>> {
+    if let Some(episodes) = cap_compact_episodes(filename) {
+        return Some(episodes);
+    }
+
+    let found = Regex::new(EPISODES_REGEX)
+        .expect("Could not make episodes regex")
+        .find(filename)?;
+    let mut episodes: Vec<usize> = Vec::new();
+
+    for part in Regex::new(EPISODES_PART_REGEX)
+        .expect("Could not make episodes part regex")
+        .captures_iter(found.as_str())
+    {
+        let episode: usize = part[2].parse().ok()?;
+        let is_range = part
+            .get(1)
+            .map(|sep| sep.as_str() == "-" || sep.as_str().eq_ignore_ascii_case("to"))
+            .unwrap_or(false);
+
+        match episodes.last() {
+            Some(&last) if is_range && episode > last && episode - last <= MAX_EPISODE_RANGE => {
+                episodes.extend(last + 1..=episode)
+            }
+            _ => episodes.push(episode),
+        }
+    }
+
+    Some(episodes)
+}
+
+/// Attempts to capture a number using a user-supplied `regex` in place of
+/// [EPISODE_REGEX] or [SEASON_REGEX], taking the first match containing a number
+fn cap_custom(regex: &Regex, filename: &str) -> Option<usize> {
+    regex.find_iter(filename).find_map(cap_num)
+}
+
+/// Parses a spelt out `word` number such as `five` or `twenty-one`, from zero up
+/// to ninety-nine
+fn parse_word_number(word: &str) -> Option<usize> {
+    let word = word.to_lowercase();
+    let mut split = word.splitn(2, |c| c == ' ' || c == '-');
+    let first = split.next()?;
+    let index = |words: &[&str], word: &str| words.iter().position(|w| *w == word);
+
+    match (index(&WORD_TENS, first), split.next()) {
+        (Some(tens), None) => Some((tens + 2) * 10),
+        (Some(tens), Some(unit)) => {
+            index(&WORD_UNITS[1..10], unit).map(|unit| (tens + 2) * 10 + unit + 1)
+        }
+        (None, None) => index(&WORD_UNITS, first),
+        (None, Some(_)) => None,
+    }
+}
+
+/// Attempts to capture a CJK marker of a given `filename` using `regex` such as
+/// [CJK_EPISODE_REGEX], giving the number inside of it
+fn cap_cjk(filename: &str, regex: &str) -> Option<usize> {
+    Regex::new(regex)
+        .expect("Could not make cjk regex")
+        .captures(filename)?[1]
+        .parse()
+        .ok()
+}
+
+/// Attempts to capture a spelt out number following one of the `keywords` such
+/// as `Season Two`, used for [CaptureOptions::word_numbers]
+fn cap_word_number(filename: &str, keywords: &str) -> Option<usize> {
+    let units = WORD_UNITS.join("|");
+    let tens = WORD_TENS.join("|");
+    let regex = format!(
+        r"(?i)\b(?:{})[ ._-]*((?:{})(?:[ -](?:{}))?|{})\b",
+        keywords, tens, units, units
+    );
+
+    Regex::new(&regex)
+        .expect("Could not make word number regex")
+        .captures(filename)
+        .and_then(|captures| parse_word_number(&captures[1]))
+}
+
+/// Attempts to capture the last standalone number of a given `filename` as the
+/// episode number, skipping the `year` if one was found. This is used for names
+/// without any episode marker such as `Show 5.mkv`
+pub fn cap_bare_episode(filename: &str, year: Option<usize>) -> Result<usize, CaptureError> {
+    Regex::new(BARE_NUMBER_REGEX)
+        .expect("Could not make bare number regex")
+        .find_iter(filename)
+        .filter_map(cap_num)
+        .filter(|num| Some(*num) != year)
+        .last()
+        .ok_or(CaptureError::NoEpisodeRegex)
+}
+
+/// Attempts to capture an absolute episode number following a spaced dash of a
+/// given `filename`, skipping the `year` if one was found. This is used for
+/// anime names such as `Show - 13.mkv` without an episode marker
+pub fn cap_absolute_episode(filename: &str, year: Option<usize>) -> Result<usize, CaptureError> {
+    Regex::new(ABSOLUTE_REGEX)
+        .expect("Could not make absolute regex")
+        .captures_iter(filename)
+        .filter_map(|captures| captures[1].parse().ok())
+        .find(|num| Some(*num) != year)
+        .ok_or(CaptureError::NoEpisodeRegex)
+}
+
+/// Attempts to capture a season then episode of a given `filename` as two bare
+/// numbers separated by dashes such as `Show - 1 - 2`, giving the season and
+/// episode in that order. This is ambiguous so only used with
+/// [CaptureOptions::dash_se]
+pub fn cap_dash_se(filename: &str) -> Option<(usize, usize)> {
+    let captures = Regex::new(DASH_SE_REGEX)
+        .expect("Could not make dash season episode regex")
+        .captures(filename)?;
+
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+/// Attempts to capture the last three or four digit number of a given `filename`
+/// as a merged season and episode, such as `0205` being season 2 episode 5,
+/// skipping the `year` if one was found. If there's no standalone number, the
+/// first number run together with the text before it such as `Show105` or
+/// `Show0105` is used instead, see [RUN_TOGETHER_REGEX]. This is ambiguous so
+/// only used with [CaptureOptions::merged] or as an alternative by [interpret]
+pub fn cap_merged_se(filename: &str, year: Option<usize>) -> Option<(usize, usize)> {
+    let is_merged = |num: &usize| Some(*num) != year && num / 100 > 0 && num % 100 > 0;
+
+    Regex::new(BARE_NUMBER_REGEX)
+        .expect("Could not make bare number regex")
+        .find_iter(filename)
+        .filter(|found| (3..=4).contains(&found.as_str().len()))
+        .filter_map(cap_num)
+        .filter(is_merged)
+        .last()
+        .or_else(|| {
+            Regex::new(RUN_TOGETHER_REGEX)
+                .expect("Could not make run together regex")
+                .captures_iter(filename)
+                .filter_map(|captures| captures[1].parse().ok())
+                .find(is_merged)
+        })
+        .map(|num| (num / 100, num % 100))
+}
+
+/// Attempts to capture the part of a single episode split into multiple files,
+/// such as the `1` in `Show S01E05 pt1`, returning the filename without it
+/// alongside the part found. Only parts after an episode marker are captured
+/// so titles like `Part 2` aren't mistaken for one, see [PART_REGEX]
+pub fn cap_part(filename: String) -> (String, Option<usize>) {
+    let marker_end = match Regex::new(TITLE_END_REGEX)
+        .expect("Could not make title end regex")
+        .captures(&filename)
+        .and_then(|captures| captures.get(1))
+    {
+        Some(marker) => marker.end(),
+        None => return (filename, None),
+    };
+    let found = Regex::new(PART_REGEX)
+        .expect("Could not make part regex")
+        .captures(&filename[marker_end..])
+        .and_then(|captures| {
+            let range = captures.get(0)?.range();
+            Some((range, captures[1].parse().ok()?))
+        });
+
+    match found {
+        Some((range, part)) => (
+            format!(
+                "{}{}",
+                &filename[..marker_end + range.start],
+                &filename[marker_end + range.end..]
+            ),
+            Some(part),
+        ),
+        None => (filename, None),
+    }
+}
+
+/// Attempts to capture the version of a re-release directly after a number, such
+/// as the `2` in `[Group] Show - 05v2.mkv`, returning the filename without the
+/// `v2` alongside the version found so it isn't absorbed into the episode, see
+/// [RELEASE_VERSION_REGEX]
+pub fn cap_release_version(filename: String) -> (String, Option<usize>) {
+    let found = Regex::new(RELEASE_VERSION_REGEX)
+        .expect("Could not make release version regex")
+        .captures(&filename)
+        .and_then(|captures| Some((captures.get(1)?.range(), captures[2].parse().ok()?)));
+
+    match found {
+        Some((range, version)) => (
+            format!("{}{}", &filename[..range.start], &filename[range.end..]),
+            Some(version),
+        ),
+        None => (filename, None),
+    }
+}
+
+/// Normalises a show `title` for comparing to others, being [format_name] in
+/// lowercase without a leading `the` so `The.Show` and `show` are equal
+pub fn title_key(title: &str) -> String {
+    let title = format_name(title).to_lowercase();
+
+    match title.strip_prefix("the ") {
+        Some(rest) if !rest.is_empty() => rest.to_string(),
+        _ => title,
+    }
+}
+
+/// Captures the show title of a given `filename`, being everything before the
+/// first season, episode or absolute episode marker formatted using
+/// [format_name], which may be empty if the filename starts with a marker
+pub fn cap_title(filename: &str) -> String {
+    let marker = Regex::new(TITLE_END_REGEX)
+        .expect("Could not make title end regex")
+        .captures(filename)
+        .map(|captures| captures.get(1).unwrap().start());
+    let absolute = Regex::new(ABSOLUTE_REGEX)
+        .expect("Could not make absolute regex")
+        .find(filename)
+        .map(|found| found.start());
+    let end = marker
+        .into_iter()
+        .chain(absolute)
+        .min()
+        .unwrap_or_else(|| filename.len());
+
+    format_name(filename[..end].trim_end_matches(|c| "([{ ._-".contains(c)))
+}
+
+/// Captures the episode title of a given `filename`, being everything after the
+/// season and episode markers up until the first quality tag, in either spaced
+/// or dotted scene form such as `Show.S01E02.The.Title.720p`. This is formatted
+/// using [format_name], giving [None] if there's no marker or nothing after it
+pub fn cap_episode_title(filename: &str) -> Option<String> {
+    let marker = Regex::new(TITLE_END_REGEX)
+        .expect("Could not make title end regex")
+        .captures(filename)?
+        .get(1)?;
+    let next_marker = Regex::new(NEXT_MARKER_REGEX).expect("Could not make next marker regex");
+    let mut rest = &filename[marker.end()..];
+
+    while let Some(found) = next_marker.find(rest) {
+        rest = &rest[found.end()..];
+    }
+
+    let is_tag = |token: &str| {
+        token
+            .split('-')
+            .chain(Some(token))
+            .any(|part| QUALITY_TAGS.contains(&part.to_uppercase()))
+    };
+    let end = Regex::new(TOKEN_REGEX)
+        .expect("Could not make token regex")
+        .find_iter(rest)
+        .find(|token| is_tag(token.as_str()))
+        .map_or(rest.len(), |token| token.start());
+    let title = format_name(rest[..end].trim_end_matches(|c| "([{ ._-".contains(c)));
+
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Captures the show title of a season pack `filename` such as `Show S01 Complete`
+/// or `Show S01-S03`, being a name for a whole season rather than an episode.
+/// Gives [None] if there's no [SEASON_PACK_REGEX] marker or a combined episode
+/// marker is found using [cap_sxe]
+pub fn cap_season_pack(filename: &str) -> Option<String> {
+    if cap_sxe(filename).is_some() {
+        return None;
+    }
+
+    let start = Regex::new(SEASON_PACK_REGEX)
+        .expect("Could not make season pack regex")
+        .captures(filename)?
+        .get(1)
+        .unwrap()
+        .start();
+
+    Some(cap_title(&filename[..start]))
+}
+
 /// Context for captures which if provided, takes precidence over any parsed regex
 ///
 /// Providing this is advised as it increases speed due to no reliance on regex
 /// parsing of a given [Capture::file_path] string
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct Context {
     /// Episode number, equates to [Capture::episode]
     pub episode: Option<usize>,
@@ -62,6 +632,241 @@ pub struct Context {
     pub season: Option<usize>,
 }
 
+/// Optional behaviours for creating a [Capture], all of which are disabled by
+/// default as they add ambiguity to parsing
+#[derive(Debug, Clone, Default)]
+pub struct CaptureOptions {
+    /// Treats the last standalone number as the episode when no episode marker
+    /// is found, with the season defaulting to `1` if not found either, see
+    /// [cap_bare_episode]
+    pub bare_episode: bool,
+
+    /// Detects a release year into [Capture::year], which is then excluded from
+    /// [CaptureOptions::bare_episode] so `Show 2019` isn't episode 2019
+    pub detect_year: bool,
+
+    /// User-supplied regex used in place of [EPISODE_REGEX], which should be
+    /// compiled using [compile_user_regex](crate::utils::compile_user_regex)
+    pub episode_regex: Option<Regex>,
+
+    /// User-supplied regex used in place of [SEASON_REGEX], which should be
+    /// compiled using [compile_user_regex](crate::utils::compile_user_regex)
+    pub season_regex: Option<Regex>,
+
+    /// Parses spelt out numbers such as `Season Two Episode Five` when no digits
+    /// are found, which adds ambiguity so is opt-in
+    pub word_numbers: bool,
+
+    /// Captures the episode as an absolute number following a spaced dash such
+    /// as `Show - 13` when no episode marker is found, with the season
+    /// defaulting to `1` unless a season such as `S2` is present, see
+    /// [cap_absolute_episode]
+    pub absolute: bool,
+
+    /// Minimum season number allowed when not passed as [Context], giving
+    /// [CaptureError::OutOfRange] if lower
+    pub min_season: Option<usize>,
+
+    /// Maximum season number allowed when not passed as [Context], giving
+    /// [CaptureError::OutOfRange] if higher, e.g. to reject years
+    pub max_season: Option<usize>,
+
+    /// Minimum episode number allowed when not passed as [Context], giving
+    /// [CaptureError::OutOfRange] if lower
+    pub min_episode: Option<usize>,
+
+    /// Maximum episode number allowed when not passed as [Context], giving
+    /// [CaptureError::OutOfRange] if higher
+    pub max_episode: Option<usize>,
+
+    /// Gives an unparsed [Capture] with a season and episode of `0` instead of
+    /// an error when neither could be found, see [Capture::parsed]
+    pub lenient: bool,
+
+    /// Only parses the basename of names containing `/` or `\` directory
+    /// separators such as `Show/Season 1/ep 2.mkv`, falling back to the nearest
+    /// directory for the season and title if they aren't in the basename. See
+    /// [split_basename]
+    pub basename: bool,
+
+    /// Extension assumed for names without one, see [ext_or_default]
+    pub default_ext: Option<String>,
+
+    /// Captures the season and episode as two dash separated bare numbers such
+    /// as `Show - 1 - 2` when no markers are found, which is ambiguous so is
+    /// opt-in, see [cap_dash_se]
+    pub dash_se: bool,
+
+    /// Captures the season and episode from a merged number such as `0205.mkv`
+    /// or `Show105.mkv` being season 2 episode 5 and season 1 episode 5 when no
+    /// markers are found, so purely numeric names fail by default rather than
+    /// being silently misparsed, see [cap_merged_se]
+    pub merged: bool,
+
+    /// Maximum length in characters of names, over which
+    /// [CaptureError::TooLong] is given before any regex is ran, defaulting to
+    /// [DEFAULT_MAX_NAME_LEN](crate::utils::DEFAULT_MAX_NAME_LEN), see
+    /// [check_name_len]
+    pub max_len: Option<usize>,
+}
+
+impl CaptureOptions {
+    /// Captures the episode number using [CaptureOptions::episode_regex] if
+    /// given, otherwise using [cap_sxe], [cap_episode] then [CJK_EPISODE_REGEX],
+    /// alongside the [Source] used
+    fn cap_episode(&self, filename: &str) -> Result<(usize, Source), CaptureError> {
+        match &self.episode_regex {
+            Some(regex) => cap_custom(regex, filename)
+                .map(|ep| (ep, Source::Regex))
+                .ok_or(CaptureError::NoEpisodeRegex),
+            None => match cap_sxe(filename) {
+                Some((_, ep)) => Ok((ep, Source::Regex)),
+                None => match cap_episode(filename)
+                    .or_else(|err| cap_cjk(filename, CJK_EPISODE_REGEX).ok_or(err))
+                {
+                    Ok(ep) => Ok((ep, Source::Regex)),
+                    Err(err) if self.word_numbers => cap_word_number(filename, "episode|ep")
+                        .map(|ep| (ep, Source::Words))
+                        .ok_or(err),
+                    Err(err) => Err(err),
+                },
+            },
+        }
+    }
+
+    /// Captures the season number using [CaptureOptions::season_regex] if given,
+    /// otherwise using [cap_sxe], [cap_season] then [CJK_SEASON_REGEX], alongside
+    /// the [Source] used
+    fn cap_season(&self, filename: &str) -> Result<(usize, Source), CaptureError> {
+        match &self.season_regex {
+            Some(regex) => cap_custom(regex, filename)
+                .map(|se| (se, Source::Regex))
+                .ok_or(CaptureError::NoSeasonRegex),
+            None => match cap_sxe(filename) {
+                Some((se, _)) => Ok((se, Source::Regex)),
+                None => match cap_season(filename)
+                    .or_else(|err| cap_cjk(filename, CJK_SEASON_REGEX).ok_or(err))
+                {
+                    Ok(se) => Ok((se, Source::Regex)),
+                    Err(err) if self.word_numbers => cap_word_number(filename, "season")
+                        .map(|se| (se, Source::Words))
+                        .ok_or(err),
+                    Err(err) => Err(err),
+                },
+            },
+        }
+    }
+
+    /// Finds how a captured `num` was written in a `filename`, such as the `1` of
+    /// `S1E02` or the `01` of `S01E2`, taking the digits of the same marker the
+    /// `source` of the season (if `season`) or episode was captured from so the
+    /// season's digits are never reused for the episode or the other way round
+    fn cap_raw_num(
+        &self,
+        filename: &str,
+        num: usize,
+        source: Source,
+        season: bool,
+    ) -> Option<String> {
+        let regex = |regex: &str| Regex::new(regex).expect("Could not make raw number regex");
+        let digits = |found: &str| {
+            regex(NUMBER_REGEX)
+                .find(found)
+                .map(|d| d.as_str().to_string())
+        };
+        let group = |pattern: &str, index: usize| {
+            regex(pattern)
+                .captures(filename)
+                .map(|captures| captures[index].to_string())
+        };
+        let custom = if season {
+            &self.season_regex
+        } else {
+            &self.episode_regex
+        };
+
+        let raw = match (source, custom) {
+            (Source::Regex, Some(custom)) => custom
+                .find_iter(filename)
+                .find(|found| cap_num(*found).is_some())
+                .and_then(|found| digits(found.as_str())),
+            (Source::Regex, None) => {
+                let marker = if season {
+                    regex(SEASON_REGEX).find(filename)
+                } else {
+                    episode_markers(filename).into_iter().next()
+                };
+                let cjk = if season {
+                    CJK_SEASON_REGEX
+                } else {
+                    CJK_EPISODE_REGEX
+                };
+
+                match regex(SXE_REGEX).captures(filename) {
+                    Some(captures) => Some(captures[if season { 1 } else { 2 }].to_string()),
+                    None => match regex(EXS_REGEX).captures(filename) {
+                        Some(captures) => Some(captures[if season { 2 } else { 1 }].to_string()),
+                        None => marker
+                            .and_then(|found| digits(found.as_str()))
+                            .or_else(|| group(cjk, 1)),
+                    },
+                }
+            }
+            (Source::Dash, _) => group(DASH_SE_REGEX, if season { 1 } else { 2 }),
+            (Source::Absolute, _) => regex(ABSOLUTE_REGEX)
+                .captures_iter(filename)
+                .map(|captures| captures[1].to_string())
+                .find(|raw| raw.parse().ok() == Some(num)),
+            (Source::Bare, _) => regex(BARE_NUMBER_REGEX)
+                .find_iter(filename)
+                .filter_map(|found| digits(found.as_str()))
+                .filter(|raw| raw.parse().ok() == Some(num))
+                .last(),
+            _ => None,
+        };
+
+        raw.filter(|raw| raw.parse().ok() == Some(num))
+    }
+}
+
+/// Method used to obtain a number of a [Capture], used for debugging and to let
+/// clients weight how much to trust each number
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// Passed by the caller using [Context]
+    Context,
+
+    /// Found in the filename using [EPISODE_REGEX], [SEASON_REGEX] or a custom
+    /// regex from [CaptureOptions]
+    Regex,
+
+    /// Found in the filename as a spelt out number from
+    /// [CaptureOptions::word_numbers]
+    Words,
+
+    /// Found in the filename as a standalone number from
+    /// [CaptureOptions::bare_episode]
+    Bare,
+
+    /// Found as an absolute number using [cap_absolute_episode]
+    Absolute,
+
+    /// Found as dash separated numbers from [CaptureOptions::dash_se]
+    Dash,
+
+    /// Found in a parent directory using [CaptureOptions::basename]
+    Directory,
+
+    /// Found as a merged season and episode number such as the `102` of
+    /// `Show 102` from [CaptureOptions::merged], or as an alternative by
+    /// [interpret]
+    Merged,
+
+    /// Not found, so a fallback value was used
+    Default,
+}
+
 /// Error enum encapsulating errors that may arrise on the creation of a [Capture]
 /// structure
 #[derive(Debug, PartialEq, Clone)]
@@ -73,6 +878,19 @@ pub enum CaptureError {
     /// No episode context supplied and season number wasn't found in regex capture
     /// from [cap_episode]
     NoEpisodeRegex,
+
+    /// Captured season or episode number (named by `name`) was outside of the
+    /// bounds set in [CaptureOptions]
+    OutOfRange { name: &'static str, value: usize },
+
+    /// Name was longer than [CaptureOptions::max_len], see [check_name_len]
+    TooLong(NameTooLong),
+}
+
+impl From<NameTooLong> for CaptureError {
+    fn from(err: NameTooLong) -> Self {
+        CaptureError::TooLong(err)
+    }
 }
 
 impl fmt::Display for CaptureError {
@@ -86,13 +904,19 @@ impl fmt::Display for CaptureError {
                 f,
                 "No episode number passed as context and it wasn't found in the file name either"
             ),
+            CaptureError::OutOfRange { name, value } => write!(
+                f,
+                "Captured {} number {} is out of the allowed range",
+                name, value
+            ),
+            CaptureError::TooLong(err) => write!(f, "{}", err),
         }
     }
 }
 
 /// Single capture found using passed [Context] or with help from a given `cap_x`
 /// function for regex capturing
-#[derive(Debug, PartialEq, Clone, Serialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Capture {
     /// Original, pre-parsed file_path provided
     pub file_path: String,
@@ -101,123 +925,5108 @@ pub struct Capture {
     /// season/episode numbers or [Capture::ext]
     pub name: String,
 
+    /// Show title found before any season or episode markers, see [cap_title]
+    pub title: String,
+
+    /// Episode title found after the season and episode markers, see
+    /// [cap_episode_title]
+    pub episode_title: Option<String>,
+
     /// Optional file extension found from parsing [Capture::file_path]
     pub ext: Option<String>,
 
+    /// Optional subtitle language found before a subtitle [Capture::ext], e.g.
+    /// the `en` in `Show.S01E02.en.srt`
+    pub sub_language: Option<String>,
+
     /// Episode number
     pub episode: usize,
 
+    /// Method used to obtain [Capture::episode]
+    pub episode_source: Source,
+
+    /// Episode number exactly as written in the filename such as the `2` of
+    /// `S1E2`, which is [None] if it wasn't found in the filename itself
+    pub episode_raw: Option<String>,
+
+    /// All episodes contained in this file, which is just [Capture::episode]
+    /// unless multiple episodes were found using [cap_episodes]
+    pub episodes: Vec<usize>,
+
+    /// Optional part of a single episode split into multiple files, e.g. the
+    /// `1` in `Show S01E05 pt1` which keeps [Capture::episode] as `5`, see
+    /// [cap_part]
+    pub part: Option<usize>,
+
+    /// Optional version of a re-release, e.g. the `2` in `Show - 05v2` which
+    /// keeps [Capture::episode] as `5`, see [cap_release_version]
+    pub release_version: Option<usize>,
+
     /// Season number
     pub season: usize,
+
+    /// Method used to obtain [Capture::season]
+    pub season_source: Source,
+
+    /// Season number exactly as written in the filename such as the `1` of
+    /// `S1E2`, which is [None] if it wasn't found in the filename itself
+    pub season_raw: Option<String>,
+
+    /// If the season and episode were found, which is only ever `false` when
+    /// [CaptureOptions::lenient] is used, leaving [Capture::name] as the whole
+    /// filename so unrecognised files can still be catalogued
+    pub parsed: bool,
+
+    /// Release year if [CaptureOptions::detect_year] was used and one was found
+    pub year: Option<usize>,
+
+    /// Quality tags found in the filename such as `1080p` or `x264`, see
+    /// [cap_tags] for more infomation
+    pub tags: Vec<String>,
+
+    /// Video container from [Capture::ext] such as `mkv`, see [cap_container]
+    pub container: Option<String>,
+
+    /// Canonical video codec from [Capture::tags] such as `H.264`, see
+    /// [cap_codecs]
+    pub video_codec: Option<String>,
+
+    /// Canonical audio codec from [Capture::tags] such as `DTS`, see
+    /// [cap_codecs]
+    pub audio_codec: Option<String>,
+
+    /// Audio channel layout such as `5.1` or `7.1 Atmos`, see
+    /// [cap_audio_channels]
+    pub audio_channels: Option<String>,
+
+    /// Streaming platform the file was ripped from such as `Netflix` for `NF`,
+    /// which is removed from [Capture::name] and [Capture::episode_title], see
+    /// [cap_platform]
+    pub platform: Option<String>,
+
+    /// Scene release group found after the quality tags, see [cap_group], or
+    /// otherwise in a leading block such as `[Group] Show - 05` which is removed
+    /// from [Capture::name] and [Capture::title], see [cap_leading_group]
+    pub group: Option<String>,
+
+    /// Season and episode combined in the form of `S01E02`, see [se_string]
+    pub se_string: String,
+
+    /// Airdate of daily shows such as `Show 2021.03.14`, see [cap_airdate]
+    pub airdate: Option<Airdate>,
+
+    /// Bracketed CRC32 checksum of anime releases such as `A1B2C3D4`, which is
+    /// removed from [Capture::name] and [Capture::title], see [cap_crc]
+    pub crc: Option<String>,
+
+    /// Contents of every `[...]` and `(...)` block in the filename for debugging,
+    /// such as the `Group`, `BD 1080p` and `A1B2C3D4` of anime releases like
+    /// `[Group] Show - 05 (BD 1080p) [A1B2C3D4]`, see [parse_brackets]
+    pub blocks: Vec<String>,
+
+    /// If this is a sample or trailer rather than the real episode, such as
+    /// `Show.S01E02.sample.mkv`, see [is_sample]
+    pub is_sample: bool,
+
+    /// If this is a season pack such as `Show S01 Complete` rather than a single
+    /// episode, which is only given by [Season::capture] with an episode of `0`
+    /// and no [Capture::episodes], see [cap_season_pack]
+    pub is_pack: bool,
 }
 
-impl Capture {
-    /// Creates a new [Capture] from filepath and optional context to help it along
-    pub fn new(file_path: String, context: &Context) -> Result<Self, CaptureError> {
-        let (filename, ext) = cap_filename_ext(&file_path);
+/// Name cleaned up for capturing by both [Capture::new] and [interpret], with
+/// everything stripped from the [Preprocessed::filename] kept alongside it
+struct Preprocessed {
+    /// Name as given, cleaned up using [clean_input]
+    file_path: String,
+
+    /// Directories before the basename if [CaptureOptions::basename] is set
+    dirs: Vec<String>,
+
+    /// What's left of the basename to capture numbers and titles from
+    filename: String,
+
+    /// Extension of the name, or the [CaptureOptions::default_ext]
+    ext: Option<String>,
+
+    /// See [Capture::sub_language]
+    sub_language: Option<String>,
+
+    /// See [Capture::blocks]
+    blocks: Vec<String>,
+
+    /// See [Capture::crc]
+    crc: Option<String>,
+
+    /// Release group leading the name such as `[Group] Show - 01`
+    leading_group: Option<String>,
+
+    /// See [Capture::part]
+    part: Option<usize>,
+
+    /// See [Capture::release_version]
+    release_version: Option<usize>,
+
+    /// See [Capture::platform]
+    platform: Option<String>,
+
+    /// Release year if [CaptureOptions::detect_year] is set
+    year: Option<usize>,
+}
+
+impl Preprocessed {
+    /// Cleans up `file_path` using `options`, giving an error if it's longer
+    /// than [CaptureOptions::max_len]
+    fn new(file_path: String, options: &CaptureOptions) -> Result<Self, NameTooLong> {
+        check_name_len(&file_path, options.max_len)?;
+
+        let file_path = clean_input(file_path);
+        let (dirs, basename) = if options.basename {
+            split_basename(&file_path)
+        } else {
+            (vec![], file_path.as_str())
+        };
+        let dirs = dirs.into_iter().map(str::to_string).collect();
+        let (filename, ext) = cap_filename_ext(normalize_fullwidth(basename));
+        let filename = strip_site_prefix(filename);
+        let (filename, sub_language) = cap_sub_language(filename, ext.as_deref());
+        let blocks = parse_brackets(&filename);
+        let (filename, crc) = cap_crc(filename);
+        let (filename, leading_group) = match cap_leading_group(&filename) {
+            Some((rest, group)) if !cap_title(&rest).is_empty() => (rest, Some(group)),
+            _ => (filename, None),
+        };
+        let (filename, part) = cap_part(filename);
+        let (filename, release_version) = cap_release_version(filename);
+        let (filename, platform) = cap_platform(filename);
+        let ext = ext_or_default(ext, options.default_ext.as_deref());
+        let year = if options.detect_year {
+            cap_year(&filename)
+        } else {
+            None
+        };
 
         Ok(Self {
             file_path,
+            dirs,
+            filename,
+            ext,
+            sub_language,
+            blocks,
+            crc,
+            leading_group,
+            part,
+            release_version,
+            platform,
+            year,
+        })
+    }
+
+    /// Season found in the nearest of the [Preprocessed::dirs], if any
+    fn dir_season(&self, options: &CaptureOptions) -> Option<usize> {
+        self.dirs
+            .iter()
+            .rev()
+            .find_map(|dir| options.cap_season(&normalize_fullwidth(dir)).ok())
+            .map(|(season, _)| season)
+    }
+}
+
+impl Capture {
+    /// Creates a new [Capture] from filepath and optional context to help it
+    /// along, with extra behaviours enabled using `options`. The filepath is
+    /// cleaned up using [clean_input] first
+    pub fn new(
+        file_path: String,
+        context: &Context,
+        options: &CaptureOptions,
+    ) -> Result<Self, CaptureError> {
+        let preprocessed = Preprocessed::new(file_path, options)?;
+        let dir_season = preprocessed.dir_season(options);
+        let Preprocessed {
+            file_path,
+            dirs,
+            filename,
             ext,
-            episode: match context.episode {
-                Some(ep) => ep,
-                None => cap_episode(&filename)?,
+            sub_language,
+            blocks,
+            crc,
+            leading_group,
+            part,
+            release_version,
+            platform,
+            year,
+        } = preprocessed;
+
+        let dash_se = if options.dash_se {
+            cap_dash_se(&filename)
+        } else {
+            None
+        };
+        let merged_se = if options.merged {
+            cap_merged_se(&filename, year)
+        } else {
+            None
+        };
+        let episode = match context.episode {
+            Some(ep) => Ok((ep, Source::Context)),
+            None => match options
+                .cap_episode(&filename)
+                .or_else(|err| dash_se.map(|(_, ep)| (ep, Source::Dash)).ok_or(err))
+                .or_else(|err| merged_se.map(|(_, ep)| (ep, Source::Merged)).ok_or(err))
+            {
+                Err(_) if options.absolute => match cap_absolute_episode(&filename, year) {
+                    Err(_) if options.bare_episode => {
+                        cap_bare_episode(&filename, year).map(|ep| (ep, Source::Bare))
+                    }
+                    res => res.map(|ep| (ep, Source::Absolute)),
+                },
+                Err(_) if options.bare_episode => {
+                    cap_bare_episode(&filename, year).map(|ep| (ep, Source::Bare))
+                }
+                res => res,
             },
-            season: match context.season {
-                Some(se) => se,
-                None => cap_season(&filename)?,
+        };
+        let season = match context.season {
+            Some(se) => Ok((se, Source::Context)),
+            None => match options
+                .cap_season(&filename)
+                .or_else(|err| dash_se.map(|(se, _)| (se, Source::Dash)).ok_or(err))
+                .or_else(|err| merged_se.map(|(se, _)| (se, Source::Merged)).ok_or(err))
+            {
+                Err(err) => match dir_season {
+                    Some(se) => Ok((se, Source::Directory)),
+                    None if matches!(
+                        episode,
+                        Ok((_, Source::Bare)) | Ok((_, Source::Absolute))
+                    ) =>
+                    {
+                        Ok((1, Source::Default))
+                    }
+                    None => Err(err),
+                },
+                res => res,
             },
+        };
+        let ((episode, episode_source), (season, season_source), parsed) = match (episode, season) {
+            (Err(_), Err(_)) if options.lenient => {
+                ((0, Source::Default), (0, Source::Default), false)
+            }
+            (episode, season) => (episode?, season?, true),
+        };
+        let check_range = |name, value, source, min: Option<usize>, max: Option<usize>| {
+            if parsed
+                && source != Source::Context
+                && (min.map_or(false, |min| value < min) || max.map_or(false, |max| value > max))
+            {
+                Err(CaptureError::OutOfRange { name, value })
+            } else {
+                Ok(())
+            }
+        };
+        check_range(
+            "episode",
+            episode,
+            episode_source,
+            options.min_episode,
+            options.max_episode,
+        )?;
+        check_range(
+            "season",
+            season,
+            season_source,
+            options.min_season,
+            options.max_season,
+        )?;
+        let episodes = match cap_episodes(&filename) {
+            _ if !parsed => vec![],
+            Some(episodes)
+                if episode_source == Source::Regex
+                    && options.episode_regex.is_none()
+                    && episodes[0] == episode =>
+            {
+                episodes
+            }
+            _ => vec![episode],
+        };
+
+        let episode_title = if parsed {
+            cap_episode_title(&filename)
+        } else {
+            None
+        };
+        let title = match cap_title(&filename) {
+            title if title.is_empty() => dirs
+                .iter()
+                .rev()
+                .map(|dir| cap_title(&normalize_fullwidth(dir)))
+                .find(|title| !title.is_empty())
+                .unwrap_or(title),
+            title => title,
+        };
+        let episode_raw = options.cap_raw_num(&filename, episode, episode_source, false);
+        let season_raw = options.cap_raw_num(&filename, season, season_source, true);
+        let sample = is_sample(&file_path);
+        let tags = cap_tags(&filename);
+        let (video_codec, audio_codec) = cap_codecs(&tags);
+        let container = cap_container(ext.as_deref());
+
+        Ok(Self {
+            file_path,
+            ext,
+            sub_language,
+            episode,
+            episode_source,
+            episode_raw,
+            episodes,
+            part,
+            release_version,
+            season,
+            season_source,
+            season_raw,
+            parsed,
+            year,
+            container,
+            video_codec,
+            audio_codec,
+            audio_channels: cap_audio_channels(&filename),
+            platform,
+            tags,
+            group: cap_group(&filename).or(leading_group),
+            se_string: se_string(season, episode),
+            airdate: cap_airdate(&filename),
+            crc,
+            blocks,
+            is_sample: sample,
+            is_pack: false,
+            title,
+            episode_title,
             name: format_name(filename),
         })
     }
-}
 
-/// Seasonal input for the [season] rocket path
-#[derive(Debug, PartialEq, Clone, Deserialize)]
-pub struct Season {
-    /// Names for episodes, corrosponding to [Capture::file_path]
-    pub episodes: Vec<String>,
+    /// Checks if this capture refers to the same episode as `other`, only
+    /// comparing the season and episode numbers alongside the titles if both
+    /// have one, unlike the derived [PartialEq] which also compares
+    /// [Capture::file_path] and so on. Titles are compared using [title_key]
+    pub fn same_episode(&self, other: &Capture) -> bool {
+        let (title, other_title) = (title_key(&self.title), title_key(&other.title));
 
-    /// Season number
-    pub number: usize,
-}
+        self.season == other.season
+            && self.episode == other.episode
+            && (title.is_empty() || other_title.is_empty() || title == other_title)
+    }
 
-/// Gives help by providing available endpoints (to [episode] and [season])
-#[get("/tv")]
-pub fn help() -> &'static str {
-    "ROUTE /tv\n\n\nAbout\n    Allows tagging of tv shows with conventional season + episode tagging,\n    allowing manual explicit (optional) season or episode numbers to be passed\n    for clarification\n\n\nChild routes/endpoints\n    - /episode: Single episode tagging\n    - /season: Bulk per-season tagging"
-}
+    /// Re-derives the computed fields of this capture from its authoritative
+    /// [Capture::season], [Capture::episode], [Capture::title] and
+    /// [Capture::ext] without capturing from the filename again, such as after
+    /// a client hand-edits one of them. Titles are cleaned up using
+    /// [format_name] and raw numbers no longer matching are dropped
+    pub fn reparse(self) -> Self {
+        let raw = |raw: Option<String>, num: usize| {
+            raw.filter(|raw| raw.parse::<usize>().ok() == Some(num))
+        };
+        let episodes = if self.episodes.first() == Some(&self.episode) {
+            self.episodes
+        } else {
+            vec![self.episode]
+        };
 
-/// Gives help for how to use the [episode] path
-#[get("/tv/episode")]
-pub fn episode_help() -> &'static str {
-    "ENDPOINT POST /tv/episode?<name>&<episode>&<season>\n\n\nAbout\n    Tags a single episode of a tv show by it's required `name` with optional\n    passed context by including either `episode` or `season` query args."
-}
+        Self {
+            title: format_name(&self.title),
+            episode_title: self
+                .episode_title
+                .map(format_name)
+                .filter(|title| !title.is_empty()),
+            episode_raw: raw(self.episode_raw, self.episode),
+            episodes,
+            season_raw: raw(self.season_raw, self.season),
+            container: cap_container(self.ext.as_deref()),
+            se_string: se_string(self.season, self.episode),
+            ..self
+        }
+    }
 
-/// Single episode file capture with [Context] providers
-#[post("/tv/episode?<name>&<episode>&<season>")]
-pub fn episode(
-    name: String,
-    episode: Option<usize>,
-    season: Option<usize>,
-) -> ResponseModel<Capture> {
-    match Capture::new(name, &Context { episode, season }) {
-        Ok(cap) => ResponseModel::new(200, "Success", cap),
-        Err(err) => ResponseModel::basic(400, err),
+    /// Renders the canonical name of this capture using [render_episode],
+    /// keeping any [Capture::sub_language] before the [Capture::ext]. This is
+    /// the same as [DEFAULT_RENAME_TEMPLATE] but without the `Title - ` prefix
+    /// for captures with no [Capture::title]
+    pub fn render(&self) -> String {
+        self.render_with(false)
     }
-}
 
-/// Gives help for how to use the [season] path
-#[get("/tv/season")]
-pub fn season_help() -> &'static str {
-    "ENDPOINT POST /tv/season\n\n\nAbout\n    Tags entire array of episodes into a single season according to the provided\n    `season` parameter.\n\n\nExample JSON\n    {\n        \"season\": {\n            \"episodes\": [\n                \"a ep1.mp4\",\n                \"other hello season 6 episode 2.mpv\"\n            ],\n            \"number\": 5\n        }\n    }"
-}
+    /// Renders the canonical name of this capture like [Capture::render], but
+    /// with `preserve_padding` keeping the season and episode as written such as
+    /// `Show - S1E2.mkv`, see [Capture::season_raw] and [Capture::episode_raw]
+    pub fn render_with(&self, preserve_padding: bool) -> String {
+        let se = if preserve_padding {
+            self.raw_se_string()
+        } else {
+            self.se_string.clone()
+        };
 
-/// Multiple tv inputs corrosponding to seasons
-#[post("/tv/season", format = "json", data = "<season>")]
-pub fn season(season: Json<Season>) -> ResponseModel<Vec<Capture>> {
-    let season = season.into_inner();
-    let context = Context {
-        season: Some(season.number),
-        episode: None,
-    };
-    let mut caps = Vec::with_capacity(season.episodes.len());
+        join_episode(&self.title, &se, self.full_ext().as_deref())
+    }
 
-    for episode in season.episodes {
-        caps.push(match Capture::new(episode, &context) {
-            Ok(cap) => cap,
-            Err(err) => return ResponseModel::basic(400, err),
+    /// Renders this capture into a `template` such as `{title} {season}x{episode:02}{ext}`,
+    /// see [render_template] for more infomation
+    pub fn render_template(&self, template: &str) -> Result<String, TemplateError> {
+        self.render_template_with(template, false)
+    }
+
+    /// Renders this capture into a `template` like [Capture::render_template],
+    /// but with `preserve_padding` substituting the season and episode as written
+    /// and ignoring their padding formats, so `S{season:02}` gives `S1` for `S1E2`
+    pub fn render_template_with(
+        &self,
+        template: &str,
+        preserve_padding: bool,
+    ) -> Result<String, TemplateError> {
+        render_template(template, |name| match name {
+            "season" if preserve_padding && self.season_raw.is_some() => {
+                Some(TemplateValue::Verbatim(self.season_raw.clone()))
+            }
+            "episode" if preserve_padding && self.episode_raw.is_some() => {
+                Some(TemplateValue::Verbatim(self.episode_raw.clone()))
+            }
+            "se" if preserve_padding => Some(TemplateValue::Text(Some(self.raw_se_string()))),
+            name => self.placeholder(name),
         })
     }
 
-    ResponseModel::new(200, "Success", caps)
-}
+    /// Renders this capture into the naming convention of Plex such as
+    /// `Show Name (2019) - s01e02 - Episode Title.mkv`, with the year only given
+    /// if found, a range such as `s01e02-e03` for multiple episodes and the
+    /// episode title only given if found
+    pub fn render_plex(&self) -> String {
+        let title = match self.year {
+            Some(year) => {
+                let title = Regex::new(&format!(r"[(\[]?\b{}\b[)\]]?", year))
+                    .expect("Could not make year regex")
+                    .replace_all(&self.title, " ")
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("{} ({})", title, year).trim().to_string()
+            }
+            None => self.title.clone(),
+        };
+        let mut se = format!("s{}e{}", pad_num(self.season, 2), pad_num(self.episode, 2));
+        if let Some(last) = self.episodes.last().filter(|_| self.episodes.len() > 1) {
+            se.push_str(&format!("-e{}", pad_num(*last, 2)));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let name = vec![Some(title), Some(se), self.episode_title.clone()]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" - ");
 
-    const TEST_1: (&str, usize, usize) = ("hello s01 e02 hi.mp4", 2, 1);
-    const TEST_2: (&str, usize, usize) = ("xs01e20.epc", 20, 1);
-    const TEST_3: (&str, usize, usize) = ("SEASON3EPISODE4", 4, 3);
-    const TEST_4: (&str, usize, usize) = ("EPIsode2 and SEASOn 0002.exy", 2, 2);
-    const TEST_5: (&str, usize, usize) = ("hiS01E04.ex", 4, 1);
+        format!("{}{}", name, self.full_ext().unwrap_or_default())
+    }
 
-    #[test]
-    fn episode_simple() {
-        assert_eq!(cap_episode(TEST_1.0), Ok(TEST_1.1));
-        assert_eq!(cap_episode(TEST_2.0), Ok(TEST_2.1));
+    /// Renders this capture into a Kodi or Jellyfin style `.nfo` sidecar, being
+    /// an `<episodedetails>` xml document with the show title, season, episode
+    /// and year if found. The episode title is used as the `<title>` if found,
+    /// otherwise falling back to the show title
+    pub fn to_nfo(&self) -> String {
+        let element =
+            |name: &str, value: &str| format!("    <{0}>{1}</{0}>\n", name, escape_xml(value));
+        let mut nfo = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<episodedetails>\n",
+        );
+
+        nfo.push_str(&element(
+            "title",
+            self.episode_title.as_ref().unwrap_or(&self.title),
+        ));
+        nfo.push_str(&element("showtitle", &self.title));
+        nfo.push_str(&element("season", &self.season.to_string()));
+        nfo.push_str(&element("episode", &self.episode.to_string()));
+        if let Some(year) = self.year {
+            nfo.push_str(&element("year", &year.to_string()));
+        }
+        nfo.push_str("</episodedetails>\n");
+
+        nfo
+    }
+
+    /// Combines the season and episode as written in the form of `S1E2`,
+    /// padding either to two digits if it wasn't found in the filename itself
+    fn raw_se_string(&self) -> String {
+        format!(
+            "S{}E{}",
+            self.season_raw
+                .clone()
+                .unwrap_or_else(|| pad_num(self.season, 2)),
+            self.episode_raw
+                .clone()
+                .unwrap_or_else(|| pad_num(self.episode, 2))
+        )
+    }
+
+    /// Gets the value of a template placeholder for use in [render_template],
+    /// returning [None] if `name` isn't a known placeholder
+    fn placeholder(&self, name: &str) -> Option<TemplateValue> {
+        Some(match name {
+            "title" => TemplateValue::Text(Some(self.title.clone())),
+            "episode_title" => TemplateValue::Text(self.episode_title.clone()),
+            "name" => TemplateValue::Text(Some(self.name.clone())),
+            "season" => TemplateValue::Number(Some(self.season)),
+            "episode" => TemplateValue::Number(Some(self.episode)),
+            "se" => TemplateValue::Text(Some(self.se_string.clone())),
+            "ext" => TemplateValue::Text(self.full_ext()),
+            "year" => TemplateValue::Number(self.year),
+            "group" => TemplateValue::Text(self.group.clone()),
+            "part" => TemplateValue::Number(self.part),
+            _ => return None,
+        })
+    }
+
+    /// Extension including any [Capture::sub_language] before the [Capture::ext]
+    fn full_ext(&self) -> Option<String> {
+        match (&self.sub_language, &self.ext) {
+            (Some(language), Some(ext)) => Some(format!(".{}{}", language, ext)),
+            (_, ext) => ext.clone(),
+        }
+    }
+}
+
+/// Single planned rename from the [rename_plan](routes::rename_plan) path
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Rename {
+    /// Original name as given
+    pub from: String,
+
+    /// Canonical name to rename to from [Capture::render], which is [None] if
+    /// the name couldn't be captured
+    pub to: Option<String>,
+
+    /// If another name in the same plan renames to the same [Rename::to],
+    /// compared case insensitively, meaning renaming would clobber files
+    pub conflict: bool,
+
+    /// Why the name couldn't be captured if [Rename::to] is [None]
+    pub error: Option<String>,
+}
+
+/// Plans renames of all `names` to their canonical name without renaming
+/// anything, marking any [Rename::conflict]s where names would collide. Names
+/// are rendered using [Capture::render] or into a `template` if given, see
+/// [Capture::render_template], with `preserve_padding` keeping the season and
+/// episode padded as they were written such as `S1E2`. Names longer than
+/// `max_len` can't be captured, see [CaptureOptions::max_len]
+pub fn plan_renames(
+    names: Vec<String>,
+    template: Option<&str>,
+    preserve_padding: bool,
+    max_len: Option<usize>,
+) -> Result<Vec<Rename>, TemplateError> {
+    let options = CaptureOptions {
+        max_len,
+        ..Default::default()
+    };
+
+    plan_renames_with(names, &options, |cap| match template {
+        Some(template) => cap.render_template_with(template, preserve_padding),
+        None => Ok(cap.render_with(preserve_padding)),
+    })
+}
+
+/// Plans renames of all `names` into the naming convention of Plex like
+/// [plan_renames], detecting years for the title, see [Capture::render_plex]
+pub fn plan_plex_renames(names: Vec<String>, max_len: Option<usize>) -> Vec<Rename> {
+    let options = CaptureOptions {
+        detect_year: true,
+        max_len,
+        ..Default::default()
+    };
+
+    plan_renames_with(names, &options, |cap| {
+        Ok::<_, Infallible>(cap.render_plex())
+    })
+    .unwrap_or_else(|never| match never {})
+}
+
+/// Plans renames of all `names` captured using `options`, rendering each using
+/// `render` and marking any [Rename::conflict]s, see [plan_renames]
+fn plan_renames_with<E>(
+    names: Vec<String>,
+    options: &CaptureOptions,
+    render: impl Fn(&Capture) -> Result<String, E>,
+) -> Result<Vec<Rename>, E> {
+    let mut plan = Vec::with_capacity(names.len());
+
+    for name in names {
+        plan.push(
+            match Capture::new(name.clone(), &Context::default(), options) {
+                Ok(cap) => Rename {
+                    from: name,
+                    to: Some(render(&cap)?),
+                    conflict: false,
+                    error: None,
+                },
+                Err(err) => Rename {
+                    from: name,
+                    to: None,
+                    conflict: false,
+                    error: Some(err.to_string()),
+                },
+            },
+        );
+    }
+
+    let mut targets: HashMap<String, usize> = HashMap::new();
+    for to in plan.iter().filter_map(|rename| rename.to.as_ref()) {
+        *targets.entry(to.to_lowercase()).or_default() += 1;
+    }
+    for rename in plan.iter_mut() {
+        rename.conflict = match &rename.to {
+            Some(to) => targets[&to.to_lowercase()] > 1,
+            None => false,
+        };
+    }
+
+    Ok(plan)
+}
+
+/// Single problem found with a name by [lint_name]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Lint {
+    /// Which of the [LINT_RULES] found this problem
+    pub rule: &'static str,
+
+    /// Human readable explanation of the problem
+    pub message: String,
+}
+
+/// Problems found with a single name alongside how it should be named, as given
+/// by the [lint](routes::lint) path
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct LintReport {
+    /// Original name as given
+    pub name: String,
+
+    /// Every problem found, which is empty for clean names
+    pub lints: Vec<Lint>,
+
+    /// Canonical name from [Capture::render], which is [None] if the name
+    /// couldn't be captured
+    pub suggestion: Option<String>,
+}
+
+/// Lints a single `name` for library hygiene using the chosen `rules` from
+/// [LINT_RULES], suggesting its canonical name. Names are captured leniently
+/// using [CaptureOptions::bare_episode] and [CaptureOptions::absolute] so that
+/// a missing season gives a lint rather than an error. Names longer than
+/// `max_len` are linted as `unparsed`, see [CaptureOptions::max_len]
+pub fn lint_name(name: String, rules: &[&str], max_len: Option<usize>) -> LintReport {
+    let options = CaptureOptions {
+        bare_episode: true,
+        absolute: true,
+        detect_year: true,
+        max_len,
+        ..Default::default()
+    };
+    let cap = Capture::new(name.clone(), &Context::default(), &options);
+    let mut lints = vec![];
+    let mut lint = |rule: &'static str, message: String| {
+        if rules.contains(&rule) {
+            lints.push(Lint { rule, message })
+        }
+    };
+
+    match &cap {
+        Ok(cap) => {
+            if cap.season_source == Source::Default {
+                lint(
+                    "missing_season",
+                    format!("No season marker, assuming season {}", cap.season),
+                );
+            }
+
+            let raw = (cap.season_raw.as_deref(), cap.episode_raw.as_deref());
+            if let (Some(season), Some(episode)) = raw {
+                if (season, episode) != (&pad_num(cap.season, 2), &pad_num(cap.episode, 2)) {
+                    lint(
+                        "inconsistent_padding",
+                        format!(
+                            "Season and episode are written as `{}` rather than `{}`",
+                            cap.raw_se_string(),
+                            cap.se_string
+                        ),
+                    );
+                }
+            }
+
+            for tag in cap_tags(&cap.title) {
+                lint(
+                    "stray_tags",
+                    format!("Quality tag `{}` is part of the title", tag),
+                );
+            }
+        }
+        Err(err) => lint("unparsed", format!("Could not be captured: {}", err)),
+    }
+
+    let non_ascii: String = name.chars().filter(|c| !c.is_ascii()).collect();
+    if !non_ascii.is_empty() {
+        lint(
+            "non_ascii",
+            format!("Contains non-ascii characters `{}`", non_ascii),
+        );
+    }
+
+    LintReport {
+        name,
+        lints,
+        suggestion: cap.ok().map(|cap| cap.render()),
+    }
+}
+
+/// Single regex match found whilst inspecting a filename, used inside of
+/// [Inspection] to show exactly what was matched and where
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct InspectMatch {
+    /// Raw text matched by the regex
+    pub text: String,
+
+    /// Byte offset of the start of this match inside of the filename
+    pub start: usize,
+
+    /// Byte offset of the end of this match inside of the filename
+    pub end: usize,
+
+    /// Number parsed from [InspectMatch::text] using [cap_num], which is [None]
+    /// if the number was too large
+    pub value: Option<usize>,
+}
+
+impl From<Match<'_>> for InspectMatch {
+    fn from(captured: Match<'_>) -> Self {
+        Self {
+            text: captured.as_str().to_string(),
+            start: captured.start(),
+            end: captured.end(),
+            value: cap_num(captured),
+        }
+    }
+}
+
+/// Single plausible reading of the season and episode of a filename, as given
+/// by [interpret]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Interpretation {
+    /// Season number of this reading
+    pub season: usize,
+
+    /// Episode number of this reading
+    pub episode: usize,
+
+    /// Strategy used to read the numbers, being the [Source] of the episode
+    pub strategy: Source,
+
+    /// Heuristic confidence of this reading from `0.0` to `1.0`
+    pub confidence: f64,
+}
+
+/// Gives every plausible season and episode of a `file_path` using each of the
+/// capture strategies rather than committing to the first like [Capture::new],
+/// ranked by a heuristic confidence. Markers found using `options` rank
+/// highest, followed by [cap_dash_se], [cap_absolute_episode], a merged number
+/// like `102` as season 1 episode 2 and finally [cap_bare_episode], with each
+/// season and episode only given once. Names are cleaned up the same way as
+/// [Capture::new], so years are only left out of numbers using
+/// [CaptureOptions::detect_year] and names longer than [CaptureOptions::max_len]
+/// give an error
+pub fn interpret(
+    file_path: String,
+    options: &CaptureOptions,
+) -> Result<Vec<Interpretation>, NameTooLong> {
+    let preprocessed = Preprocessed::new(file_path, options)?;
+    let (filename, year) = (&preprocessed.filename, preprocessed.year);
+
+    let marked = match (
+        options.cap_season(filename),
+        preprocessed.dir_season(options),
+        options.cap_episode(filename),
+    ) {
+        (Ok((season, _)), _, Ok((episode, source))) => {
+            let confidence = if source == Source::Words { 0.9 } else { 1.0 };
+            Some((season, episode, source, confidence))
+        }
+        (Err(_), Some(season), Ok((episode, source))) => Some((season, episode, source, 0.8)),
+        (Err(_), None, Ok((episode, source))) => Some((1, episode, source, 0.7)),
+        _ => None,
+    };
+    let merged = cap_merged_se(filename, year)
+        .map(|(season, episode)| (season, episode, Source::Merged, 0.4));
+    let candidates = marked
+        .into_iter()
+        .chain(cap_dash_se(filename).map(|(season, episode)| (season, episode, Source::Dash, 0.6)))
+        .chain(
+            cap_absolute_episode(filename, year)
+                .ok()
+                .map(|episode| (1, episode, Source::Absolute, 0.5)),
+        )
+        .chain(merged)
+        .chain(
+            cap_bare_episode(filename, year)
+                .ok()
+                .map(|episode| (1, episode, Source::Bare, 0.3)),
+        );
+
+    let mut interpretations: Vec<Interpretation> = vec![];
+    for (season, episode, strategy, confidence) in candidates {
+        if !interpretations
+            .iter()
+            .any(|found| (found.season, found.episode) == (season, episode))
+        {
+            interpretations.push(Interpretation {
+                season,
+                episode,
+                strategy,
+                confidence,
+            });
+        }
+    }
+
+    Ok(interpretations)
+}
+
+/// Diagnostics for every season and episode match in a filename, without
+/// committing to any single one like [Capture] does
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Inspection {
+    /// Original, pre-parsed file_path provided
+    pub file_path: String,
+
+    /// Filename used for matching, which is [Inspection::file_path] without the
+    /// extension; all match spans are relative to this
+    pub filename: String,
+
+    /// All matches for [SEASON_REGEX] in order of appearance
+    pub seasons: Vec<InspectMatch>,
+
+    /// All matches for [EPISODE_REGEX] in order of appearance
+    pub episodes: Vec<InspectMatch>,
+}
+
+impl Inspection {
+    /// Creates a new [Inspection] by running both regexes over the filename
+    pub fn new(file_path: String) -> Self {
+        let (filename, _) = cap_filename_ext(normalize_fullwidth(&file_path));
+
+        let seasons = Regex::new(SEASON_REGEX)
+            .expect("Could not make season regex")
+            .find_iter(&filename)
+            .map(InspectMatch::from)
+            .collect();
+        let episodes = episode_markers(&filename)
+            .into_iter()
+            .map(InspectMatch::from)
+            .collect();
+
+        Self {
+            file_path,
+            filename,
+            seasons,
+            episodes,
+        }
+    }
+}
+
+/// Splits a `file_path` into its parent directories and basename on both `/`
+/// and `\` separators, ignoring empty directories such as from a leading `/`
+pub fn split_basename(file_path: &str) -> (Vec<&str>, &str) {
+    let mut parts: Vec<&str> = file_path.split(|c| c == '/' || c == '\\').collect();
+    let basename = parts.pop().unwrap_or_default();
+    parts.retain(|dir| !dir.trim().is_empty());
+
+    (parts, basename)
+}
+
+/// Combines a `season` and `episode` into the form of `S01E02`, padding both
+/// to at least two digits
+pub fn se_string(season: usize, episode: usize) -> String {
+    format!("S{}E{}", pad_num(season, 2), pad_num(episode, 2))
+}
+
+/// Renders a canonical episode name such as `Title - S01E02.mkv` from its parts,
+/// with the `ext` given either with or without a leading dot
+pub fn render_episode(title: &str, season: usize, episode: usize, ext: Option<&str>) -> String {
+    join_episode(title, &se_string(season, episode), ext)
+}
+
+/// Joins a `title`, already combined `se` string such as `S01E02` and `ext`
+/// into an episode name for [render_episode] and [Capture::render_with]
+fn join_episode(title: &str, se: &str, ext: Option<&str>) -> String {
+    let ext = match ext {
+        Some(ext) if !ext.is_empty() && !ext.starts_with('.') => format!(".{}", ext),
+        Some(ext) => ext.to_string(),
+        None => String::new(),
+    };
+
+    format!(
+        "{}{}{}",
+        if title.is_empty() {
+            String::new()
+        } else {
+            format!("{} - ", title)
+        },
+        se,
+        ext
+    )
+}
+
+/// Captures multiple `file_paths` with the same shared `context` and `options`,
+/// stopping at the first file which fails to capture
+pub fn cap_batch(
+    file_paths: Vec<String>,
+    context: &Context,
+    options: &CaptureOptions,
+) -> Result<Vec<Capture>, CaptureError> {
+    file_paths
+        .into_iter()
+        .map(|file_path| Capture::new(file_path, context, options))
+        .collect()
+}
+
+/// Captures grouped by season from [cap_grouped], serialized as an object keyed
+/// by season number alongside an `unknown` key. Seasons are kept in a [BTreeMap]
+/// so they're always serialized in ascending numeric order, whatever the order
+/// of the names given
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct SeasonGroups {
+    /// Captures keyed by [Capture::season], sorted by episode within each season
+    #[serde(flatten)]
+    pub seasons: BTreeMap<usize, Vec<Capture>>,
+
+    /// Original names of files which failed to capture
+    pub unknown: Vec<String>,
+}
+
+/// Captures multiple `file_paths` independently like [cap_batch] with the same
+/// shared `context` and `options`, grouping them by season instead of stopping
+/// at the first file which fails to capture
+pub fn cap_grouped(
+    file_paths: Vec<String>,
+    context: &Context,
+    options: &CaptureOptions,
+    metrics: &Metrics,
+) -> SeasonGroups {
+    let mut groups = SeasonGroups::default();
+
+    for file_path in file_paths {
+        let cap = Capture::new(file_path.clone(), context, options);
+        metrics.record_parse(&cap);
+
+        match cap {
+            Ok(cap) => groups.seasons.entry(cap.season).or_default().push(cap),
+            Err(_) => groups.unknown.push(file_path),
+        }
+    }
+
+    for caps in groups.seasons.values_mut() {
+        caps.sort_by_key(|cap| cap.episode);
+    }
+
+    groups
+}
+
+/// Summary of a single season inside of a [ShowSummary]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SeasonSummary {
+    /// Season number
+    pub number: usize,
+
+    /// Number of distinct episodes found, counting each of [Capture::episodes]
+    pub episodes: usize,
+
+    /// Inclusive `[start, end]` ranges of episodes missing between `1` and the
+    /// highest episode found, which may mean incomplete downloads. These are
+    /// ranges so a single huge episode number can't give a huge list
+    pub missing: Vec<[usize; 2]>,
+}
+
+/// Summary of a single show inside of a [LibrarySummary]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ShowSummary {
+    /// Title of the show from the first file found, see [Capture::title]
+    pub title: String,
+
+    /// All seasons of this show sorted by number
+    pub seasons: Vec<SeasonSummary>,
+}
+
+/// Summary of the structure of a library of files from [summarize]
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct LibrarySummary {
+    /// Number of distinct shows found
+    pub total_shows: usize,
+
+    /// Number of distinct episodes found across all shows
+    pub total_episodes: usize,
+
+    /// All shows found sorted by title, compared case insensitively
+    pub shows: Vec<ShowSummary>,
+
+    /// Original names of files which failed to capture
+    pub unknown: Vec<String>,
+}
+
+/// Summarizes seasons `groups` from [cap_grouped] into distinct shows by their
+/// [Capture::title], compared case insensitively, counting the episodes of each
+/// season and finding any gaps in episode numbering
+pub fn summarize(groups: SeasonGroups) -> LibrarySummary {
+    let mut shows: BTreeMap<String, (String, BTreeMap<usize, Vec<usize>>)> = BTreeMap::new();
+
+    for (season, caps) in groups.seasons {
+        for cap in caps {
+            shows
+                .entry(cap.title.to_lowercase())
+                .or_insert_with(|| (cap.title.clone(), BTreeMap::new()))
+                .1
+                .entry(season)
+                .or_default()
+                .extend(&cap.episodes);
+        }
+    }
+
+    let mut summary = LibrarySummary {
+        unknown: groups.unknown,
+        ..Default::default()
+    };
+
+    for (_, (title, seasons)) in shows {
+        let seasons: Vec<SeasonSummary> = seasons
+            .into_iter()
+            .map(|(number, mut episodes)| {
+                episodes.sort_unstable();
+                episodes.dedup();
+
+                let mut missing = vec![];
+                let mut last = 0;
+                for &episode in &episodes {
+                    if episode > last + 1 {
+                        missing.push([last + 1, episode - 1]);
+                    }
+                    last = episode;
+                }
+
+                SeasonSummary {
+                    number,
+                    episodes: episodes.len(),
+                    missing,
+                }
+            })
+            .collect();
+
+        summary.total_episodes += seasons.iter().map(|season| season.episodes).sum::<usize>();
+        summary.shows.push(ShowSummary { title, seasons });
+    }
+    summary.total_shows = summary.shows.len();
+
+    summary
+}
+
+/// Parsed title of a name snapped to the closest known title, see [match_title]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct TitleMatch {
+    /// Raw title parsed from the name, see [cap_title]
+    pub title: String,
+
+    /// Closest candidate to [TitleMatch::title], if any scored at least
+    /// [MIN_MATCH_SCORE]
+    pub matched: Option<String>,
+
+    /// Score of [TitleMatch::matched] from `0.0` to `1.0`, see [similarity]
+    pub score: Option<f64>,
+}
+
+/// Matches the title parsed from `name` to the most similar of the known
+/// `candidates`, such as correcting `Breakin Bad` to `Breaking Bad`. The first
+/// candidate wins ties and candidates scoring under [MIN_MATCH_SCORE] never match.
+/// Blank titles and candidates never match, as two empty strings are identical.
+/// Candidates whose length alone rules out [MIN_MATCH_SCORE] are skipped without
+/// scoring them, so huge candidates are cheap
+pub fn match_title(name: &str, candidates: &[String]) -> TitleMatch {
+    let (filename, _) = cap_filename_ext(normalize_fullwidth(&clean_input(name)));
+    let title = cap_title(&filename);
+    let title_len = title.to_lowercase().chars().count();
+    let best = candidates
+        .iter()
+        .filter(|candidate| !title.trim().is_empty() && !candidate.trim().is_empty())
+        .filter(|candidate| {
+            // the edit distance is at least the difference in length
+            let len = candidate.to_lowercase().chars().count();
+            len.min(title_len) as f64 >= len.max(title_len) as f64 * MIN_MATCH_SCORE
+        })
+        .map(|candidate| (candidate, similarity(&title, candidate)))
+        .fold(
+            None,
+            |best: Option<(&String, f64)>, (candidate, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((candidate, score)),
+            },
+        )
+        .filter(|(_, score)| *score >= MIN_MATCH_SCORE);
+
+    TitleMatch {
+        title,
+        matched: best.map(|(candidate, _)| candidate.clone()),
+        score: best.map(|(_, score)| score),
+    }
+}
+
+/// Name and known titles for the [match_titles](routes::match_titles) path
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct MatchRequest {
+    /// Name of the file to parse the title of
+    pub name: String,
+
+    /// Known titles to match against, see [match_title]
+    pub candidates: Vec<String>,
+}
+
+/// Ranks the quality of a file from its [Capture::tags] as a resolution then
+/// source score, where higher is better and `0` means no known tag was found.
+/// Scores come from [RESOLUTION_RANKING] and [SOURCE_RANKING] respectively
+pub fn quality_rank(tags: &[String]) -> (usize, usize) {
+    let score = |ranking: &[&str]| {
+        tags.iter()
+            .filter_map(|tag| {
+                ranking
+                    .iter()
+                    .position(|known| tag.eq_ignore_ascii_case(known))
+            })
+            .map(|ind| ranking.len() - ind)
+            .max()
+            .unwrap_or(0)
+    };
+
+    (score(RESOLUTION_RANKING), score(SOURCE_RANKING))
+}
+
+/// Files kept and removed by [dedup_by_quality]
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct Dedup {
+    /// Best quality file of each distinct episode, in order of first appearance
+    pub kept: Vec<String>,
+
+    /// Lower quality duplicates of a file inside of [Dedup::kept]
+    pub removed: Vec<String>,
+
+    /// Original names of files which failed to capture
+    pub unknown: Vec<String>,
+}
+
+/// Keeps only the best quality capture of each distinct episode found using
+/// [Capture::same_episode], ranked by [quality_rank]. As files are removed, the
+/// titles must always be equal using [title_key] so a capture without a title
+/// is never a duplicate of one with a title. Equal quality duplicates keep
+/// whichever came first
+pub fn dedup_by_quality(caps: Vec<Capture>) -> Dedup {
+    let mut kept: Vec<Capture> = Vec::new();
+    let mut removed = Vec::new();
+
+    for cap in caps {
+        let title = title_key(&cap.title);
+
+        match kept
+            .iter_mut()
+            .find(|other| title_key(&other.title) == title && other.same_episode(&cap))
+        {
+            Some(other) if quality_rank(&cap.tags) > quality_rank(&other.tags) => {
+                removed.push(std::mem::replace(other, cap).file_path)
+            }
+            Some(_) => removed.push(cap.file_path),
+            None => kept.push(cap),
+        }
+    }
+
+    Dedup {
+        kept: kept.into_iter().map(|cap| cap.file_path).collect(),
+        removed,
+        unknown: vec![],
+    }
+}
+
+/// Episode claimed by more than one file, as found by [find_duplicates]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Duplicate {
+    /// Season number shared by every file
+    pub season: usize,
+
+    /// Episode number claimed by every file
+    pub episode: usize,
+
+    /// Original names of every file claiming this episode, in order given
+    pub names: Vec<String>,
+}
+
+/// Finds every episode claimed by more than one of the `caps`, a common sign of a
+/// bad download. Files containing multiple episodes claim each of their
+/// [Capture::episodes], and duplicates are sorted by season then episode
+pub fn find_duplicates(caps: &[Capture]) -> Vec<Duplicate> {
+    let mut claims: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+    for cap in caps {
+        for episode in &cap.episodes {
+            claims
+                .entry((cap.season, *episode))
+                .or_default()
+                .push(cap.file_path.clone());
+        }
+    }
+
+    claims
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|((season, episode), names)| Duplicate {
+            season,
+            episode,
+            names,
+        })
+        .collect()
+}
+
+/// Body of a response from the [season](routes::season) path, which is just the
+/// episodes unless duplicates were asked for
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(untagged)]
+pub enum SeasonBody {
+    /// Every episode with its chosen fields, see [Season::capture]
+    Episodes(Vec<Selected<Capture>>),
+
+    /// Every episode alongside any episodes claimed by more than one file, see
+    /// [find_duplicates]
+    WithDuplicates {
+        episodes: Vec<Selected<Capture>>,
+        duplicates: Vec<Duplicate>,
+    },
+}
+
+/// Which episodes of a season are present against a known total, as given by
+/// [completeness]
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct Completeness {
+    /// Episodes from `1` up to the total which were found, sorted
+    pub present: Vec<usize>,
+
+    /// Episodes from `1` up to the total which weren't found, sorted
+    pub missing: Vec<usize>,
+
+    /// Original names of files containing an episode which was already found or
+    /// is outside of `1` up to the total, in order given
+    pub extra: Vec<String>,
+
+    /// Original names of files which failed to capture
+    pub unknown: Vec<String>,
+}
+
+/// Checks which episodes from `1` up to a known `total` are present in the
+/// `caps` of a single season, ignoring their season numbers. Files containing
+/// multiple episodes count towards each of their [Capture::episodes], with any
+/// duplicate or out of range episode marking the file as [Completeness::extra].
+/// Every missing episode is listed, so the `total` should be bounded such as by
+/// [MAX_COMPLETENESS_TOTAL]
+pub fn completeness(caps: Vec<Capture>, total: usize) -> Completeness {
+    let mut found = BTreeSet::new();
+    let mut extra = vec![];
+
+    for cap in caps {
+        let mut is_extra = false;
+        for episode in &cap.episodes {
+            if !(1..=total).contains(episode) || !found.insert(*episode) {
+                is_extra = true;
+            }
+        }
+
+        if is_extra {
+            extra.push(cap.file_path);
+        }
+    }
+
+    Completeness {
+        missing: (1..=total)
+            .filter(|episode| !found.contains(episode))
+            .collect(),
+        present: found.into_iter().collect(),
+        extra,
+        unknown: vec![],
+    }
+}
+
+/// Single episode of a [Season], given as either a plain name or an object with
+/// its own context overriding the shared season, e.g. for specials in season 0
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SeasonEpisode {
+    /// Plain name using the shared [Season::context]
+    Name(String),
+
+    /// Name with its own `season` and/or `episode`, with a missing `season`
+    /// falling back to [Season::number]
+    Override {
+        name: String,
+        season: Option<usize>,
+        episode: Option<usize>,
+    },
+}
+
+impl SeasonEpisode {
+    /// Gives the name of this episode, corrosponding to [Capture::file_path]
+    pub fn name(&self) -> &str {
+        match self {
+            SeasonEpisode::Name(name) => name,
+            SeasonEpisode::Override { name, .. } => name,
+        }
+    }
+}
+
+impl From<String> for SeasonEpisode {
+    fn from(name: String) -> Self {
+        SeasonEpisode::Name(name)
+    }
+}
+
+/// Seasonal input for the [season](routes::season) rocket path
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct Season {
+    /// Names for episodes, corrosponding to [Capture::file_path], which may
+    /// override the shared season individually, see [SeasonEpisode]
+    pub episodes: Vec<SeasonEpisode>,
+
+    /// Season number
+    pub number: usize,
+}
+
+impl Season {
+    /// Creates the [Context] shared by every episode of this season, which only
+    /// ever contains the season number. An episode override never makes sense
+    /// for a whole season, so episodes are always captured independently per file
+    pub fn context(&self) -> Context {
+        Context {
+            season: Some(self.number),
+            episode: None,
+        }
+    }
+
+    /// Captures every episode of this season using the shared [Season::context]
+    /// unless overridden, stopping at the first episode which fails to capture.
+    /// Plain names of season packs such as `Show S01 Complete` are given as
+    /// [Capture::is_pack] captures of episode `0`, with their title given to any
+    /// episode without a title of its own, see [cap_season_pack]
+    pub fn capture(self, options: &CaptureOptions) -> Result<Vec<Capture>, CaptureError> {
+        let context = self.context();
+        let episodes: Vec<_> = self
+            .episodes
+            .into_iter()
+            .map(|episode| {
+                let pack = match &episode {
+                    SeasonEpisode::Name(name) => {
+                        cap_season_pack(&cap_filename_ext(clean_input(name)).0)
+                    }
+                    _ => None,
+                };
+
+                (episode, pack)
+            })
+            .collect();
+        let pack_title = episodes
+            .iter()
+            .filter_map(|(_, pack)| pack.clone())
+            .find(|title| !title.is_empty());
+
+        episodes
+            .into_iter()
+            .map(|(episode, pack)| match (episode, pack) {
+                (SeasonEpisode::Name(name), Some(title)) => Capture::new(
+                    name,
+                    &Context {
+                        season: context.season,
+                        episode: Some(0),
+                    },
+                    options,
+                )
+                .map(|cap| Capture {
+                    title,
+                    episodes: vec![],
+                    is_pack: true,
+                    ..cap
+                }),
+                (SeasonEpisode::Name(name), None) => Capture::new(name, &context, options),
+                (
+                    SeasonEpisode::Override {
+                        name,
+                        season,
+                        episode,
+                    },
+                    _,
+                ) => Capture::new(
+                    name,
+                    &Context {
+                        season: season.or(context.season),
+                        episode,
+                    },
+                    options,
+                ),
+            })
+            .map(|cap| match (cap, &pack_title) {
+                (Ok(cap), Some(title)) if cap.title.is_empty() => Ok(Capture {
+                    title: title.clone(),
+                    ..cap
+                }),
+                (cap, _) => cap,
+            })
+            .collect()
+    }
+}
+
+/// Parses the optional comma separated `fields` of a [Capture] to respond with,
+/// giving an error listing [CAPTURE_FIELDS] if any are unknown
+pub fn parse_capture_fields(fields: Option<&str>) -> Result<Option<Vec<String>>, String> {
+    fields
+        .map(|fields| parse_fields(fields, CAPTURE_FIELDS))
+        .transpose()
+}
+
+/// Wraps each of the `caps` to only serialize the chosen `fields`, see [Selected]
+pub fn select_fields(caps: Vec<Capture>, fields: &Option<Vec<String>>) -> Vec<Selected<Capture>> {
+    caps.into_iter()
+        .map(|cap| Selected::new(cap, fields.clone()))
+        .collect()
+}
+
+/// Capture corrected by [Capture::reparse] alongside its [Capture::render],
+/// flattened into a single object so it may be edited and sent again
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Reparsed {
+    /// Corrected capture
+    #[serde(flatten)]
+    pub capture: Capture,
+
+    /// Canonical name of the corrected capture
+    pub render: String,
+}
+
+/// Lazily captures newline-delimited names read from `names` as they arrive,
+/// giving a server-sent event in the form of `data: {json}` for each one, where
+/// the json is a [ResponseModel] of the [Capture]. A flush is requested after
+/// each event by returning [io::ErrorKind::WouldBlock] so clients get events
+/// live, meaning this is meant to be read by rocket's `sse` feature
+pub struct CaptureStream<'r, R> {
+    /// Source of newline-delimited names
+    names: R,
+
+    /// Remaining bytes of the current event being read
+    event: Cursor<Vec<u8>>,
+
+    /// If a flush should be requested before the next event
+    flush: bool,
+
+    /// Counters to record each capture into
+    metrics: &'r Metrics,
+
+    /// Maximum length of a single name, see [CaptureOptions::max_len]
+    max_len: usize,
+}
+
+impl<'r, R: BufRead> CaptureStream<'r, R> {
+    /// Creates a new stream of events from `names`, recorded into `metrics`,
+    /// giving an error event for names longer than `max_len`
+    pub fn new(names: R, metrics: &'r Metrics, max_len: usize) -> Self {
+        Self {
+            names,
+            event: Cursor::new(Vec::new()),
+            flush: false,
+            metrics,
+            max_len,
+        }
+    }
+
+    /// Reads the next non-empty name and renders its event, returning [None]
+    /// once there are no names left
+    fn next_event(&mut self) -> io::Result<Option<Vec<u8>>> {
+        // a character is at most 4 bytes, so past this a name is always too long
+        let limit = self.max_len as u64 * 4 + 1;
+
+        loop {
+            let mut line = Vec::new();
+            let read = (&mut self.names).take(limit).read_until(b'\n', &mut line)?;
+
+            if read == 0 {
+                return Ok(None);
+            }
+
+            let response = if !line.ends_with(b"\n") && line.len() as u64 == limit {
+                // skip the rest of the overly long name, counting its characters
+                let mut len = char_count(&line);
+                while !line.ends_with(b"\n") {
+                    line.clear();
+                    if (&mut self.names).take(limit).read_until(b'\n', &mut line)? == 0 {
+                        break;
+                    }
+                    len += char_count(&line);
+                }
+
+                ResponseModel::basic(
+                    400,
+                    NameTooLong {
+                        len,
+                        max: self.max_len,
+                    },
+                )
+            } else {
+                let name = String::from_utf8_lossy(trim_newline(&line));
+
+                if name.trim().is_empty() {
+                    continue;
+                }
+
+                let options = CaptureOptions {
+                    max_len: Some(self.max_len),
+                    ..Default::default()
+                };
+                let cap = Capture::new(name.into_owned(), &Context::default(), &options);
+                self.metrics.record_parse(&cap);
+
+                match cap {
+                    Ok(cap) => ResponseModel::new(200, "Success", cap),
+                    Err(err) => ResponseModel::basic(400, err),
+                }
+            };
+
+            let json = serde_json::to_string(&response).expect("Could not serialize event");
+            return Ok(Some(format!("data: {}\n\n", json).into_bytes()));
+        }
+    }
+}
+
+/// Strips a trailing `\n` or `\r\n` from a line of `bytes`
+fn trim_newline(bytes: &[u8]) -> &[u8] {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes.strip_suffix(b"\r").unwrap_or(bytes)
+}
+
+/// Number of characters in a chunk of utf-8 `bytes` without any trailing
+/// newline, counting each byte which doesn't continue a character so chunks
+/// split mid-character can be added up
+fn char_count(bytes: &[u8]) -> usize {
+    trim_newline(bytes)
+        .iter()
+        .filter(|byte| *byte & 0xC0 != 0x80)
+        .count()
+}
+
+impl<R: BufRead> Read for CaptureStream<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let read = self.event.read(buf)?;
+
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            } else if self.flush {
+                self.flush = false;
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+
+            match self.next_event()? {
+                Some(event) => {
+                    self.event = Cursor::new(event);
+                    self.flush = true;
+                }
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Rocket routes for tagging tv, alongside their help and query args
+#[cfg(feature = "server")]
+pub mod routes {
+    use super::*;
+    use crate::cache::{BatchCache, CaptureCache};
+    use crate::config::AppConfig;
+    use crate::idempotency::{IdempotencyCache, IdempotencyKey, Idempotent};
+    use crate::utils::{compile_user_regex, to_csv, to_ndjson, JsonBody, Page};
+    use rocket::data::{Data, DataStream};
+    use rocket::http::{Accept, ContentType};
+    use rocket::request::LenientForm;
+    use rocket::response::content::Content;
+    use rocket::response::Stream;
+    use rocket::State;
+    use rocket_contrib::json::Json;
+    use std::io::BufReader;
+
+    /// Gives help by providing available endpoints (to [episode] and [season])
+    #[get("/tv")]
+    pub fn help() -> &'static str {
+        "ROUTE /tv\n\n\nAbout\n    Allows tagging of tv shows with conventional season + episode tagging,\n    allowing manual explicit (optional) season or episode numbers to be passed\n    for clarification\n\n\nChild routes/endpoints\n    - /episode: Single episode tagging\n    - /season: Bulk per-season tagging\n    - /batch: Bulk tagging of unrelated episodes\n    - /group: Bulk tagging of unrelated episodes grouped by season\n    - /dedup: Keeps only the best quality file of each episode\n    - /summary: Shows, seasons and missing episodes of a library\n    - /completeness: Present and missing episodes of a season against a total\n    - /match: Snaps a parsed title to the closest of some known titles\n    - /inspect: Diagnostics of every season and episode match\n    - /build: Builds a canonical episode name from a title, season and episode\n    - /reparse: Corrects a hand-edited capture without capturing it again\n    - /se: Compact season and episode numbers of a single episode\n    - /rename/plan: Dry run of renaming names to their canonical names\n    - /plex: Renames names into the naming convention of Plex\n    - /lint: Warnings and suggested fixes for badly named files\n    - /stream: Live stream of captures for names sent over one connection"
+    }
+
+    /// Records the result of a [cap_batch] into [Metrics], which fails on the first
+    /// file it couldn't capture
+    fn record_batch_parses(metrics: &Metrics, caps: &Result<Vec<Capture>, CaptureError>) {
+        match caps {
+            Ok(caps) => metrics.record_parses(caps.len(), None),
+            Err(err) => metrics.record_parses(0, Some(err)),
+        }
+    }
+
+    /// Gives help for how to use the [episode] path
+    #[get("/tv/episode")]
+    pub fn episode_help() -> &'static str {
+        "ENDPOINT POST /tv/episode?<name>&<episode>&<season>&<bare_episode>&<detect_year>&<episode_regex>&<season_regex>&<word_numbers>&<absolute>&<min_season>&<max_season>&<min_episode>&<max_episode>&<lenient>&<basename>&<default_ext>&<dash_se>&<merged>&<compact>&<fields>&<all>&<echo>&<format>\n\n\nAbout\n    Tags a single episode of a tv show by it's required `name` with optional\n    passed context by including either `episode` or `season` query args.\n\n\nOptions\n    - bare_episode: Uses the last standalone number as the episode if no episode\n      marker is found, defaulting the season to 1\n    - detect_year: Detects a release year, excluding it from `bare_episode`\n    - episode_regex: Custom regex used to find the episode number, size limited\n    - season_regex: Custom regex used to find the season number, size limited\n    - word_numbers: Parses spelt out numbers like `Season Two` if no digits\n    - absolute: Uses the number after a spaced dash like `Show S2 - 13` as the\n      episode if no episode marker is found, defaulting the season to 1\n    - min_season, max_season, min_episode, max_episode: Bounds on captured\n      numbers, giving an error if out of range such as a year as the season\n    - lenient: Gives season and episode `0` with `parsed: false` instead of an\n      error if neither are found, keeping the whole filename as the name\n    - basename: Only tags the text after the last `/` or `\\` of names such as\n      `Show/Season 1/ep 2.mkv`, using the nearest directory for the season and\n      title if missing. There's no dedicated path endpoint, so this is the\n      way to tag full paths\n    - default_ext: Extension such as `.mkv` used for names without one, with\n      extensions found in the name always taking precedence\n    - dash_se: Uses two dash separated numbers like `Show - 1 - 2` as the season\n      then episode if no markers are found, which is ambiguous so is opt-in\n    - merged: Uses a merged number like `0205` as season 2 episode 5 if no\n      markers are found, including when run together like `Show105`, so\n      purely numeric names give an error by default\n    - compact: Omits `null` fields such as a missing `ext` from the response\n    - fields: Comma separated fields such as `title,season,episode` to respond\n      with in that order, giving a 400 listing the valid fields if unknown\n    - all: Responds with every plausible `season`, `episode` and `strategy`\n      such as `Show 102` being season 1 episode 2 or episode 102, ranked by a\n      heuristic `confidence` instead of committing to the first found\n    - echo: Gives the query args sent back in an `input` object for debugging\n    - format: Either `json` (default) or `nfo`, which responds with a Kodi or\n      Jellyfin style `<episodedetails>` xml sidecar of the season, episode,\n      title and year instead, as does an `Accept: application/xml` header.\n      Errors and `all` responses are still json\n\n\nCaching\n    Successful responses include an `ETag` header, with requests sending a\n    matching `If-None-Match` header given an empty 304 response instead."
+    }
+
+    /// Query args accepted by the [episode] path
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, Default, Serialize, FromForm)]
+    pub struct EpisodeQuery {
+        /// Name of the file to capture, equates to [Capture::file_path]
+        pub name: String,
+
+        /// Episode number context, see [Context::episode]
+        pub episode: Option<usize>,
+
+        /// Season number context, see [Context::season]
+        pub season: Option<usize>,
+
+        /// See [CaptureOptions::bare_episode]
+        pub bare_episode: Option<bool>,
+
+        /// See [CaptureOptions::detect_year]
+        pub detect_year: Option<bool>,
+
+        /// Uncompiled [CaptureOptions::episode_regex]
+        pub episode_regex: Option<String>,
+
+        /// Uncompiled [CaptureOptions::season_regex]
+        pub season_regex: Option<String>,
+
+        /// See [CaptureOptions::word_numbers]
+        pub word_numbers: Option<bool>,
+
+        /// See [CaptureOptions::absolute]
+        pub absolute: Option<bool>,
+
+        /// See [CaptureOptions::min_season]
+        pub min_season: Option<usize>,
+
+        /// See [CaptureOptions::max_season]
+        pub max_season: Option<usize>,
+
+        /// See [CaptureOptions::min_episode]
+        pub min_episode: Option<usize>,
+
+        /// See [CaptureOptions::max_episode]
+        pub max_episode: Option<usize>,
+
+        /// See [CaptureOptions::lenient]
+        pub lenient: Option<bool>,
+
+        /// See [CaptureOptions::basename]
+        pub basename: Option<bool>,
+
+        /// See [CaptureOptions::default_ext]
+        pub default_ext: Option<String>,
+
+        /// See [CaptureOptions::dash_se]
+        pub dash_se: Option<bool>,
+
+        /// See [CaptureOptions::merged]
+        pub merged: Option<bool>,
+
+        /// Omits `null` fields from the response, see [ResponseModel::compact]
+        pub compact: Option<bool>,
+
+        /// Comma separated [CAPTURE_FIELDS] to respond with, see [parse_capture_fields]
+        pub fields: Option<String>,
+
+        /// Responds with every plausible [Interpretation] instead of a single
+        /// capture, see [interpret]
+        pub all: Option<bool>,
+
+        /// Echoes these query args back in the response, see [ResponseModel::echo]
+        pub echo: Option<bool>,
+
+        /// Either `json` or `nfo`, overriding the `Accept` header of the request
+        pub format: Option<String>,
+    }
+
+    impl EpisodeQuery {
+        /// Splits these query args into the [Context] and [CaptureOptions] to use,
+        /// compiling any custom regexes given
+        pub fn context_options(&self) -> Result<(Context, CaptureOptions), regex::Error> {
+            let compile = |pattern: &Option<String>| {
+                pattern
+                    .as_ref()
+                    .map(|pattern| compile_user_regex(pattern))
+                    .transpose()
+            };
+
+            Ok((
+                Context {
+                    episode: self.episode,
+                    season: self.season,
+                },
+                CaptureOptions {
+                    bare_episode: self.bare_episode.unwrap_or(false),
+                    detect_year: self.detect_year.unwrap_or(false),
+                    episode_regex: compile(&self.episode_regex)?,
+                    season_regex: compile(&self.season_regex)?,
+                    word_numbers: self.word_numbers.unwrap_or(false),
+                    absolute: self.absolute.unwrap_or(false),
+                    min_season: self.min_season,
+                    max_season: self.max_season,
+                    min_episode: self.min_episode,
+                    max_episode: self.max_episode,
+                    lenient: self.lenient.unwrap_or(false),
+                    basename: self.basename.unwrap_or(false),
+                    default_ext: self.default_ext.clone(),
+                    dash_se: self.dash_se.unwrap_or(false),
+                    merged: self.merged.unwrap_or(false),
+                    max_len: None,
+                },
+            ))
+        }
+    }
+
+    /// Body of an [episode] response, being either the single capture or every
+    /// [Interpretation] if [EpisodeQuery::all] was passed
+    #[derive(Debug, PartialEq, Clone, Serialize)]
+    #[serde(untagged)]
+    pub enum EpisodeBody {
+        /// Single capture with its chosen fields, see [Capture::new]
+        Capture(Selected<Capture>),
+
+        /// Every plausible interpretation ranked by confidence, see [interpret]
+        All(Vec<Interpretation>),
+    }
+
+    /// Response of the [episode] path, which is json unless an `.nfo` sidecar was
+    /// asked for using the `format` query arg or `Accept` header
+    #[derive(Responder)]
+    pub enum EpisodeResponse {
+        /// Usual json response, which is always used for errors
+        Json(ResponseModel<EpisodeBody>),
+
+        /// Xml sidecar of a successful capture, see [Capture::to_nfo]
+        Nfo(Content<String>),
+    }
+
+    impl From<ResponseModel<EpisodeBody>> for EpisodeResponse {
+        fn from(response: ResponseModel<EpisodeBody>) -> Self {
+            EpisodeResponse::Json(response)
+        }
+    }
+
+    /// Single episode file capture with [Context] providers and [CaptureOptions]
+    #[post("/tv/episode?<query..>")]
+    pub fn episode(
+        query: LenientForm<EpisodeQuery>,
+        accept: Option<&Accept>,
+        metrics: State<Metrics>,
+        cache: State<CaptureCache>,
+        config: State<AppConfig>,
+    ) -> EpisodeResponse {
+        let input = query.into_inner();
+        let echo = input.echo.unwrap_or(false);
+        let mut query = input.clone();
+        query.default_ext = query.default_ext.or_else(|| config.default_ext.clone());
+        let fields = match parse_capture_fields(query.fields.as_deref()) {
+            Ok(fields) => fields,
+            Err(err) => return ResponseModel::basic(400, err).into(),
+        };
+        let nfo = match query.format.as_deref() {
+            Some("nfo") => true,
+            Some("json") => false,
+            Some(format) => {
+                return ResponseModel::basic(
+                    400,
+                    format!("Unknown format `{}`, valid formats are json, nfo", format),
+                )
+                .into()
+            }
+            None => accept.map_or(false, |accept| accept.preferred().media_type().is_xml()),
+        };
+
+        if let Err(err) = check_name_len(&query.name, Some(config.max_name_len)) {
+            return ResponseModel::basic(400, err).into();
+        }
+
+        if query.all.unwrap_or(false) {
+            return match query.context_options() {
+                Ok((_, options)) => {
+                    let options = CaptureOptions {
+                        max_len: Some(config.max_name_len),
+                        ..options
+                    };
+
+                    match interpret(query.name.clone(), &options) {
+                        Ok(all) => ResponseModel::new(200, "Success", EpisodeBody::All(all)),
+                        Err(err) => ResponseModel::basic(400, err),
+                    }
+                }
+                Err(err) => ResponseModel::basic(400, format!("Invalid custom regex: {}", err)),
+            }
+            .compact(query.compact.unwrap_or(false))
+            .echo(echo, &input)
+            .etag(true)
+            .into();
+        }
+        let cached = cache.get(&query);
+        metrics.record_cache(cached.is_some());
+
+        let cap = match cached {
+            Some(cap) => cap,
+            None => {
+                let (context, mut options) = match query.context_options() {
+                    Ok(context_options) => context_options,
+                    Err(err) => {
+                        return ResponseModel::basic(400, format!("Invalid custom regex: {}", err))
+                            .into()
+                    }
+                };
+                options.max_len = Some(config.max_name_len);
+
+                let cap = Capture::new(query.name.clone(), &context, &options);
+                cache.put(&query, cap.clone());
+
+                cap
+            }
+        };
+        metrics.record_parse(&cap);
+
+        match cap {
+            Ok(cap) if nfo => EpisodeResponse::Nfo(Content(ContentType::XML, cap.to_nfo())),
+            Ok(cap) => ResponseModel::new(
+                200,
+                "Success",
+                EpisodeBody::Capture(Selected::new(cap, fields)),
+            )
+            .compact(query.compact.unwrap_or(false))
+            .echo(echo, &input)
+            .etag(true)
+            .into(),
+            Err(err) => ResponseModel::basic(400, err)
+                .compact(query.compact.unwrap_or(false))
+                .echo(echo, &input)
+                .into(),
+        }
+    }
+
+    /// Gives help for how to use the [season] path
+    #[get("/tv/season")]
+    pub fn season_help() -> &'static str {
+        "ENDPOINT POST /tv/season?<fields>&<skip_samples>&<duplicates>&<echo>\n\n\nAbout\n    Tags entire array of episodes into a single season according to the provided\n    `season` parameter. Episodes may also be objects with their own `name` and\n    optional `season` or `episode`, overriding the shared season for specials.\n    Season pack names such as `Show S01 Complete` are given as\n    `is_pack: true` entries of episode 0, giving their title to episodes\n    without one. An `Idempotency-Key` header may be sent to\n    replay the response of a retried request, as with `/tv/batch`. Optional\n    `fields` may be passed to choose the fields of each episode like\n    `/tv/episode`, and `skip_samples=true` leaves out sample or trailer files\n    such as `Show.S01E02.sample.mkv`. Passing `duplicates=true` responds with\n    an object of the `episodes` alongside `duplicates`, listing each `season`\n    and `episode` claimed by more than one file with the `names` claiming it,\n    which is a common sign of a bad download. With `echo=true` the season is\n    sent back as `input.body` next to the other query args for debugging.\n\n\nExample JSON\n    {\n        \"season\": {\n            \"episodes\": [\n                \"a ep1.mp4\",\n                \"other hello season 6 episode 2.mpv\",\n                {\"name\": \"special ep1.mp4\", \"season\": 0}\n            ],\n            \"number\": 5\n        }\n    }"
+    }
+
+    /// Multiple tv inputs corrosponding to seasons
+    #[post(
+        "/tv/season?<fields>&<skip_samples>&<duplicates>&<echo>",
+        format = "json",
+        data = "<season>"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    pub fn season(
+        season: JsonBody<Season>,
+        fields: Option<String>,
+        skip_samples: Option<bool>,
+        duplicates: Option<bool>,
+        echo: Option<bool>,
+        metrics: State<Metrics>,
+        key: IdempotencyKey,
+        cache: State<IdempotencyCache>,
+        config: State<AppConfig>,
+    ) -> Idempotent {
+        let input = serde_json::json!({
+            "fields": fields,
+            "skip_samples": skip_samples,
+            "duplicates": duplicates,
+            "body": season.0,
+        });
+        let skip_samples = skip_samples.unwrap_or(false);
+        let duplicates = duplicates.unwrap_or(false);
+        let echo = echo.unwrap_or(false);
+        let route = format!(
+            "/tv/season?fields={:?}&skip_samples={}&duplicates={}&echo={}",
+            fields, skip_samples, duplicates, echo
+        );
+        let request = season.0.clone();
+
+        cache.respond(&route, &key, &request, || {
+            let fields = match parse_capture_fields(fields.as_deref()) {
+                Ok(fields) => fields,
+                Err(err) => return ResponseModel::basic(400, err),
+            };
+            let mut season = season.into_inner();
+            metrics.record_batch(season.episodes.len());
+
+            if skip_samples {
+                season.episodes.retain(|episode| !is_sample(episode.name()));
+            }
+
+            let options = CaptureOptions {
+                max_len: Some(config.max_name_len),
+                ..Default::default()
+            };
+            let caps = season.capture(&options);
+            record_batch_parses(&metrics, &caps);
+
+            let response = match caps {
+                Ok(caps) if duplicates => ResponseModel::new(
+                    200,
+                    "Success",
+                    SeasonBody::WithDuplicates {
+                        duplicates: find_duplicates(&caps),
+                        episodes: select_fields(caps, &fields),
+                    },
+                ),
+                Ok(caps) => ResponseModel::new(
+                    200,
+                    "Success",
+                    SeasonBody::Episodes(select_fields(caps, &fields)),
+                ),
+                Err(err) => ResponseModel::basic(400, err),
+            };
+
+            response.echo(echo, &input)
+        })
+    }
+
+    /// Gives help for how to use the [batch] path
+    #[get("/tv/batch")]
+    pub fn batch_help() -> &'static str {
+        "ENDPOINT POST /tv/batch?<offset>&<limit>&<fields>&<format>&<skip_samples>&<echo>\n\n\nAbout\n    Tags an array of unrelated episode names independently of each other. All\n    names are tagged but optional `offset` and `limit` query args may be passed\n    to only return a single page of the results, with the `total` count, `offset`\n    and `limit` included in the response. An `Idempotency-Key` header may be sent\n    to replay the response of a retried request for up to 5 minutes, marked by\n    an `Idempotent-Replayed: true` header. Reusing a key for a different body\n    gives a 422 and retrying before the first request finishes gives a 409.\n    Optional `fields` may be passed to choose the fields of each episode like\n    `/tv/episode`, and `skip_samples=true` leaves out sample or trailer files\n    such as `Show.S01E02.sample.mkv`, which are otherwise tagged with\n    `is_sample: true`. Identical lists of names are cached whole for 5 minutes\n    by default, so re-submitting the same directory listing responds without\n    tagging it again. Passing `echo=true` also responds with an `input` object\n    of the query args and the names as `input.body`.\n\n\nCSV\n    Passing `format=csv` or sending an `Accept: text/csv` header responds with\n    a csv of the captures instead, with a header row of the chosen `fields` or\n    `file_path,title,season,episode,ext` by default. Errors are still json and\n    `echo` is ignored.\n\n\nNDJSON\n    Passing `format=ndjson` or sending an `Accept: application/x-ndjson` header\n    responds with one capture per line instead of a json array, for piping\n    into tools such as `jq`. Each line is a json object with the chosen\n    `fields`, without the `status` or paging info. Errors are still json and\n    `echo` is ignored.\n\n\nExample JSON\n    [\n        \"a s01e01.mp4\",\n        \"other show season 6 episode 2.mpv\"\n    ]"
+    }
+
+    /// Query args accepted by the [batch] path
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, FromForm)]
+    pub struct BatchQuery {
+        /// Number of captures to skip, see [Page::new]
+        pub offset: Option<usize>,
+
+        /// Maximum number of captures to respond with, see [Page::new]
+        pub limit: Option<usize>,
+
+        /// Comma separated [CAPTURE_FIELDS] to respond with, see [parse_capture_fields]
+        pub fields: Option<String>,
+
+        /// Either `json`, `csv` or `ndjson`, overriding the `Accept` header of the
+        /// request
+        pub format: Option<String>,
+
+        /// Leaves out names of samples and trailers before tagging, see [is_sample]
+        pub skip_samples: Option<bool>,
+
+        /// Echoes these query args and the json body back in `json` responses,
+        /// see [ResponseModel::echo]
+        pub echo: Option<bool>,
+    }
+
+    /// Multiple unrelated tv inputs, paginated by the optional `offset` and `limit`
+    #[post("/tv/batch?<query..>", format = "json", data = "<names>")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn batch(
+        names: JsonBody<Vec<String>>,
+        query: LenientForm<BatchQuery>,
+        accept: Option<&Accept>,
+        metrics: State<Metrics>,
+        key: IdempotencyKey,
+        cache: State<IdempotencyCache>,
+        batch_cache: State<BatchCache>,
+        config: State<AppConfig>,
+    ) -> Idempotent {
+        let mut query = query.into_inner();
+        let input = serde_json::json!({
+            "offset": query.offset,
+            "limit": query.limit,
+            "fields": query.fields,
+            "format": query.format,
+            "skip_samples": query.skip_samples,
+            "body": names.0,
+        });
+        query.limit = query.limit.or(config.batch_limit);
+        let format = match query.format.as_deref() {
+            Some(format @ "json") | Some(format @ "csv") | Some(format @ "ndjson") => format,
+            Some(format) => {
+                return ResponseModel::<()>::basic(
+                    400,
+                    format!(
+                        "Unknown format `{}`, valid formats are json, csv, ndjson",
+                        format
+                    ),
+                )
+                .into()
+            }
+            None => match accept.map(|accept| accept.preferred().media_type()) {
+                Some(media_type) if media_type.is_csv() => "csv",
+                Some(media_type) if media_type.sub() == "x-ndjson" => "ndjson",
+                _ => "json",
+            },
+        };
+        let skip_samples = query.skip_samples.unwrap_or(false);
+        let echo = query.echo.unwrap_or(false) && format == "json";
+        let route = format!(
+            "/tv/batch?offset={:?}&limit={:?}&fields={:?}&format={}&skip_samples={}&echo={}",
+            query.offset, query.limit, query.fields, format, skip_samples, echo
+        );
+        let request = names.0.clone();
+        let process = || {
+            let fields = parse_capture_fields(query.fields.as_deref())?;
+            let context = Context {
+                season: None,
+                episode: None,
+            };
+
+            let mut names = names.into_inner();
+            metrics.record_batch(names.len());
+
+            if skip_samples {
+                names.retain(|name| !is_sample(name));
+            }
+
+            let cached = batch_cache.get(&names);
+            metrics.record_batch_cache(cached.is_some());
+
+            let caps = match cached {
+                Some(caps) => caps,
+                None => {
+                    let options = CaptureOptions {
+                        max_len: Some(config.max_name_len),
+                        ..Default::default()
+                    };
+                    let caps = cap_batch(names.clone(), &context, &options);
+                    batch_cache.put(&names, caps.clone());
+
+                    caps
+                }
+            };
+            record_batch_parses(&metrics, &caps);
+
+            caps.map(|caps| (caps, fields))
+                .map_err(|err| err.to_string())
+        };
+
+        if format == "json" {
+            return cache.respond(&route, &key, &request, || {
+                let response = match process() {
+                    Ok((caps, fields)) => ResponseModel::new(
+                        200,
+                        "Success",
+                        Page::new(select_fields(caps, &fields), query.offset, query.limit),
+                    ),
+                    Err(err) => ResponseModel::basic(400, err),
+                };
+
+                response.echo(echo, &input)
+            });
+        }
+
+        cache.respond_raw(&route, &key, &request, || match process() {
+            Ok((caps, fields)) if format == "csv" => {
+                let columns = fields
+                    .unwrap_or_else(|| CSV_FIELDS.iter().map(|field| field.to_string()).collect());
+                let page = Page::new(caps, query.offset, query.limit);
+
+                Idempotent::raw(200, to_csv(page.items, &columns), ContentType::CSV)
+            }
+            Ok((caps, fields)) => {
+                let page = Page::new(select_fields(caps, &fields), query.offset, query.limit);
+
+                Idempotent::raw(
+                    200,
+                    to_ndjson(page.items),
+                    ContentType::new("application", "x-ndjson"),
+                )
+            }
+            Err(err) => ResponseModel::<()>::basic(400, err).into(),
+        })
+    }
+
+    /// Gives help for how to use the [group] path
+    #[get("/tv/group")]
+    pub fn group_help() -> &'static str {
+        "ENDPOINT POST /tv/group?<echo>\n\n\nAbout\n    Tags an array of unrelated episode names independently of each other like\n    `/tv/batch`, grouping the captures into an object keyed by season number\n    with episodes sorted within each season. Names which couldn't be tagged are\n    given in an `unknown` array instead of failing the request, giving a ready\n    to display season tree, with `echo=true` adding the names sent as\n    `input.body`.\n\n\nExample JSON\n    [\n        \"show s02e01.mkv\",\n        \"show s01e02.mkv\",\n        \"show s01e01.mkv\",\n        \"extras.mkv\"\n    ]"
+    }
+
+    /// Multiple unrelated tv inputs grouped by season, see [cap_grouped]
+    #[post("/tv/group?<echo>", format = "json", data = "<names>")]
+    pub fn group(
+        names: JsonBody<Vec<String>>,
+        echo: Option<bool>,
+        metrics: State<Metrics>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<SeasonGroups> {
+        let input = serde_json::json!({ "body": names.0 });
+        let names = names.into_inner();
+        metrics.record_batch(names.len());
+
+        let options = CaptureOptions {
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+
+        ResponseModel::new(
+            200,
+            "Success",
+            cap_grouped(names, &Context::default(), &options, &metrics),
+        )
+        .echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [summary] path
+    #[get("/tv/summary")]
+    pub fn summary_help() -> &'static str {
+        "ENDPOINT POST /tv/summary?<echo>\n\n\nAbout\n    Summarizes the structure of a library from an array of episode names, giving\n    the distinct shows (by title, ignoring case), their seasons and the number of\n    episodes in each. Each season lists any `missing` episodes between 1 and the\n    highest episode found as inclusive `[start, end]` ranges, useful for\n    spotting incomplete downloads. Names which couldn't be tagged are given in\n    `unknown`, and `echo=true` gives back the names sent as `input.body`.\n\n\nExample JSON\n    [\n        \"Show S01E01.mkv\",\n        \"Show S01E02.mkv\",\n        \"Show S01E04.mkv\",\n        \"Other S02E01.mkv\"\n    ]"
+    }
+
+    /// Summary of a library of tv names, see [summarize]
+    #[post("/tv/summary?<echo>", format = "json", data = "<names>")]
+    pub fn summary(
+        names: JsonBody<Vec<String>>,
+        echo: Option<bool>,
+        metrics: State<Metrics>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<LibrarySummary> {
+        let input = serde_json::json!({ "body": names.0 });
+        let names = names.into_inner();
+        metrics.record_batch(names.len());
+
+        let options = CaptureOptions {
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+        let groups = cap_grouped(names, &Context::default(), &options, &metrics);
+
+        ResponseModel::new(200, "Success", summarize(groups)).echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [match_titles] path
+    #[get("/tv/match")]
+    pub fn match_help() -> &'static str {
+        "ENDPOINT POST /tv/match?<echo>\n\n\nAbout\n    Snaps the title parsed from a `name` to the closest of the known `candidates`,\n    such as correcting `Breakin Bad` to `Breaking Bad`. Gives the raw parsed\n    `title` alongside the `matched` candidate and its `score` from 0 to 1, based\n    on the edit distance ignoring case. If no candidate scores at least 0.8 then\n    both `matched` and `score` are null, as they are for a blank title or\n    candidate. Over 1000 `candidates`, or a `name` or candidate longer than\n    the maximum name length, gives a 400. Passing `echo=true` gives the sent\n    `name` and `candidates` back under `input.body`.\n\n\nExample JSON\n    {\n        \"name\": \"Breakin.Bad.S01E02.720p.mkv\",\n        \"candidates\": [\"Breaking Bad\", \"Better Call Saul\"]\n    }"
+    }
+
+    /// Title of a name matched to known titles, see [match_title]
+    #[post("/tv/match?<echo>", format = "json", data = "<request>")]
+    pub fn match_titles(
+        request: JsonBody<MatchRequest>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<TitleMatch> {
+        let input = serde_json::json!({ "body": request.0 });
+        let request = request.into_inner();
+
+        if let Err(err) = check_name_len(&request.name, Some(config.max_name_len)) {
+            return ResponseModel::basic(400, err);
+        }
+
+        if request.candidates.len() > MAX_MATCH_CANDIDATES {
+            return ResponseModel::basic(
+                400,
+                format!(
+                    "Got {} candidates, over the maximum of {}",
+                    request.candidates.len(),
+                    MAX_MATCH_CANDIDATES
+                ),
+            );
+        }
+
+        for (index, candidate) in request.candidates.iter().enumerate() {
+            if let Err(err) = check_name_len(candidate, Some(config.max_name_len)) {
+                return ResponseModel::basic(
+                    400,
+                    format!(
+                        "Candidate {} is {} characters long, over the maximum of {}",
+                        index, err.len, err.max
+                    ),
+                );
+            }
+        }
+
+        ResponseModel::new(
+            200,
+            "Success",
+            match_title(&request.name, &request.candidates),
+        )
+        .echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [completeness_check] path
+    #[get("/tv/completeness")]
+    pub fn completeness_help() -> &'static str {
+        "ENDPOINT POST /tv/completeness?<total>&<echo>\n\n\nAbout\n    Checks if a season is complete from an array of its episode names and the\n    known `total` number of episodes, such as from an external database. Gives\n    the episodes from 1 up to the `total` which are `present` and `missing`,\n    alongside the names of any `extra` files containing an episode which was\n    already found or is out of range, a common sign of a bad download. Names\n    which couldn't be tagged are given in `unknown`, and season numbers are\n    ignored so names like `Show - 05.mkv` are also accepted. A `total` over\n    10000 gives a 400. Passing `echo=true` gives the `total` and the names\n    sent back in an `input` object, with the names as `input.body`.\n\n\nExample JSON\n    [\n        \"Show S01E01.mkv\",\n        \"Show S01E03.mkv\",\n        \"Show S01E03 PROPER.mkv\",\n        \"Show S01E14.mkv\"\n    ]"
+    }
+
+    /// Present and missing episodes of a season against a known total, see
+    /// [completeness]
+    #[post("/tv/completeness?<total>&<echo>", format = "json", data = "<names>")]
+    pub fn completeness_check(
+        names: JsonBody<Vec<String>>,
+        total: usize,
+        echo: Option<bool>,
+        metrics: State<Metrics>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Completeness> {
+        let input = serde_json::json!({ "total": total, "body": names.0 });
+        if total > MAX_COMPLETENESS_TOTAL {
+            return ResponseModel::basic(
+                400,
+                format!(
+                    "Total of {} episodes is over the maximum of {}",
+                    total, MAX_COMPLETENESS_TOTAL
+                ),
+            );
+        }
+
+        let names = names.into_inner();
+        metrics.record_batch(names.len());
+
+        let options = CaptureOptions {
+            absolute: true,
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+        let mut caps = Vec::with_capacity(names.len());
+        let mut unknown = Vec::new();
+
+        for name in names {
+            let cap = Capture::new(name.clone(), &Context::default(), &options);
+            metrics.record_parse(&cap);
+
+            match cap {
+                Ok(cap) => caps.push(cap),
+                Err(_) => unknown.push(name),
+            }
+        }
+
+        ResponseModel::new(
+            200,
+            "Success",
+            Completeness {
+                unknown,
+                ..completeness(caps, total)
+            },
+        )
+        .echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [dedup] path
+    #[get("/tv/dedup")]
+    pub fn dedup_help() -> &'static str {
+        "ENDPOINT POST /tv/dedup?<echo>\n\n\nAbout\n    Tags an array of episode names and keeps only the best quality file of each\n    distinct show, season and episode for library cleanup, giving the rest in\n    `removed` and any names which couldn't be tagged in `unknown`. Quality is\n    ranked by resolution (`2160p`/`4K` > `1080p` > `720p` > `576p` > `480p`)\n    then source (`BluRay` > `WEB-DL` > `WEBRip` > `WEB` > `BDRip` > `BRRip` >\n    `HDTV` > `DVDRip`), with the first given file kept for equal quality. The\n    names sent are given back as `input.body` when passing `echo=true`.\n\n\nExample JSON\n    [\n        \"Show.S01E01.720p.HDTV.mkv\",\n        \"Show.S01E01.1080p.WEB-DL.mkv\",\n        \"Show.S01E02.480p.mkv\"\n    ]"
+    }
+
+    /// Keeps the best quality file of each episode, see [dedup_by_quality]
+    #[post("/tv/dedup?<echo>", format = "json", data = "<names>")]
+    pub fn dedup(
+        names: JsonBody<Vec<String>>,
+        echo: Option<bool>,
+        metrics: State<Metrics>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Dedup> {
+        let input = serde_json::json!({ "body": names.0 });
+        let names = names.into_inner();
+        metrics.record_batch(names.len());
+
+        let options = CaptureOptions {
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+        let mut caps = Vec::with_capacity(names.len());
+        let mut unknown = Vec::new();
+
+        for name in names {
+            let cap = Capture::new(name.clone(), &Context::default(), &options);
+            metrics.record_parse(&cap);
+
+            match cap {
+                Ok(cap) => caps.push(cap),
+                Err(_) => unknown.push(name),
+            }
+        }
+
+        ResponseModel::new(
+            200,
+            "Success",
+            Dedup {
+                unknown,
+                ..dedup_by_quality(caps)
+            },
+        )
+        .echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [stream] path
+    #[get("/tv/stream")]
+    pub fn stream_help() -> &'static str {
+        "ENDPOINT POST /tv/stream\n\n\nAbout\n    Streams captures back over a single connection for live folder watching.\n    The request body is a stream of newline-delimited names which are tagged as\n    they arrive, with each giving a `text/event-stream` event in the form of\n    `data: {json}` followed by a blank line. The json is the same as a response\n    from `/tv/episode`, so failed names give an event with a 400 `status`.\n    Blank lines are ignored. The first 512 bytes of the body are buffered before\n    streaming begins, so the first events are given once that much has been sent\n    or the body ends.\n\n\nExample events\n    data: {\"status\":200,\"msg\":\"Success\",\"body\":{...}}\n\n    data: {\"status\":400,\"msg\":\"No episode number...\",\"body\":null}"
+    }
+
+    /// Live stream of captures for newline-delimited names, see [CaptureStream]
+    ///
+    /// This is a `POST` rather than a `GET` as rocket warns on request bodies of
+    /// `GET` routes, which can't be allowed and so fail the build, and `GET
+    /// /tv/stream` already gives [stream_help]. Rocket also always buffers the
+    /// first 512 bytes of a body before routing, so events can't be given any
+    /// sooner than that
+    #[post("/tv/stream", data = "<names>")]
+    pub fn stream<'r>(
+        names: Data,
+        metrics: State<'r, Metrics>,
+        config: State<AppConfig>,
+    ) -> Content<Stream<CaptureStream<'r, BufReader<DataStream>>>> {
+        Content(
+            ContentType::new("text", "event-stream"),
+            Stream::chunked(
+                CaptureStream::new(
+                    BufReader::new(names.open()),
+                    metrics.inner(),
+                    config.max_name_len,
+                ),
+                4096,
+            ),
+        )
+    }
+
+    /// Gives help for how to use the [plex] path
+    #[get("/tv/plex")]
+    pub fn plex_help() -> &'static str {
+        "ENDPOINT POST /tv/plex?<echo>\n\n\nAbout\n    Renames an array of names into the naming convention of Plex, such as\n    `Show Name (2019) - s01e02 - Episode Title.mkv`, giving `from` and `to`\n    pairs like `/tv/rename/plan`. The year is only included if one is detected,\n    multiple episodes are given as a range like `s01e02-e03` and the episode\n    title is only included if found. Passing `echo=true` also gives back the\n    names sent as `input.body`.\n\n\nExample JSON\n    [\n        \"Show.Name.2019.S01E02.Episode.Title.1080p.mkv\",\n        \"show name s01e03.mkv\"\n    ]"
+    }
+
+    /// Plex-compatible names for multiple names, see [plan_plex_renames]
+    #[post("/tv/plex?<echo>", format = "json", data = "<names>")]
+    pub fn plex(
+        names: JsonBody<Vec<String>>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Vec<Rename>> {
+        let input = serde_json::json!({ "body": names.0 });
+        let plan = plan_plex_renames(names.into_inner(), Some(config.max_name_len));
+
+        ResponseModel::new(200, "Success", plan).echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [lint] path
+    #[get("/tv/lint")]
+    pub fn lint_help() -> &'static str {
+        "ENDPOINT POST /tv/lint?<rules>&<echo>\n\n\nAbout\n    Lints an array of names for library hygiene, giving each `name` alongside\n    any `lints` found and the canonical name as a `suggestion`. Each lint has\n    the `rule` which found it and a `message` explaining the problem.\n\n\nRules\n    - unparsed: The name couldn't be tagged, giving a `null` suggestion\n    - missing_season: No season marker was found, so season 1 was assumed\n    - inconsistent_padding: The season and episode aren't written like `S01E02`\n    - stray_tags: Quality tags such as `1080p` are part of the title\n    - non_ascii: The name has characters some filesystems and players mangle\n\n    All rules are checked by default, with `rules` choosing which to check as a\n    comma separated list like `missing_season,non_ascii`. Passing `echo=true`\n    gives the `rules` and names sent back in an `input` object, with the names\n    as `input.body`.\n\n\nExample JSON\n    [\n        \"Show S1E2.mkv\",\n        \"Show 1080p - 05.mkv\",\n        \"Café S01E02.mkv\"\n    ]"
+    }
+
+    /// Lints multiple names, see [lint_name]
+    #[post("/tv/lint?<rules>&<echo>", format = "json", data = "<names>")]
+    pub fn lint(
+        names: JsonBody<Vec<String>>,
+        rules: Option<String>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Vec<LintReport>> {
+        let input = serde_json::json!({ "rules": rules, "body": names.0 });
+        let rules = match rules
+            .map(|rules| parse_fields(&rules, LINT_RULES))
+            .transpose()
+        {
+            Ok(rules) => rules,
+            Err(err) => return ResponseModel::basic(400, err),
+        };
+        let rules: Vec<&str> = match &rules {
+            Some(rules) => rules.iter().map(String::as_str).collect(),
+            None => LINT_RULES.to_vec(),
+        };
+        let reports = names
+            .into_inner()
+            .into_iter()
+            .map(|name| lint_name(name, &rules, Some(config.max_name_len)))
+            .collect();
+
+        ResponseModel::new(200, "Success", reports).echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [rename_plan] path
+    #[get("/tv/rename/plan")]
+    pub fn rename_plan_help() -> &'static str {
+        "ENDPOINT POST /tv/rename/plan?<template>&<preserve_padding>&<echo>\n\n\nAbout\n    Dry run of renaming an array of names to their canonical names such as\n    `Title - S01E02.mkv`, giving `from` and `to` pairs without renaming anything.\n    Names which would rename to the same `to` as another (ignoring case) are\n    marked with `conflict: true` so files aren't clobbered, and names which\n    couldn't be tagged have a `null` `to` alongside an `error`.\n\n    A `template` such as `{title} {season}x{episode:02}{ext}` may be given to\n    match other naming conventions, defaulting to\n    `{title} - S{season:02}E{episode:02}{ext}`. Available placeholders are\n    `{title}`, `{episode_title}`, `{name}`, `{season}`, `{episode}`, `{se}`,\n    `{ext}`, `{year}`, `{group}` and `{part}`, with numbers being paddable like\n    `{episode:02}`. Unknown placeholders give a 400.\n\n    Passing `preserve_padding=true` keeps the season and episode padded as they\n    were written, so `Show S1E2.mkv` renames to `Show - S1E2.mkv` instead of\n    `Show - S01E02.mkv`, ignoring any padding in the `template`.\n\n    Passing `echo=true` responds with the `template`, `preserve_padding` and\n    names sent in an `input` object, with the names as `input.body`.\n\n\nExample JSON\n    [\n        \"show.s01e01.720p.mkv\",\n        \"Show S01E01 1080p.mkv\"\n    ]"
+    }
+
+    /// Dry run rename plan for multiple names, see [plan_renames]
+    #[post(
+        "/tv/rename/plan?<template>&<preserve_padding>&<echo>",
+        format = "json",
+        data = "<names>"
+    )]
+    pub fn rename_plan(
+        names: JsonBody<Vec<String>>,
+        template: Option<String>,
+        preserve_padding: Option<bool>,
+        echo: Option<bool>,
+        config: State<AppConfig>,
+    ) -> ResponseModel<Vec<Rename>> {
+        let input = serde_json::json!({
+            "template": template,
+            "preserve_padding": preserve_padding,
+            "body": names.0,
+        });
+        let response = match plan_renames(
+            names.into_inner(),
+            template.as_deref(),
+            preserve_padding.unwrap_or(false),
+            Some(config.max_name_len),
+        ) {
+            Ok(plan) => ResponseModel::new(200, "Success", plan),
+            Err(err) => ResponseModel::basic(400, err),
+        };
+
+        response.echo(echo.unwrap_or(false), &input)
+    }
+
+    /// Gives help for how to use the [inspect] path
+    #[get("/tv/inspect")]
+    pub fn inspect_help() -> &'static str {
+        "ENDPOINT GET /tv/inspect?<name>\n\n\nAbout\n    Shows every season and episode match found in the given `name` along with\n    their spans and parsed values, without committing to any one of them. Useful\n    for debugging why a file was tagged a certain way."
+    }
+
+    /// Diagnostics for all regex matches on a single file, see [Inspection]
+    #[get("/tv/inspect?<name>")]
+    pub fn inspect(name: String, config: State<AppConfig>) -> ResponseModel<Inspection> {
+        if let Err(err) = check_name_len(&name, Some(config.max_name_len)) {
+            return ResponseModel::basic(400, err);
+        }
+
+        ResponseModel::new(200, "Success", Inspection::new(name))
+    }
+
+    /// Gives help for how to use the [se] path
+    #[get("/tv/se")]
+    pub fn se_help() -> &'static str {
+        "ENDPOINT GET /tv/se?<name>\n\n\nAbout\n    Lightweight version of `/tv/episode` for minimal bandwidth, returning just\n    `[season, episode]` as a JSON array for the given `name`, or `null` if the\n    numbers couldn't be captured."
+    }
+
+    /// Compact capture of a single file, returning only the season and episode
+    #[get("/tv/se?<name>")]
+    pub fn se(
+        name: String,
+        metrics: State<Metrics>,
+        config: State<AppConfig>,
+    ) -> Json<Option<(usize, usize)>> {
+        let options = CaptureOptions {
+            max_len: Some(config.max_name_len),
+            ..Default::default()
+        };
+        let cap = Capture::new(name, &Context::default(), &options);
+        metrics.record_parse(&cap);
+
+        Json(cap.ok().map(|cap| (cap.season, cap.episode)))
+    }
+
+    /// Gives help for how to use the [build] path
+    #[get("/tv/build")]
+    pub fn build_help() -> &'static str {
+        "ENDPOINT GET /tv/build?<title>&<season>&<episode>&<ext>\n\n\nAbout\n    Builds a canonical episode name like `Title - S01E02.mkv` from a required\n    `title`, `season` and `episode` alongside an optional `ext`, without needing\n    a filename to capture from. This is the inverse of `/tv/episode`."
+    }
+
+    /// Builds a canonical episode name from its parts, see [render_episode]
+    #[get("/tv/build?<title>&<season>&<episode>&<ext>")]
+    pub fn build(
+        title: String,
+        season: usize,
+        episode: usize,
+        ext: Option<String>,
+    ) -> ResponseModel<String> {
+        let title = title.trim();
+
+        if title.is_empty() {
+            ResponseModel::basic(400, "Title must not be empty")
+        } else {
+            let render = render_episode(title, season, episode, ext.as_deref());
+            ResponseModel::new(200, "Success", render)
+        }
+    }
+
+    /// Gives help for how to use the [reparse] path
+    #[get("/tv/reparse")]
+    pub fn reparse_help() -> &'static str {
+        "ENDPOINT POST /tv/reparse?<echo>\n\n\nAbout\n    Corrects a capture previously given by `/tv/episode`, which may have been\n    hand-edited, without capturing from the filename again. The `season`,\n    `episode`, `title` and `ext` are authoritative, with the `se_string`,\n    `episodes` and `container` re-derived from them, the titles cleaned up and\n    any `season_raw` or `episode_raw` no longer matching dropped. The corrected\n    capture is given with its canonical `render` such as `Title - S01E02.mkv`.\n    Captures with chosen `fields` can't be reparsed as every field is needed,\n    and `echo=true` gives the capture sent back as `input.body`.\n\n\nExample JSON\n    {\n        \"file_path\": \"Show S01E02.mkv\",\n        \"season\": 3,\n        ...\n    }"
+    }
+
+    /// Previously given capture with its computed fields re-derived, see
+    /// [Capture::reparse]
+    #[post("/tv/reparse?<echo>", format = "json", data = "<capture>")]
+    pub fn reparse(capture: JsonBody<Capture>, echo: Option<bool>) -> ResponseModel<Reparsed> {
+        let input = serde_json::json!({ "body": capture.0 });
+        let capture = capture.into_inner().reparse();
+        let render = capture.render();
+
+        ResponseModel::new(200, "Success", Reparsed { capture, render })
+            .echo(echo.unwrap_or(false), &input)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn batch_skip_samples() {
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(BatchCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![batch]),
+            )
+            .expect("Could not make client");
+            let names = r#"["Show S01E02.mkv", "sample.mkv", "Show.S01E02.sample.mkv"]"#;
+
+            let response = client
+                .post("/tv/batch")
+                .header(ContentType::JSON)
+                .body(names)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+
+            let mut response = client
+                .post("/tv/batch?skip_samples=true&fields=file_path")
+                .header(ContentType::JSON)
+                .body(names)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains(r#""items":[{"file_path":"Show S01E02.mkv"}]"#));
+        }
+
+        #[test]
+        fn season_skip_samples() {
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![season]),
+            )
+            .expect("Could not make client");
+            let body = r#"{"episodes": ["Show E01.mkv", "sample.mkv", "Show.E02.sample.mkv"], "number": 1}"#;
+
+            let response = client
+                .post("/tv/season")
+                .header(ContentType::JSON)
+                .body(body)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+
+            let mut response = client
+                .post("/tv/season?skip_samples=true&fields=file_path")
+                .header(ContentType::JSON)
+                .body(body)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains(r#""body":[{"file_path":"Show E01.mkv"}]"#));
+        }
+
+        #[test]
+        fn json_body_echo() {
+            use rocket::http::ContentType;
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(BatchCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![season, batch, lint]),
+            )
+            .expect("Could not make client");
+            let post = |uri: &str, body: &str| -> serde_json::Value {
+                let mut response = client
+                    .post(uri.to_string())
+                    .header(ContentType::JSON)
+                    .body(body)
+                    .dispatch();
+
+                serde_json::from_str(&response.body_string().unwrap()).unwrap()
+            };
+
+            let season = r#"{"episodes": ["Show E01.mkv"], "number": 2}"#;
+            assert_eq!(post("/tv/season", season)["input"], serde_json::Value::Null);
+
+            let echoed = post("/tv/season?duplicates=true&echo=true", season);
+            assert_eq!(echoed["input"]["duplicates"], true);
+            assert_eq!(echoed["input"]["body"]["number"], 2);
+            assert_eq!(echoed["input"]["body"]["episodes"][0], "Show E01.mkv");
+
+            let echoed = post("/tv/batch?limit=1&echo=true", r#"["Show S01E02.mkv"]"#);
+            assert_eq!(echoed["input"]["limit"], 1);
+            assert_eq!(echoed["input"]["body"][0], "Show S01E02.mkv");
+
+            let echoed = post(
+                "/tv/lint?rules=non_ascii&echo=true",
+                r#"["Show S01E02.mkv"]"#,
+            );
+            assert_eq!(echoed["input"]["rules"], "non_ascii");
+            assert_eq!(echoed["input"]["body"][0], "Show S01E02.mkv");
+        }
+
+        #[test]
+        fn config_precedence() {
+            use rocket::http::ContentType;
+
+            let config = AppConfig {
+                default_ext: Some(".mkv".to_string()),
+                batch_limit: Some(1),
+                ..Default::default()
+            };
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(BatchCache::default())
+                    .manage(CaptureCache::default())
+                    .manage(config)
+                    .mount("/", routes![episode, batch]),
+            )
+            .expect("Could not make client");
+            let body = |url: &str| {
+                let mut response = client
+                    .post(url)
+                    .header(ContentType::JSON)
+                    .body(r#"["a S01E01", "b S01E02"]"#)
+                    .dispatch();
+                serde_json::from_str::<serde_json::Value>(&response.body_string().unwrap()).unwrap()
+            };
+
+            assert_eq!(body("/tv/episode?name=a%20S01E01")["body"]["ext"], ".mkv");
+            assert_eq!(
+                body("/tv/episode?name=a%20S01E01&default_ext=.mp4")["body"]["ext"],
+                ".mp4"
+            );
+            assert_eq!(
+                body("/tv/episode?name=a%20S01E01.avi")["body"]["ext"],
+                ".avi"
+            );
+            assert_eq!(body("/tv/batch")["body"]["limit"], 1);
+            assert_eq!(body("/tv/batch?limit=2")["body"]["limit"], 2);
+        }
+
+        #[test]
+        fn completeness_total_bound() {
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![completeness_check]),
+            )
+            .expect("Could not make client");
+            let post = |total: String| {
+                client
+                    .post(format!("/tv/completeness?total={}", total))
+                    .header(ContentType::JSON)
+                    .body(r#"["Show S01E01.mkv", "Show S01E99999999.mkv"]"#)
+                    .dispatch()
+                    .status()
+            };
+
+            assert_eq!(post(MAX_COMPLETENESS_TOTAL.to_string()), Status::Ok);
+            assert_eq!(post("100000000000".to_string()), Status::BadRequest);
+            assert_eq!(post(usize::MAX.to_string()), Status::BadRequest);
+        }
+
+        #[test]
+        fn match_candidates_bound() {
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(AppConfig::default())
+                    .mount("/", routes![match_titles]),
+            )
+            .expect("Could not make client");
+            let post = |count: usize| {
+                let candidates = vec!["Show"; count];
+
+                client
+                    .post("/tv/match")
+                    .header(ContentType::JSON)
+                    .body(
+                        serde_json::json!({"name": "Show S01E02.mkv", "candidates": candidates})
+                            .to_string(),
+                    )
+                    .dispatch()
+                    .status()
+            };
+
+            assert_eq!(post(MAX_MATCH_CANDIDATES), Status::Ok);
+            assert_eq!(post(MAX_MATCH_CANDIDATES + 1), Status::BadRequest);
+
+            let mut response = client
+                .post("/tv/match")
+                .header(ContentType::JSON)
+                .body(
+                    serde_json::json!({
+                        "name": "Show S01E02.mkv",
+                        "candidates": ["Show", "a".repeat(1025)]
+                    })
+                    .to_string(),
+                )
+                .dispatch();
+            assert_eq!(response.status(), Status::BadRequest);
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains("Candidate 1 is 1025 characters long, over the maximum of 1024"));
+        }
+
+        #[test]
+        fn invalid_json() {
+            use crate::utils;
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![season])
+                    .register(catchers![utils::bad_request]),
+            )
+            .expect("Could not make client");
+
+            let mut response = client
+                .post("/tv/season")
+                .header(ContentType::JSON)
+                .body(r#"{"episodes": ["a.mkv",}"#)
+                .dispatch();
+            let body: serde_json::Value =
+                serde_json::from_str(&response.body_string().unwrap()).unwrap();
+
+            assert_eq!(response.status(), Status::BadRequest);
+            assert_eq!(body["status"], 400);
+            assert!(body["msg"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid JSON: expected value at line 1"));
+
+            let mut response = client
+                .post("/tv/season")
+                .header(ContentType::JSON)
+                .body(r#"{"episodes": 5, "number": 1}"#)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains(r#""msg":"Invalid JSON: invalid type"#));
+        }
+
+        #[test]
+        fn episode_fields() {
+            use crate::cache::CaptureCache;
+            use rocket::http::Status;
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(CaptureCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![episode]),
+            )
+            .expect("Could not make client");
+
+            let mut response = client
+                .post("/tv/episode?name=Show%20S01E02.mkv&fields=se_string,ext")
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(
+                response.body_string().unwrap(),
+                r#"{"status":200,"msg":"Success","body":{"se_string":"S01E02","ext":".mkv"},"schema_version":1}"#
+            );
+
+            let mut response = client
+                .post("/tv/episode?name=Show%20S01E02.mkv&fields=title,size")
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains("Unknown field `size`, valid fields are"));
+        }
+
+        #[test]
+        fn cached_parse_metrics() {
+            use crate::cache::{BatchCache, CaptureCache};
+            use rocket::http::ContentType;
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(CaptureCache::default())
+                    .manage(BatchCache::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![episode, batch]),
+            )
+            .expect("Could not make client");
+
+            for _ in 0..2 {
+                client.post("/tv/episode?name=Show%20S01E02.mkv").dispatch();
+                client
+                    .post("/tv/batch")
+                    .header(ContentType::JSON)
+                    .body(r#"["Show S01E01.mkv", "Show S01E02.mkv"]"#)
+                    .dispatch();
+            }
+
+            let metrics = client.rocket().state::<Metrics>().unwrap().render();
+
+            assert!(metrics.contains("tagzen_parse_successes_total 6\n"));
+            assert!(metrics.contains("tagzen_capture_cache_total{result=\"hit\"} 1\n"));
+            assert!(metrics.contains("tagzen_batch_cache_total{result=\"hit\"} 1\n"));
+        }
+
+        #[test]
+        fn episode_echo() {
+            use crate::cache::CaptureCache;
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(CaptureCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![episode]),
+            )
+            .expect("Could not make client");
+            let body = |uri: &str| -> serde_json::Value {
+                serde_json::from_str(&client.post(uri).dispatch().body_string().unwrap()).unwrap()
+            };
+
+            let echoed = body("/tv/episode?name=Show%20E02.mkv&season=3&echo=true");
+            assert_eq!(echoed["input"]["name"], "Show E02.mkv");
+            assert_eq!(echoed["input"]["season"], 3);
+            assert_eq!(echoed["input"]["episode"], serde_json::Value::Null);
+            assert_eq!(echoed["body"]["season"], 3);
+
+            let plain = body("/tv/episode?name=Show%20E02.mkv&season=3");
+            assert!(plain.get("input").is_none());
+        }
+
+        #[test]
+        fn episode_compact_fields() {
+            use crate::cache::CaptureCache;
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(CaptureCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![episode]),
+            )
+            .expect("Could not make client");
+            let mut response = client
+                .post("/tv/episode?name=Show%20S01E02&fields=title,season,episode,ext&compact=true")
+                .dispatch();
+
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains(r#""body":{"title":"Show","season":1,"episode":2}"#));
+        }
+
+        #[test]
+        fn episode_too_long() {
+            use crate::cache::CaptureCache;
+            use rocket::http::Status;
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(CaptureCache::default())
+                    .manage(AppConfig {
+                        max_name_len: 16,
+                        ..Default::default()
+                    })
+                    .mount("/", routes![episode]),
+            )
+            .expect("Could not make client");
+
+            let response = client.post("/tv/episode?name=Show%20S01E02.mkv").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+
+            for query in &["", "&all=true", "&format=nfo"] {
+                let mut response = client
+                    .post(format!(
+                        "/tv/episode?name=Longer%20Show%20S01E02.mkv{}",
+                        query
+                    ))
+                    .dispatch();
+                assert_eq!(response.status(), Status::BadRequest);
+                assert!(response
+                    .body_string()
+                    .unwrap()
+                    .contains("Name is 22 characters long, over the maximum of 16"));
+            }
+        }
+
+        #[test]
+        fn json_body_too_long() {
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(AppConfig {
+                        max_name_len: 16,
+                        ..Default::default()
+                    })
+                    .mount("/", routes![season, group, lint, match_titles]),
+            )
+            .expect("Could not make client");
+            let post = |uri: &str, body: &str| {
+                let mut response = client
+                    .post(uri.to_string())
+                    .header(ContentType::JSON)
+                    .body(body)
+                    .dispatch();
+
+                (response.status(), response.body_string().unwrap())
+            };
+            let too_long = "Name is 22 characters long, over the maximum of 16";
+
+            let (status, body) = post(
+                "/tv/season",
+                r#"{"episodes": ["Show E02.mkv"], "number": 1}"#,
+            );
+            assert_eq!(status, Status::Ok);
+            assert!(body.contains(r#""episode":2"#));
+
+            let (status, body) = post(
+                "/tv/season",
+                r#"{"episodes": ["Longer Show S01E02.mkv"], "number": 1}"#,
+            );
+            assert_eq!(status, Status::BadRequest);
+            assert!(body.contains(too_long));
+
+            let (_, body) = post("/tv/group", r#"["Longer Show S01E02.mkv"]"#);
+            assert!(body.contains(r#""unknown":["Longer Show S01E02.mkv"]"#));
+
+            let (_, body) = post("/tv/lint", r#"["Longer Show S01E02.mkv"]"#);
+            assert!(body.contains(r#""rule":"unparsed""#));
+
+            let (status, body) = post(
+                "/tv/match",
+                r#"{"name": "Longer Show S01E02.mkv", "candidates": ["Show"]}"#,
+            );
+            assert_eq!(status, Status::BadRequest);
+            assert!(body.contains(too_long));
+        }
+
+        #[test]
+        fn episode_nfo() {
+            use crate::cache::CaptureCache;
+            use rocket::http::{Accept, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(CaptureCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![episode]),
+            )
+            .expect("Could not make client");
+
+            let mut response = client
+                .post("/tv/episode?name=Show%20S01E02.mkv&format=nfo")
+                .dispatch();
+            assert_eq!(response.content_type(), Some(ContentType::XML));
+            let body = response.body_string().unwrap();
+            assert!(body.starts_with("<?xml"));
+            assert!(body.contains("<episodedetails>\n    <title>Show</title>"));
+            assert!(body.trim_end().ends_with("</episodedetails>"));
+
+            let response = client
+                .post("/tv/episode?name=Show%20S01E02.mkv")
+                .header(Accept::XML)
+                .dispatch();
+            assert_eq!(response.content_type(), Some(ContentType::XML));
+
+            let response = client
+                .post("/tv/episode?name=nothing.mkv&format=nfo")
+                .dispatch();
+            assert_eq!(response.status(), Status::BadRequest);
+            assert_eq!(response.content_type(), Some(ContentType::JSON));
+
+            let response = client
+                .post("/tv/episode?name=Show%20S01E02.mkv&format=yaml")
+                .dispatch();
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+
+        #[test]
+        fn batch_csv() {
+            use rocket::http::{Accept, ContentType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(BatchCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![batch]),
+            )
+            .expect("Could not make client");
+            let names = r#"["Show, The S01E02.mkv", "Other \"Show\" S02E01"]"#;
+
+            let mut response = client
+                .post("/tv/batch?format=csv")
+                .header(ContentType::JSON)
+                .body(names)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.content_type(), Some(ContentType::CSV));
+            assert_eq!(
+                response.body_string().unwrap(),
+                "file_path,title,season,episode,ext\r\n\"Show, The S01E02.mkv\",\"Show, The\",1,2,.mkv\r\n\"Other \"\"Show\"\" S02E01\",\"Other \"\"Show\"\"\",2,1,\r\n"
+            );
+
+            let mut response = client
+                .post("/tv/batch?fields=se_string&limit=1")
+                .header(ContentType::JSON)
+                .header(Accept::CSV)
+                .body(names)
+                .dispatch();
+
+            assert_eq!(response.body_string().unwrap(), "se_string\r\nS01E02\r\n");
+
+            let response = client
+                .post("/tv/batch?format=xml")
+                .header(ContentType::JSON)
+                .body(names)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+
+        #[test]
+        fn reparse_route() {
+            use rocket::http::{ContentType, Status};
+
+            let client = rocket::local::Client::new(rocket::ignite().mount("/", routes![reparse]))
+                .expect("Could not make client");
+            let cap = Capture::new(
+                "Show S01E02.mkv".to_string(),
+                &Context::default(),
+                &Default::default(),
+            )
+            .unwrap();
+            let mut edited = serde_json::to_value(&cap).unwrap();
+            edited["season"] = 4.into();
+
+            let mut response = client
+                .post("/tv/reparse")
+                .header(ContentType::JSON)
+                .body(edited.to_string())
+                .dispatch();
+            let body: serde_json::Value =
+                serde_json::from_str(&response.body_string().unwrap()).unwrap();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(body["body"]["render"], "Show - S04E02.mkv");
+            assert_eq!(body["body"]["se_string"], "S04E02");
+            assert_eq!(body["body"]["season"], 4);
+
+            let response = client
+                .post("/tv/reparse")
+                .header(ContentType::JSON)
+                .body(r#"{"season": 4}"#)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::BadRequest);
+        }
+
+        #[test]
+        fn batch_ndjson() {
+            use rocket::http::{Accept, ContentType, MediaType, Status};
+
+            let client = rocket::local::Client::new(
+                rocket::ignite()
+                    .manage(Metrics::default())
+                    .manage(IdempotencyCache::default())
+                    .manage(BatchCache::default())
+                    .manage(AppConfig::default())
+                    .mount("/", routes![batch]),
+            )
+            .expect("Could not make client");
+            let names = r#"["Show S01E02.mkv", "Other S02E01.mkv", "Last S03E04.mkv"]"#;
+
+            let mut response = client
+                .post("/tv/batch?format=ndjson&fields=title,episode")
+                .header(ContentType::JSON)
+                .body(names)
+                .dispatch();
+
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(
+                response.content_type(),
+                Some(ContentType::new("application", "x-ndjson"))
+            );
+            let body = response.body_string().unwrap();
+            let lines: Vec<serde_json::Value> = body
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            assert_eq!(
+                lines,
+                vec![
+                    serde_json::json!({"title": "Show", "episode": 2}),
+                    serde_json::json!({"title": "Other", "episode": 1}),
+                    serde_json::json!({"title": "Last", "episode": 4}),
+                ]
+            );
+
+            let mut response = client
+                .post("/tv/batch?offset=1&limit=1")
+                .header(ContentType::JSON)
+                .header(Accept::from(MediaType::new("application", "x-ndjson")))
+                .body(names)
+                .dispatch();
+            let body = response.body_string().unwrap();
+
+            assert_eq!(body.lines().count(), 1);
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(body.trim_end()).unwrap()["file_path"],
+                "Other S02E01.mkv"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::compile_user_regex;
+
+    const TEST_1: (&str, usize, usize) = ("hello s01 e02 hi.mp4", 2, 1);
+    const TEST_2: (&str, usize, usize) = ("xs01e20.epc", 20, 1);
+    const TEST_3: (&str, usize, usize) = ("SEASON3EPISODE4", 4, 3);
+    const TEST_4: (&str, usize, usize) = ("EPIsode2 and SEASOn 0002.exy", 2, 2);
+    const TEST_5: (&str, usize, usize) = ("hiS01E04.ex", 4, 1);
+
+    #[test]
+    fn episode_simple() {
+        assert_eq!(cap_episode(TEST_1.0), Ok(TEST_1.1));
+        assert_eq!(cap_episode(TEST_2.0), Ok(TEST_2.1));
         assert_eq!(cap_episode(TEST_3.0), Ok(TEST_3.1));
         assert_eq!(cap_episode(TEST_4.0), Ok(TEST_4.1));
         assert_eq!(cap_episode(TEST_5.0), Ok(TEST_5.1));
     }
 
     #[test]
-    fn season_simple() {
-        assert_eq!(cap_season(TEST_1.0), Ok(TEST_1.2));
-        assert_eq!(cap_season(TEST_2.0), Ok(TEST_2.2));
-        assert_eq!(cap_season(TEST_3.0), Ok(TEST_3.2));
-        assert_eq!(cap_season(TEST_4.0), Ok(TEST_4.2));
-        assert_eq!(cap_season(TEST_5.0), Ok(TEST_5.2));
+    fn episode_word_boundary() {
+        assert_eq!(cap_episode("Maze 5"), Err(CaptureError::NoEpisodeRegex));
+        assert_eq!(cap_episode("Take5"), Err(CaptureError::NoEpisodeRegex));
+        assert_eq!(cap_episode("Maze e05"), Ok(5));
+        assert_eq!(cap_episode("Base64things e2"), Ok(2));
+        assert_eq!(
+            Capture::new(
+                "Maze Runner.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            ),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+        assert_eq!(
+            Capture::new(
+                "Maze 5.mkv".to_string(),
+                &Context {
+                    season: Some(1),
+                    episode: None
+                },
+                &Default::default()
+            ),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+
+        let cap = Capture::new(
+            "Maze e05.mkv".to_string(),
+            &Context {
+                season: Some(1),
+                episode: None,
+            },
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!((cap.title.as_str(), cap.episode), ("Maze", 5));
+        assert_eq!(
+            Inspection::new("Maze 5 e06.mkv".to_string()).episodes[0].text,
+            "e06"
+        );
+    }
+
+    #[test]
+    fn season_simple() {
+        assert_eq!(cap_season(TEST_1.0), Ok(TEST_1.2));
+        assert_eq!(cap_season(TEST_2.0), Ok(TEST_2.2));
+        assert_eq!(cap_season(TEST_3.0), Ok(TEST_3.2));
+        assert_eq!(cap_season(TEST_4.0), Ok(TEST_4.2));
+        assert_eq!(cap_season(TEST_5.0), Ok(TEST_5.2));
+    }
+
+    #[test]
+    fn same_episode_different_names() {
+        let context = Context::default();
+        let options = CaptureOptions::default();
+        let first = Capture::new("show s01e02.mkv".to_string(), &context, &options).unwrap();
+        let second =
+            Capture::new("The.Show.S1E2.1080p.mp4".to_string(), &context, &options).unwrap();
+        let third = Capture::new("show s01e03.mkv".to_string(), &context, &options).unwrap();
+
+        assert_ne!(first, second);
+        assert!(first.same_episode(&second));
+        assert!(!first.same_episode(&third));
+    }
+
+    #[test]
+    fn same_episode_titles() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap()
+        };
+
+        assert!(!cap("ShowA S01E02.mkv").same_episode(&cap("ShowB S01E02.mkv")));
+        assert!(cap("SHOW.a S01E02.mkv").same_episode(&cap("Show.A.S01E02.mkv")));
+        assert!(cap("S01E02.mkv").same_episode(&cap("Show S01E02.mkv")));
+        assert_eq!(title_key("The.Show"), "show");
+        assert_eq!(title_key("The"), "the");
+    }
+
+    #[test]
+    fn sample_files() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap()
+        };
+
+        assert!(cap("Show.S01E02.sample.mkv").is_sample);
+        assert!(cap("Show S01E02 Trailer.mp4").is_sample);
+        assert!(!cap("Show.S01E02.720p.mkv").is_sample);
+        assert!(!cap("Samples Of Life S01E02.mkv").is_sample);
+    }
+
+    #[test]
+    fn bracket_blocks() {
+        let options = CaptureOptions {
+            absolute: true,
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "[Group] Show - 05 (BD 1080p) [A1B2C3D4].mkv".to_string(),
+            &Context::default(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(cap.blocks, vec!["Group", "BD 1080p", "A1B2C3D4"]);
+        assert_eq!(cap.group, Some("Group".to_string()));
+        assert_eq!(cap.crc, Some("A1B2C3D4".to_string()));
+        assert_eq!(cap.tags, vec!["1080p"]);
+        assert_eq!((cap.title.as_str(), cap.episode), ("Show", 5));
+
+        let titled = Capture::new(
+            "[Show] S01E02.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(titled.group, None);
+        assert_eq!(titled.blocks, vec!["Show"]);
+    }
+
+    #[test]
+    fn capture_codecs() {
+        let cap = Capture::new(
+            "Show.S01E02.1080p.BluRay.x265.DTS.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cap.container, Some("mkv".to_string()));
+        assert_eq!(cap.video_codec, Some("H.265".to_string()));
+        assert_eq!(cap.audio_codec, Some("DTS".to_string()));
+        assert_eq!(cap.tags, vec!["1080p", "BluRay", "x265", "DTS"]);
+
+        let plain = Capture::new(
+            "Show S01E02.srt".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            (plain.container, plain.video_codec, plain.audio_codec),
+            (None, None, None)
+        );
+    }
+
+    #[test]
+    fn capture_platform() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap()
+        };
+        let netflix = cap("Show.S01E02.NF.WEB-DL.mkv");
+
+        assert_eq!(netflix.platform, Some("Netflix".to_string()));
+        assert_eq!(netflix.episode_title, None);
+        assert_eq!(netflix.name, "Show S01E02 WEB DL");
+
+        let amazon = cap("Show.S01E02.The.Title.AMZN.WEBRip.mkv");
+
+        assert_eq!(amazon.platform, Some("Amazon".to_string()));
+        assert_eq!(amazon.episode_title, Some("The Title".to_string()));
+        assert_eq!(cap("Show.S01E02.WEB-DL.mkv").platform, None);
+    }
+
+    #[test]
+    fn capture_audio_channels() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap()
+        };
+        let dotted = cap("Show.S01E02.DD5.1.mkv");
+
+        assert_eq!(dotted.ext, Some(".mkv".to_string()));
+        assert_eq!(dotted.audio_channels, Some("5.1".to_string()));
+        assert_eq!((dotted.season, dotted.episode), (1, 2));
+
+        let bare = cap("Show.S01E02.DDP5.1");
+
+        assert_eq!(bare.ext, None);
+        assert_eq!(bare.audio_channels, Some("5.1".to_string()));
+        assert_eq!(
+            cap("Show.S01E02.DDP7.1.Atmos.mkv").audio_channels,
+            Some("7.1 Atmos".to_string())
+        );
+        assert_eq!(cap("Show S01E02.mkv").audio_channels, None);
+    }
+
+    #[test]
+    fn capture_sub_language() {
+        let context = Context::default();
+        let options = CaptureOptions::default();
+        let cap = Capture::new("Show.S01E02.en.srt".to_string(), &context, &options).unwrap();
+
+        assert_eq!(cap.ext, Some(".srt".to_string()));
+        assert_eq!(cap.sub_language, Some("en".to_string()));
+        assert_eq!(cap.name, "Show S01E02");
+    }
+
+    #[test]
+    fn bare_episode() {
+        let context = Context::default();
+        let options = CaptureOptions {
+            bare_episode: true,
+            ..Default::default()
+        };
+        let cap = Capture::new("Show 5.mkv".to_string(), &context, &options).unwrap();
+
+        assert_eq!((cap.season, cap.episode), (1, 5));
+        assert_eq!(
+            Capture::new("Show 5.mkv".to_string(), &context, &Default::default()),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+    }
+
+    #[test]
+    fn bare_episode_year() {
+        let options = CaptureOptions {
+            bare_episode: true,
+            detect_year: true,
+            ..Default::default()
+        };
+        let cap = Capture::new("Show 2019 5.mkv".to_string(), &Context::default(), &options);
+
+        assert_eq!(cap.map(|cap| (cap.year, cap.episode)), Ok((Some(2019), 5)));
+        assert_eq!(
+            Capture::new("Show 2019.mkv".to_string(), &Context::default(), &options),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+    }
+
+    #[test]
+    fn season_overrides() {
+        let season: Season = serde_json::from_str(
+            r#"{
+                "episodes": [
+                    "Show ep1.mkv",
+                    {"name": "Show Special ep1.mkv", "season": 0},
+                    {"name": "Show Bonus.mkv", "episode": 7}
+                ],
+                "number": 2
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            season.episodes[1],
+            SeasonEpisode::Override {
+                name: "Show Special ep1.mkv".to_string(),
+                season: Some(0),
+                episode: None
+            }
+        );
+        assert_eq!(
+            season
+                .capture(&Default::default())
+                .unwrap()
+                .iter()
+                .map(|cap| (cap.season, cap.episode))
+                .collect::<Vec<_>>(),
+            vec![(2, 1), (0, 1), (2, 7)]
+        );
+    }
+
+    #[test]
+    fn season_shared_context() {
+        let season = Season {
+            episodes: vec![
+                "a ep1.mp4".to_string().into(),
+                "other hello season 6 episode 2.mpv".to_string().into(),
+            ],
+            number: 5,
+        };
+        let context = season.context();
+
+        assert_eq!(context.episode, None);
+
+        let caps = season.capture(&Default::default()).unwrap();
+
+        assert_eq!(
+            caps.iter()
+                .map(|cap| (cap.season, cap.episode))
+                .collect::<Vec<_>>(),
+            vec![(5, 1), (5, 2)]
+        );
+    }
+
+    #[test]
+    fn season_pack() {
+        assert_eq!(
+            cap_season_pack("Show S01 Complete"),
+            Some("Show".to_string())
+        );
+        assert_eq!(
+            cap_season_pack("Show.Season.2.Full.1080p"),
+            Some("Show".to_string())
+        );
+        assert_eq!(
+            cap_season_pack("Show Complete Series"),
+            Some("Show".to_string())
+        );
+        assert_eq!(cap_season_pack("Show S01-S03"), Some("Show".to_string()));
+        assert_eq!(cap_season_pack("Show S01E02 Complete"), None);
+        assert_eq!(cap_season_pack("Full Metal Show E01"), None);
+        assert_eq!(cap_season_pack("Show S2 - 13"), None);
+
+        let season = Season {
+            episodes: vec![
+                "Show S01 Complete.mkv".to_string().into(),
+                "E01.mkv".to_string().into(),
+                "Other E02.mkv".to_string().into(),
+            ],
+            number: 1,
+        };
+
+        assert_eq!(
+            season
+                .capture(&Default::default())
+                .unwrap()
+                .iter()
+                .map(|cap| (cap.title.as_str(), cap.season, cap.episode, cap.is_pack))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Show", 1, 0, true),
+                ("Show", 1, 1, false),
+                ("Other", 1, 2, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn season_complete_pack() {
+        let season = Season {
+            episodes: vec![
+                "Show.S01E01.1080p.mkv".to_string().into(),
+                "Show.S01.COMPLETE.1080p.mkv".to_string().into(),
+                "Show.S01E02.1080p.mkv".to_string().into(),
+                "Show.Season.1.FULL.mkv".to_string().into(),
+            ],
+            number: 1,
+        };
+        let caps = season.capture(&Default::default()).unwrap();
+
+        assert_eq!(
+            caps.iter()
+                .map(|cap| (cap.season, cap.episode, cap.is_pack))
+                .collect::<Vec<_>>(),
+            vec![(1, 1, false), (1, 0, true), (1, 2, false), (1, 0, true)]
+        );
+        assert_eq!(caps[1].title, "Show");
+        assert_eq!(caps[1].episodes, Vec::<usize>::new());
+        assert_eq!(caps[3].episodes, Vec::<usize>::new());
+        assert_eq!(find_duplicates(&caps), vec![]);
+    }
+
+    #[test]
+    fn season_duplicates() {
+        let season = Season {
+            episodes: vec![
+                "Show E01-E03.mkv".to_string().into(),
+                "Show E02.mkv".to_string().into(),
+                "Show E03.mkv".to_string().into(),
+                "Show E03 PROPER.mkv".to_string().into(),
+            ],
+            number: 1,
+        };
+        let caps = season.capture(&Default::default()).unwrap();
+
+        assert_eq!(
+            find_duplicates(&caps),
+            vec![
+                Duplicate {
+                    season: 1,
+                    episode: 2,
+                    names: vec!["Show E01-E03.mkv".to_string(), "Show E02.mkv".to_string()],
+                },
+                Duplicate {
+                    season: 1,
+                    episode: 3,
+                    names: vec![
+                        "Show E01-E03.mkv".to_string(),
+                        "Show E03.mkv".to_string(),
+                        "Show E03 PROPER.mkv".to_string()
+                    ],
+                },
+            ]
+        );
+        assert_eq!(find_duplicates(&caps[1..3]), vec![]);
+    }
+
+    #[test]
+    fn season_completeness() {
+        let season = Season {
+            episodes: vec![
+                "Show E01-E02.mkv".to_string().into(),
+                "Show E04.mkv".to_string().into(),
+                "Show E02 PROPER.mkv".to_string().into(),
+                "Show E06.mkv".to_string().into(),
+            ],
+            number: 1,
+        };
+        let caps = season.capture(&Default::default()).unwrap();
+
+        assert_eq!(
+            completeness(caps.clone(), 5),
+            Completeness {
+                present: vec![1, 2, 4],
+                missing: vec![3, 5],
+                extra: vec![
+                    "Show E02 PROPER.mkv".to_string(),
+                    "Show E06.mkv".to_string()
+                ],
+                unknown: vec![],
+            }
+        );
+        assert_eq!(
+            completeness(caps, 0),
+            Completeness {
+                extra: vec![
+                    "Show E01-E02.mkv".to_string(),
+                    "Show E04.mkv".to_string(),
+                    "Show E02 PROPER.mkv".to_string(),
+                    "Show E06.mkv".to_string()
+                ],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn custom_regex() {
+        let options = CaptureOptions {
+            episode_regex: Some(compile_user_regex(r"(?i)ch(apter)? *[0-9]+").unwrap()),
+            season_regex: Some(compile_user_regex(r"(?i)vol *[0-9]+").unwrap()),
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "Show vol2 ch5.mkv".to_string(),
+            &Context::default(),
+            &options,
+        );
+
+        assert_eq!(cap.map(|cap| (cap.season, cap.episode)), Ok((2, 5)));
+        assert_eq!(
+            Capture::new("Show s01e02.mkv".to_string(), &Context::default(), &options),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+    }
+
+    #[test]
+    fn non_decimal_custom_regex() {
+        let options = CaptureOptions {
+            episode_regex: Some(compile_user_regex(r"0x[0-9A-Fa-f]+|ep[0-9]+").unwrap()),
+            ..Default::default()
+        };
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &options)
+                .map(|cap| (cap.season, cap.episode))
+        };
+
+        assert_eq!(cap("Show S01 0x1A.mkv"), Err(CaptureError::NoEpisodeRegex));
+        assert_eq!(cap("Show S01 0x1A ep3.mkv"), Ok((1, 3)));
+    }
+
+    #[test]
+    fn word_numbers() {
+        let options = CaptureOptions {
+            word_numbers: true,
+            ..Default::default()
+        };
+        let cap = |name: &str, options: &CaptureOptions| {
+            Capture::new(name.to_string(), &Context::default(), options)
+                .map(|cap| (cap.season, cap.episode))
+        };
+
+        assert_eq!(
+            cap("Show Season Two Episode Five.mkv", &options),
+            Ok((2, 5))
+        );
+        assert_eq!(
+            cap("Show.Season.Seventeen.Ep.Twenty-One.mkv", &options),
+            Ok((17, 21))
+        );
+        assert_eq!(cap("Show s01e02.mkv", &options), Ok((1, 2)));
+        assert_eq!(
+            cap("Show Season Two Episode Five.mkv", &Default::default()),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+    }
+
+    #[test]
+    fn sources() {
+        let options = CaptureOptions {
+            bare_episode: true,
+            word_numbers: true,
+            ..Default::default()
+        };
+        let sources = |name: &str, context: &Context| {
+            Capture::new(name.to_string(), context, &options)
+                .map(|cap| (cap.season_source, cap.episode_source))
+        };
+
+        assert_eq!(
+            sources("Show s01e02.mkv", &Context::default()),
+            Ok((Source::Regex, Source::Regex))
+        );
+        assert_eq!(
+            sources(
+                "Show e02.mkv",
+                &Context {
+                    season: Some(1),
+                    episode: None
+                }
+            ),
+            Ok((Source::Context, Source::Regex))
+        );
+        assert_eq!(
+            sources("Show Season Two 5.mkv", &Context::default()),
+            Ok((Source::Words, Source::Bare))
+        );
+        assert_eq!(
+            sources("Show 5.mkv", &Context::default()),
+            Ok((Source::Default, Source::Bare))
+        );
+    }
+
+    #[test]
+    fn render_simple() {
+        assert_eq!(
+            render_episode("Title", 1, 2, Some("mkv")),
+            "Title - S01E02.mkv"
+        );
+        assert_eq!(
+            render_episode("Title", 1, 2, Some(".mkv")),
+            "Title - S01E02.mkv"
+        );
+        assert_eq!(render_episode("Title", 10, 200, None), "Title - S10E200");
+    }
+
+    #[test]
+    fn control_characters() {
+        let cap = Capture::new(
+            "Show S01E02.mkv\r\n".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cap.file_path, "Show S01E02.mkv");
+        assert_eq!(cap.ext, Some(".mkv".to_string()));
+        assert_eq!(cap.name, "Show S01E02");
+    }
+
+    #[test]
+    fn episode_ranges() {
+        assert_eq!(cap_episodes("Show e01-e03"), Some(vec![1, 2, 3]));
+        assert_eq!(cap_episodes("Show e01 to e03"), Some(vec![1, 2, 3]));
+        assert_eq!(cap_episodes("Show e01 & e04"), Some(vec![1, 4]));
+        assert_eq!(cap_episodes("Show e01+e02"), Some(vec![1, 2]));
+        assert_eq!(
+            cap_episodes("Show ep1 to ep3 & ep5"),
+            Some(vec![1, 2, 3, 5])
+        );
+        assert_eq!(cap_episodes("Show e01-e900"), Some(vec![1, 900]));
+        assert_eq!(cap_episodes("Show e01"), None);
+    }
+
+    #[test]
+    fn compact_episodes() {
+        assert_eq!(cap_compact_episodes("Show S01E02E03"), Some(vec![2, 3]));
+        assert_eq!(
+            cap_compact_episodes("show.s02e05e06e07.mkv"),
+            Some(vec![5, 6, 7])
+        );
+        assert_eq!(cap_compact_episodes("Show S01E02"), None);
+        assert_eq!(cap_compact_episodes("Show S01E02-E03"), None);
+
+        let cap = Capture::new(
+            "Show S01E02E03.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!((cap.season, cap.episode), (1, 2));
+        assert_eq!(cap.episodes, vec![2, 3]);
+        assert_eq!(cap.title, "Show");
+    }
+
+    #[test]
+    fn crc_removed() {
+        let absolute = CaptureOptions {
+            absolute: true,
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "Show - 05 [A1B2C3D4].mkv".to_string(),
+            &Context::default(),
+            &absolute,
+        )
+        .unwrap();
+
+        assert_eq!(cap.crc, Some("A1B2C3D4".to_string()));
+        assert_eq!(cap.title, "Show");
+        assert_eq!(cap.episode, 5);
+
+        let cap = Capture::new(
+            "Show S01E05 [1080p].mkv".to_string(),
+            &Context::default(),
+            &CaptureOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cap.crc, None);
+        assert_eq!(cap.tags, vec!["1080p".to_string()]);
+    }
+
+    #[test]
+    fn site_prefixes() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default())
+                .map(|cap| (cap.title, cap.se_string))
+        };
+
+        assert_eq!(
+            cap("www.Torrenting.com - Show.S01E02.mkv"),
+            Ok(("Show".to_string(), "S01E02".to_string()))
+        );
+        assert_eq!(
+            cap("[www.site.org] Show Name S01E02.mkv"),
+            Ok(("Show Name".to_string(), "S01E02".to_string()))
+        );
+    }
+
+    #[test]
+    fn numeric_names() {
+        let merged = CaptureOptions {
+            merged: true,
+            ..Default::default()
+        };
+        let cap = |name: &str, options: &CaptureOptions| {
+            Capture::new(name.to_string(), &Context::default(), options)
+                .map(|cap| (cap.season, cap.episode, cap.season_source))
+        };
+
+        assert_eq!(
+            cap("0205.mkv", &Default::default()),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+        assert_eq!(cap("0205.mkv", &merged), Ok((2, 5, Source::Merged)));
+        assert_eq!(cap("12345.mp4", &merged), Err(CaptureError::NoEpisodeRegex));
+        assert_eq!(
+            cap("Show S01E02 0205.mkv", &merged),
+            Ok((1, 2, Source::Regex))
+        );
+        assert_eq!(cap("Show105.mkv", &merged), Ok((1, 5, Source::Merged)));
+        assert_eq!(cap("Show0105.mkv", &merged), Ok((1, 5, Source::Merged)));
+        assert_eq!(
+            cap("Show105.mkv", &Default::default()),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+        assert_eq!(cap_merged_se("Show0105 x264", None), Some((1, 5)));
+        assert_eq!(cap_merged_se("Show x264", None), None);
+        assert_eq!(cap_merged_se("Show0005", None), None);
+        assert_eq!(cap_merged_se("Show105 0203", None), Some((2, 3)));
+    }
+
+    #[test]
+    fn dash_season_episode() {
+        let options = CaptureOptions {
+            dash_se: true,
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "Show - 1 - 2.mkv".to_string(),
+            &Context::default(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(cap_dash_se("Show - 1 - 2"), Some((1, 2)));
+        assert_eq!(cap_dash_se("Show - 13"), None);
+        assert_eq!(
+            (cap.title.as_str(), cap.season, cap.episode),
+            ("Show", 1, 2)
+        );
+        assert_eq!(
+            (cap.season_source, cap.episode_source),
+            (Source::Dash, Source::Dash)
+        );
+        assert_eq!(
+            Capture::new(
+                "Show - 1 - 2.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            ),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+
+        let marked = Capture::new(
+            "Show - 1 - 2 S03E04.mkv".to_string(),
+            &Context::default(),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!((marked.season, marked.episode), (3, 4));
+    }
+
+    #[test]
+    fn interpretations() {
+        let readings_with = |name: &str, options: &CaptureOptions| {
+            interpret(name.to_string(), options)
+                .unwrap()
+                .into_iter()
+                .map(|found| (found.season, found.episode, found.strategy))
+                .collect::<Vec<_>>()
+        };
+        let readings = |name: &str| {
+            interpret(name.to_string(), &Default::default())
+                .unwrap()
+                .into_iter()
+                .map(|found| (found.season, found.episode, found.strategy))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            readings("Show 102.mkv"),
+            vec![(1, 2, Source::Merged), (1, 102, Source::Bare)]
+        );
+        assert_eq!(
+            readings("Show - 2 - 13.mkv"),
+            vec![
+                (2, 13, Source::Dash),
+                (1, 2, Source::Absolute),
+                (1, 13, Source::Bare)
+            ]
+        );
+        assert_eq!(readings("Show S01E02.mkv")[0], (1, 2, Source::Regex));
+        assert!(readings("Show.mkv").is_empty());
+
+        let detect_year = CaptureOptions {
+            detect_year: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            readings_with("Show (2019) 5.mkv", &detect_year),
+            vec![(1, 5, Source::Bare)]
+        );
+        assert_eq!(
+            readings("Show (2019) 5.mkv"),
+            vec![(20, 19, Source::Merged), (1, 5, Source::Bare)]
+        );
+
+        let basename = CaptureOptions {
+            basename: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            readings_with("Show/Season 3/[Group] Show E02.mkv", &basename)[0],
+            (3, 2, Source::Regex)
+        );
+        assert!(interpret("a".repeat(2048), &Default::default()).is_err());
+
+        let merged = CaptureOptions {
+            absolute: true,
+            merged: true,
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "Show 2019 - 03.mkv".to_string(),
+            &Context::default(),
+            &merged,
+        )
+        .unwrap();
+        assert!(readings_with("Show 2019 - 03.mkv", &merged).contains(&(
+            cap.season,
+            cap.episode,
+            cap.episode_source
+        )));
+
+        let ranked = interpret("Show S01E02 102.mkv".to_string(), &Default::default()).unwrap();
+        assert!(ranked
+            .windows(2)
+            .all(|pair| pair[0].confidence >= pair[1].confidence));
+    }
+
+    #[test]
+    fn episode_parts() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap()
+        };
+        let first = cap("Show S01E05 pt1.mkv");
+        let second = cap("Show S01E05 Part 2 720p.mkv");
+
+        assert_eq!((first.episode, first.part), (5, Some(1)));
+        assert_eq!((second.episode, second.part), (5, Some(2)));
+        assert_eq!((first.episode_title, second.episode_title), (None, None));
+
+        let titled = cap("Show S01E05 The Finale pt.2.mkv");
+
+        assert_eq!(titled.part, Some(2));
+        assert_eq!(titled.episode_title, Some("The Finale".to_string()));
+        assert_eq!(cap("Part 2 Show S01E05.mkv").part, None);
+        assert_eq!(cap_part("Show S01E05 Party.mkv".to_string()).1, None);
+    }
+
+    #[test]
+    fn release_versions() {
+        let absolute = CaptureOptions {
+            absolute: true,
+            ..Default::default()
+        };
+        let cap = |name: &str, options: &CaptureOptions| {
+            Capture::new(name.to_string(), &Context::default(), options).unwrap()
+        };
+        let anime = cap("[Group] Show - 05v2 [1080p].mkv", &absolute);
+        let marked = cap("Show S01E12v3.mkv", &Default::default());
+
+        assert_eq!((anime.episode, anime.release_version), (5, Some(2)));
+        assert_eq!(anime.title, "Show");
+        assert_eq!(anime.episode_raw, Some("05".to_string()));
+        assert_eq!((marked.episode, marked.release_version), (12, Some(3)));
+        assert_eq!(
+            cap("Show S01E05.mkv", &Default::default()).release_version,
+            None
+        );
+        assert_eq!(
+            cap_release_version("Show S01E05 v2.mkv".to_string()),
+            ("Show S01E05 v2.mkv".to_string(), None)
+        );
+        assert_eq!(cap_release_version("Show 5v2x".to_string()).1, None);
+    }
+
+    #[test]
+    fn absolute_four_digits() {
+        let absolute = CaptureOptions {
+            absolute: true,
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "[Group] One Piece - 1045 [1080p].mkv".to_string(),
+            &Context::default(),
+            &absolute,
+        )
+        .unwrap();
+
+        assert_eq!((cap.title.as_str(), cap.episode), ("One Piece", 1045));
+        assert_eq!(cap.episode_source, Source::Absolute);
+        assert_eq!(cap.se_string, "S01E1045");
+        assert_eq!(
+            cap.render_template("{title} - {episode:03}"),
+            Ok("One Piece - 1045".to_string())
+        );
+    }
+
+    #[test]
+    fn absolute_with_season() {
+        let absolute = CaptureOptions {
+            absolute: true,
+            ..Default::default()
+        };
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &absolute).map(|cap| {
+                (
+                    cap.season,
+                    cap.season_source,
+                    cap.episode,
+                    cap.episode_source,
+                )
+            })
+        };
+
+        assert_eq!(
+            cap("Show S2 - 13.mkv"),
+            Ok((2, Source::Regex, 13, Source::Absolute))
+        );
+        assert_eq!(
+            cap("Show Season 2 - 13 [1080p].mkv"),
+            Ok((2, Source::Regex, 13, Source::Absolute))
+        );
+        assert_eq!(
+            cap("Show - 13.mkv"),
+            Ok((1, Source::Default, 13, Source::Absolute))
+        );
+        assert_eq!(
+            cap("Show S01E02.mkv"),
+            Ok((1, Source::Regex, 2, Source::Regex))
+        );
+        assert_eq!(
+            Capture::new(
+                "Show S2 - 13.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            ),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+    }
+
+    #[test]
+    fn capture_fields() {
+        let cap = Capture::new(
+            "Show S01E02.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        let value = serde_json::to_value(&cap).unwrap();
+        let keys: Vec<_> = value.as_object().unwrap().keys().collect();
+
+        assert_eq!(keys.len(), CAPTURE_FIELDS.len());
+        assert!(CAPTURE_FIELDS
+            .iter()
+            .all(|field| value.get(field).is_some()));
+        assert_eq!(
+            serde_json::to_string(&select_fields(
+                vec![cap],
+                &parse_capture_fields(Some("episode,title")).unwrap()
+            ))
+            .unwrap(),
+            r#"[{"episode":2,"title":"Show"}]"#
+        );
+        assert!(parse_capture_fields(Some("title,nope"))
+            .unwrap_err()
+            .starts_with("Unknown field `nope`, valid fields are file_path, name"));
+    }
+
+    #[test]
+    fn reparse_edited() {
+        let cap = Capture::new(
+            "Show.S01E02E03.720p.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        let edited = Capture {
+            title: "  Other.Show ".to_string(),
+            season: 3,
+            ..cap.clone()
+        }
+        .reparse();
+
+        assert_eq!(cap.clone().reparse(), cap);
+        assert_eq!(edited.title, "Other Show");
+        assert_eq!(edited.se_string, "S03E02");
+        assert_eq!(edited.season_raw, None);
+        assert_eq!(edited.episode_raw, Some("02".to_string()));
+        assert_eq!(edited.episodes, vec![2, 3]);
+        assert_eq!(edited.render(), "Other Show - S03E02.mkv");
+
+        let edited = Capture {
+            episode: 7,
+            ext: Some(".avi".to_string()),
+            ..cap
+        }
+        .reparse();
+
+        assert_eq!(edited.episodes, vec![7]);
+        assert_eq!(edited.container, Some("avi".to_string()));
+    }
+
+    #[test]
+    fn stream_events() {
+        let metrics = Metrics::default();
+        let input = format!(
+            "Show S01E02.mkv\n\nnothing\n{}\n{}\nShows S02E03.mkv",
+            "é".repeat(100),
+            "a".repeat(17)
+        );
+        let mut stream = CaptureStream::new(input.as_bytes(), &metrics, 16);
+        let mut events = String::new();
+        let mut flushes = 0;
+        let mut buf = [0; 64];
+
+        loop {
+            match stream.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => events.push_str(std::str::from_utf8(&buf[..read]).unwrap()),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => flushes += 1,
+                Err(err) => panic!("{}", err),
+            }
+        }
+
+        let events: Vec<&str> = events.split_terminator("\n\n").collect();
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(flushes, 5);
+        assert!(events.iter().all(|event| event.starts_with("data: {")));
+        assert!(events[0].contains(r#""status":200"#) && events[0].contains(r#""episode":2"#));
+        assert!(events[1].contains(r#""status":400"#));
+        assert!(events[2].contains("Name is 100 characters long, over the maximum of 16"));
+        assert!(events[3].contains("Name is 17 characters long, over the maximum of 16"));
+        assert!(events[4].contains(r#""season":2"#) && events[4].contains(r#""episode":3"#));
+    }
+
+    #[test]
+    fn derived_strings() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap()
+        };
+
+        assert_eq!(cap("Show S01E02.mkv").se_string, "S01E02");
+        assert_eq!(cap("Show S10E102.mkv").se_string, "S10E102");
+        assert_eq!(cap("Show S01E02.mkv").airdate, None);
+        assert_eq!(
+            cap("Daily Show S01E02 2021.03.14.mkv")
+                .airdate
+                .map(|airdate| airdate.iso),
+            Some("2021-03-14".to_string())
+        );
+    }
+
+    #[test]
+    fn sxe_orders() {
+        assert_eq!(cap_sxe("Show S01E02.mkv"), Some((1, 2)));
+        assert_eq!(cap_sxe("Show.s01.e02"), Some((1, 2)));
+        assert_eq!(cap_sxe("Show E02S01.mkv"), Some((1, 2)));
+        assert_eq!(cap_sxe("Show E02"), None);
+        assert_eq!(cap_sxe("Shows01e02"), None);
+
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default())
+                .map(|cap| (cap.season, cap.episode))
+        };
+
+        assert_eq!(cap("Show E02S01.mkv"), Ok((1, 2)));
+        assert_eq!(cap("Show S01E02.mkv"), Ok((1, 2)));
+        assert_eq!(cap("The 100 S03E04.mkv"), Ok((3, 4)));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let bounded = CaptureOptions {
+            max_season: Some(100),
+            min_episode: Some(1),
+            max_episode: Some(500),
+            ..Default::default()
+        };
+        let cap = |name: &str, context: &Context| {
+            Capture::new(name.to_string(), context, &bounded).map(|cap| (cap.season, cap.episode))
+        };
+
+        assert_eq!(cap("Show S01E02.mkv", &Context::default()), Ok((1, 2)));
+        assert_eq!(
+            cap("Show Season 2019 E02.mkv", &Context::default()),
+            Err(CaptureError::OutOfRange {
+                name: "season",
+                value: 2019
+            })
+        );
+        assert_eq!(
+            cap("Show S01E00.mkv", &Context::default()),
+            Err(CaptureError::OutOfRange {
+                name: "episode",
+                value: 0
+            })
+        );
+        assert_eq!(
+            cap(
+                "Show.mkv",
+                &Context {
+                    season: Some(2019),
+                    episode: Some(600)
+                }
+            ),
+            Ok((2019, 600))
+        );
+        assert_eq!(
+            Capture::new(
+                "Show Season 2019 E02.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            )
+            .map(|cap| cap.season),
+            Ok(2019)
+        );
+    }
+
+    #[test]
+    fn scene_name() {
+        let cap = Capture::new(
+            "The.Show.Name.S01E02.1080p.WEB.H264-GROUP.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cap.title, "The Show Name");
+        assert_eq!((cap.season, cap.episode), (1, 2));
+        assert_eq!(cap.tags, vec!["1080p", "WEB", "H264"]);
+        assert_eq!(cap.group, Some("GROUP".to_string()));
+        assert_eq!(cap.ext, Some(".mkv".to_string()));
+        assert_eq!(cap.render(), "The Show Name - S01E02.mkv");
+    }
+
+    #[test]
+    fn episode_titles() {
+        let cap = Capture::new(
+            "Show.Name.S01E02.The.Title.720p.mkv".to_string(),
+            &Context::default(),
+            &CaptureOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cap.title, "Show Name");
+        assert_eq!(cap.episode_title, Some("The Title".to_string()));
+
+        assert_eq!(
+            cap_episode_title("Show S01E02 - The Title"),
+            Some("The Title".to_string())
+        );
+        assert_eq!(
+            cap_episode_title("Show.S01.E02-E03.Two.Parter.1080p.WEB-DL.H264-GRP"),
+            Some("Two Parter".to_string())
+        );
+        assert_eq!(
+            cap_episode_title("Show.S01E02.The.Title.WEB-DL"),
+            Some("The Title".to_string())
+        );
+        assert_eq!(cap_episode_title("Show.S01E02.720p.HDTV"), None);
+        assert_eq!(cap_episode_title("Show S01E02"), None);
+        assert_eq!(cap_episode_title("Show"), None);
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(cap_title("Some.Show.S01E02.720p"), "Some Show");
+        assert_eq!(cap_title("The 100 S01E02"), "The 100");
+        assert_eq!(cap_title("Show_s01e02"), "Show");
+        assert_eq!(cap_title("Show [Season 2] Episode 3"), "Show");
+        assert_eq!(cap_title("Show S2 - 13"), "Show");
+        assert_eq!(cap_title("Anime - 13 [1080p]"), "Anime");
+        assert_eq!(cap_title("S01E02"), "");
+    }
+
+    #[test]
+    fn renders() {
+        let render = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default())
+                .unwrap()
+                .render()
+        };
+
+        assert_eq!(
+            render("some.show.s01e02.720p.mkv"),
+            "some show - S01E02.mkv"
+        );
+        assert_eq!(render("Show S01E02.en.srt"), "Show - S01E02.en.srt");
+        assert_eq!(render("S01E02.mkv"), "S01E02.mkv");
+    }
+
+    #[test]
+    fn plex_names() {
+        let plex = |names: &[&str]| {
+            plan_plex_renames(names.iter().map(|name| name.to_string()).collect(), None)
+                .into_iter()
+                .map(|rename| rename.to)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            plex(&[
+                "Show.Name.2019.S01E02.Episode.Title.1080p.mkv",
+                "Show Name (2019) S01E03.mkv",
+                "Show Name s02e10.en.srt",
+                "Show S01E01-E02 Pilot.mkv",
+                "nothing.mkv",
+            ]),
+            vec![
+                Some("Show Name (2019) - s01e02 - Episode Title.mkv".to_string()),
+                Some("Show Name (2019) - s01e03.mkv".to_string()),
+                Some("Show Name - s02e10.en.srt".to_string()),
+                Some("Show - s01e01-e02 - Pilot.mkv".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn nfo_sidecar() {
+        let options = CaptureOptions {
+            detect_year: true,
+            ..Default::default()
+        };
+        let cap =
+            |name: &str| Capture::new(name.to_string(), &Context::default(), &options).unwrap();
+
+        assert_eq!(
+            cap("Tom & Jerry (1940) S01E02 <Pilot>.mkv").to_nfo(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+             <episodedetails>\n    \
+             <title>&lt;Pilot&gt;</title>\n    \
+             <showtitle>Tom &amp; Jerry (1940)</showtitle>\n    \
+             <season>1</season>\n    \
+             <episode>2</episode>\n    \
+             <year>1940</year>\n\
+             </episodedetails>\n"
+        );
+        assert!(!cap("Show S01E02.mkv").to_nfo().contains("<year>"));
+    }
+
+    #[test]
+    fn lint_names() {
+        let rules = |name: &str, rules: &[&str]| {
+            lint_name(name.to_string(), rules, None)
+                .lints
+                .into_iter()
+                .map(|lint| lint.rule)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(rules("Show S01E02.mkv", LINT_RULES), Vec::<&str>::new());
+        assert_eq!(rules("nothing.mkv", LINT_RULES), vec!["unparsed"]);
+        assert_eq!(rules("Show - 05.mkv", LINT_RULES), vec!["missing_season"]);
+        assert_eq!(
+            rules("Show S1E2.mkv", LINT_RULES),
+            vec!["inconsistent_padding"]
+        );
+        assert_eq!(
+            rules("Show 1080p S01E02.mkv", LINT_RULES),
+            vec!["stray_tags"]
+        );
+        assert_eq!(
+            rules("Café S1E02.mkv", LINT_RULES),
+            vec!["inconsistent_padding", "non_ascii"]
+        );
+        assert_eq!(rules("Café S1E02.mkv", &["non_ascii"]), vec!["non_ascii"]);
+        assert_eq!(
+            lint_name("Show S1E2.mkv".to_string(), LINT_RULES, None).suggestion,
+            Some("Show - S01E02.mkv".to_string())
+        );
+
+        for (name, raw) in &[("Show S1E01.mkv", "S1E01"), ("Show S01E1.mkv", "S01E1")] {
+            let linted = lint_name(name.to_string(), LINT_RULES, None);
+
+            assert_eq!(rules(name, LINT_RULES), vec!["inconsistent_padding"]);
+            assert!(linted.lints[0].message.contains(&format!("`{}`", raw)));
+        }
+    }
+
+    #[test]
+    fn preserve_padding() {
+        let names = || vec!["Show S1E2.mkv".to_string(), "Show S01E03.mkv".to_string()];
+        let renames = |template, preserve_padding| {
+            plan_renames(names(), template, preserve_padding, None)
+                .unwrap()
+                .into_iter()
+                .map(|rename| rename.to.unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            renames(None, false),
+            vec!["Show - S01E02.mkv", "Show - S01E03.mkv"]
+        );
+        assert_eq!(
+            renames(None, true),
+            vec!["Show - S1E2.mkv", "Show - S01E03.mkv"]
+        );
+        assert_eq!(
+            renames(Some("{title} {season:02}x{episode:02}{ext}"), true),
+            vec!["Show 1x2.mkv", "Show 01x03.mkv"]
+        );
+
+        let cap = Capture::new(
+            "Show S1E2.mkv".to_string(),
+            &Context::default(),
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            (cap.season_raw.as_deref(), cap.episode_raw.as_deref()),
+            (Some("1"), Some("2"))
+        );
+        assert_eq!(cap.se_string, "S01E02");
+    }
+
+    #[test]
+    fn preserve_mixed_padding() {
+        let raws = |name: &str| {
+            let cap =
+                Capture::new(name.to_string(), &Context::default(), &Default::default()).unwrap();
+
+            (cap.season_raw, cap.episode_raw)
+        };
+        let some =
+            |season: &str, episode: &str| (Some(season.to_string()), Some(episode.to_string()));
+
+        assert_eq!(raws("Show S1E01.mkv"), some("1", "01"));
+        assert_eq!(raws("Show S01E1.mkv"), some("01", "1"));
+        assert_eq!(raws("Show Season 01 Episode 1.mkv"), some("01", "1"));
+        assert_eq!(
+            plan_renames(
+                vec!["Show S1E01.mkv".to_string(), "Show S01E1.mkv".to_string()],
+                None,
+                true,
+                None
+            )
+            .unwrap()
+            .into_iter()
+            .map(|rename| rename.to.unwrap())
+            .collect::<Vec<_>>(),
+            vec!["Show - S1E01.mkv", "Show - S01E1.mkv"]
+        );
+    }
+
+    #[test]
+    fn rename_conflicts() {
+        let plan = plan_renames(
+            vec![
+                "Show.S01E01.720p.mkv".to_string(),
+                "show s01e01 1080p.mkv".to_string(),
+                "Show S01E02.mkv".to_string(),
+                "nothing.mkv".to_string(),
+            ],
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(plan[0].to, Some("Show - S01E01.mkv".to_string()));
+        assert!(plan[0].conflict && plan[1].conflict);
+        assert_eq!(plan[2].to, Some("Show - S01E02.mkv".to_string()));
+        assert!(!plan[2].conflict);
+        assert_eq!(plan[3].to, None);
+        assert!(!plan[3].conflict);
+        assert!(plan[3].error.is_some());
+    }
+
+    #[test]
+    fn grouped_seasons() {
+        let groups = cap_grouped(
+            vec![
+                "Show S02E01.mkv".to_string(),
+                "Show S01E03.mkv".to_string(),
+                "extras.mkv".to_string(),
+                "Show S01E01.mkv".to_string(),
+            ],
+            &Context::default(),
+            &CaptureOptions::default(),
+            &Metrics::default(),
+        );
+        let episodes = |season| {
+            groups.seasons[&season]
+                .iter()
+                .map(|cap| cap.episode)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(groups.seasons.len(), 2);
+        assert_eq!(episodes(1), vec![1, 3]);
+        assert_eq!(episodes(2), vec![1]);
+        assert_eq!(groups.unknown, vec!["extras.mkv".to_string()]);
+
+        let json = serde_json::to_value(&groups).unwrap();
+        assert_eq!(json["1"][1]["episode"], 3);
+        assert_eq!(json["unknown"][0], "extras.mkv");
+    }
+
+    #[test]
+    fn grouped_seasons_ordered() {
+        let names = |order: &[usize]| -> Vec<String> {
+            order
+                .iter()
+                .map(|season| format!("Show S{:02}E01.mkv", season))
+                .collect()
+        };
+
+        for order in &[[10, 3, 1, 2], [2, 10, 1, 3], [1, 2, 3, 10]] {
+            let groups = cap_grouped(
+                names(order),
+                &Context::default(),
+                &CaptureOptions::default(),
+                &Metrics::default(),
+            );
+            let json = serde_json::to_string(&groups).unwrap();
+            let positions: Vec<_> = [r#""1":"#, r#""2":"#, r#""3":"#, r#""10":"#, r#""unknown":"#]
+                .iter()
+                .map(|key| json.find(key).unwrap())
+                .collect();
+
+            assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(
+                summarize(groups).shows[0]
+                    .seasons
+                    .iter()
+                    .map(|season| season.number)
+                    .collect::<Vec<_>>(),
+                vec![1, 2, 3, 10]
+            );
+        }
+    }
+
+    #[test]
+    fn library_summary() {
+        let groups = cap_grouped(
+            [
+                "Show S01E01.mkv",
+                "Show S01E02.mkv",
+                "show.s01e04.720p.mkv",
+                "Show S02E01-E03.mkv",
+                "Other S02E02.mkv",
+                "extras.mkv",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            &Context::default(),
+            &CaptureOptions::default(),
+            &Metrics::default(),
+        );
+        let summary = summarize(groups);
+
+        assert_eq!(summary.total_shows, 2);
+        assert_eq!(summary.total_episodes, 7);
+        assert_eq!(summary.unknown, vec!["extras.mkv".to_string()]);
+        assert_eq!(summary.shows[0].title, "Other");
+        assert_eq!(
+            summary.shows[0].seasons,
+            vec![SeasonSummary {
+                number: 2,
+                episodes: 1,
+                missing: vec![[1, 1]]
+            }]
+        );
+        assert_eq!(summary.shows[1].title, "Show");
+        assert_eq!(
+            summary.shows[1].seasons,
+            vec![
+                SeasonSummary {
+                    number: 1,
+                    episodes: 3,
+                    missing: vec![[3, 3]]
+                },
+                SeasonSummary {
+                    number: 2,
+                    episodes: 3,
+                    missing: vec![]
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_huge_episode() {
+        let groups = cap_grouped(
+            [
+                "Show S01E02.mkv",
+                "Show S01E03.mkv",
+                "Show S01E99999999.mkv",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            &Context::default(),
+            &CaptureOptions::default(),
+            &Metrics::default(),
+        );
+
+        assert_eq!(
+            summarize(groups).shows[0].seasons,
+            vec![SeasonSummary {
+                number: 1,
+                episodes: 3,
+                missing: vec![[1, 1], [4, 99999998]]
+            }]
+        );
+    }
+
+    #[test]
+    fn title_matching() {
+        let candidates = vec!["Better Call Saul".to_string(), "Breaking Bad".to_string()];
+        let found = match_title("Breakin.Bad.S01E02.720p.mkv", &candidates);
+
+        assert_eq!(found.title, "Breakin Bad");
+        assert_eq!(found.matched, Some("Breaking Bad".to_string()));
+        assert!(found.score.unwrap() > 0.9);
+        assert_eq!(
+            match_title("breaking bad s01e02.mkv", &candidates).score,
+            Some(1.0)
+        );
+        assert_eq!(
+            match_title("Totally Different S01E02.mkv", &candidates),
+            TitleMatch {
+                title: "Totally Different".to_string(),
+                matched: None,
+                score: None
+            }
+        );
+        assert_eq!(match_title("Show S01E02.mkv", &[]).matched, None);
+    }
+
+    #[test]
+    fn title_matching_blank() {
+        let blank = vec!["".to_string(), " ".to_string()];
+
+        assert_eq!(match_title("S01E02.mkv", &blank).matched, None);
+        assert_eq!(match_title("S01E02.mkv", &["Show".to_string()]).score, None);
+        assert_eq!(match_title("Show S01E02.mkv", &blank).score, None);
+    }
+
+    #[test]
+    fn title_matching_lengths() {
+        let candidates = vec!["Show".repeat(5000), "Showw".to_string(), "Sh".to_string()];
+        let matched = match_title("Show S01E02.mkv", &candidates);
+
+        assert_eq!(matched.matched, Some("Showw".to_string()));
+        assert_eq!(matched.score, Some(0.8));
+        assert_eq!(
+            match_title("Show S01E02.mkv", &candidates[2..]).matched,
+            None
+        );
+    }
+
+    #[test]
+    fn quality_dedup() {
+        let caps = [
+            "Show.S01E01.720p.HDTV.mkv",
+            "Show.S01E01.1080p.WEB-DL.mkv",
+            "Show.S01E01.1080p.HDTV.mkv",
+            "Show.S01E02.mkv",
+            "Show.S01E02.480p.mkv",
+            "Show.S01E03.1080p.WEB.mkv",
+            "Show.S01E03.1080p.WEB.x265.mkv",
+        ]
+        .iter()
+        .map(|name| {
+            Capture::new(
+                name.to_string(),
+                &Context::default(),
+                &CaptureOptions::default(),
+            )
+            .unwrap()
+        })
+        .collect();
+        let dedup = dedup_by_quality(caps);
+
+        assert_eq!(
+            dedup.kept,
+            vec![
+                "Show.S01E01.1080p.WEB-DL.mkv".to_string(),
+                "Show.S01E02.480p.mkv".to_string(),
+                "Show.S01E03.1080p.WEB.mkv".to_string(),
+            ]
+        );
+        assert_eq!(
+            dedup.removed,
+            vec![
+                "Show.S01E01.720p.HDTV.mkv".to_string(),
+                "Show.S01E01.1080p.HDTV.mkv".to_string(),
+                "Show.S01E02.mkv".to_string(),
+                "Show.S01E03.1080p.WEB.x265.mkv".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn quality_dedup_titles() {
+        let caps = [
+            "ShowA S01E02 1080p.mkv",
+            "ShowB S01E02 720p.mkv",
+            "S01E02 480p.mkv",
+            "showa.S01E02.720p.mkv",
+        ]
+        .iter()
+        .map(|name| {
+            Capture::new(
+                name.to_string(),
+                &Context::default(),
+                &CaptureOptions::default(),
+            )
+            .unwrap()
+        })
+        .collect();
+        let dedup = dedup_by_quality(caps);
+
+        assert_eq!(
+            dedup.kept,
+            vec![
+                "ShowA S01E02 1080p.mkv".to_string(),
+                "ShowB S01E02 720p.mkv".to_string(),
+                "S01E02 480p.mkv".to_string(),
+            ]
+        );
+        assert_eq!(dedup.removed, vec!["showa.S01E02.720p.mkv".to_string()]);
+    }
+
+    #[test]
+    fn quality_ranks() {
+        let rank = |tags: &[&str]| {
+            quality_rank(&tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>())
+        };
+
+        assert!(rank(&["2160p"]) > rank(&["1080p", "BluRay"]));
+        assert!(rank(&["1080p", "bluray"]) > rank(&["1080p", "WEB-DL"]));
+        assert!(rank(&["720p"]) > rank(&["480p", "BluRay"]));
+        assert_eq!(rank(&["x264"]), (0, 0));
+    }
+
+    #[test]
+    fn rename_templates() {
+        let cap = Capture::new(
+            "Some.Show.S01E02.720p-GRP.en.srt".to_string(),
+            &Context::default(),
+            &CaptureOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            cap.render_template(DEFAULT_RENAME_TEMPLATE),
+            Ok(cap.render())
+        );
+        assert_eq!(
+            cap.render_template("{title} {season}x{episode:02}{ext}"),
+            Ok("Some Show 1x02.en.srt".to_string())
+        );
+        assert_eq!(
+            cap.render_template("{title}/Season {season:02}/{se}"),
+            Ok("Some Show/Season 01/S01E02".to_string())
+        );
+        assert_eq!(
+            cap.render_template("{title} {quality}"),
+            Err(TemplateError::UnknownPlaceholder("quality".to_string()))
+        );
+
+        let plan = plan_renames(
+            vec!["Show S01E02.mkv".to_string()],
+            Some("{title} {season}x{episode:02}{ext}"),
+            false,
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan[0].to, Some("Show 1x02.mkv".to_string()));
+        assert!(plan_renames(
+            vec!["Show S01E02.mkv".to_string()],
+            Some("{nope}"),
+            false,
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn lenient_fallback() {
+        let lenient = CaptureOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let cap = Capture::new(
+            "Some Home Video.mkv".to_string(),
+            &Context::default(),
+            &lenient,
+        )
+        .unwrap();
+
+        assert_eq!((cap.season, cap.episode), (0, 0));
+        assert_eq!(cap.name, "Some Home Video");
+        assert!(!cap.parsed);
+        assert!(cap.episodes.is_empty());
+
+        assert!(
+            Capture::new("Show S01E02.mkv".to_string(), &Context::default(), &lenient)
+                .unwrap()
+                .parsed
+        );
+        assert_eq!(
+            Capture::new("Show E02.mkv".to_string(), &Context::default(), &lenient),
+            Err(CaptureError::NoSeasonRegex)
+        );
+        assert_eq!(
+            Capture::new(
+                "Some Home Video.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            ),
+            Err(CaptureError::NoEpisodeRegex)
+        );
+    }
+
+    #[test]
+    fn capture_episodes() {
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default())
+                .map(|cap| (cap.episode, cap.episodes))
+        };
+
+        assert_eq!(cap("Show S01E01 to E03.mkv"), Ok((1, vec![1, 2, 3])));
+        assert_eq!(cap("Show S01E02.mkv"), Ok((2, vec![2])));
+    }
+
+    #[test]
+    fn inspect_multiple() {
+        let inspection = Inspection::new("Show S01E02E03.mkv".to_string());
+
+        assert_eq!(inspection.filename, "Show S01E02E03");
+        assert_eq!(
+            inspection.seasons,
+            vec![InspectMatch {
+                text: "S01".to_string(),
+                start: 5,
+                end: 8,
+                value: Some(1)
+            }]
+        );
+        assert_eq!(
+            inspection
+                .episodes
+                .iter()
+                .map(|m| (m.start, m.end, m.value))
+                .collect::<Vec<_>>(),
+            vec![(8, 11, Some(2)), (11, 14, Some(3))]
+        );
+    }
+
+    #[test]
+    fn default_ext() {
+        let options = CaptureOptions {
+            default_ext: Some(".mkv".to_string()),
+            ..Default::default()
+        };
+        let cap = |name: &str, options: &CaptureOptions| {
+            Capture::new(name.to_string(), &Context::default(), options)
+                .unwrap()
+                .ext
+        };
+
+        assert_eq!(cap("Show S01E02", &options), Some(".mkv".to_string()));
+        assert_eq!(cap("Show S01E02.mp4", &options), Some(".mp4".to_string()));
+        assert_eq!(cap("Show S01E02", &CaptureOptions::default()), None);
+    }
+
+    #[test]
+    fn basenames() {
+        let basename = CaptureOptions {
+            basename: true,
+            ..Default::default()
+        };
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &basename).map(|cap| {
+                (
+                    cap.title,
+                    cap.season,
+                    cap.season_source,
+                    cap.episode,
+                    cap.ext,
+                )
+            })
+        };
+
+        assert_eq!(
+            cap("Show/Season 1/ep 2.mkv"),
+            Ok((
+                "Show".to_string(),
+                1,
+                Source::Directory,
+                2,
+                Some(".mkv".to_string())
+            ))
+        );
+        assert_eq!(
+            cap("C:\\TV\\Show.v2\\Other S03E04.mkv"),
+            Ok((
+                "Other".to_string(),
+                3,
+                Source::Regex,
+                4,
+                Some(".mkv".to_string())
+            ))
+        );
+        assert_eq!(
+            cap("/media/Show S02/Extras/e05.mkv").map(|cap| (cap.1, cap.3)),
+            Ok((2, 5))
+        );
+        assert_eq!(
+            cap("Show.v2/e05.mkv").map(|cap| cap.4),
+            Err(CaptureError::NoSeasonRegex)
+        );
+
+        assert_eq!(split_basename("a/b\\c.mkv"), (vec!["a", "b"], "c.mkv"));
+        assert_eq!(split_basename("/c.mkv"), (vec![], "c.mkv"));
+        assert_eq!(split_basename("c.mkv"), (vec![], "c.mkv"));
+        assert_eq!(
+            Capture::new(
+                "Show.v2/S01E05.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            )
+            .unwrap()
+            .ext,
+            Some(".mkv".to_string())
+        );
+    }
+
+    #[test]
+    fn fullwidth_digits() {
+        let cap = |name: &str, context: &Context| {
+            Capture::new(name.to_string(), context, &CaptureOptions::default())
+                .map(|cap| (cap.season, cap.episode))
+        };
+        let season = Context {
+            season: Some(1),
+            episode: None,
+        };
+
+        assert_eq!(cap("番組 Ｅ０５.mkv", &season), Ok((1, 5)));
+        assert_eq!(
+            cap("番組　Ｓ０２Ｅ０５.mkv", &Context::default()),
+            Ok((2, 5))
+        );
+        assert_eq!(cap("番組 第５話.mkv", &season), Ok((1, 5)));
+        assert_eq!(
+            cap("番組 第2季 第12集.mp4", &Context::default()),
+            Ok((2, 12))
+        );
+        assert_eq!(
+            Capture::new(
+                "Ｓ０１Ｅ０５.mkv".to_string(),
+                &Context::default(),
+                &Default::default()
+            )
+            .unwrap()
+            .file_path,
+            "Ｓ０１Ｅ０５.mkv"
+        );
+    }
+
+    #[test]
+    fn pathological_names() {
+        let long = format!("{}S01E02{}", "a.".repeat(50_000), ".b".repeat(50_000));
+        let names = [
+            "",
+            ".",
+            "..........",
+            "S.E.",
+            "🎬📺🍿.S01E02.🎞️",
+            "Шоу S01E02 — 日本語.mkv",
+            "Show S99999999999999999999999E01.mkv",
+            "Show E99999999999999999999999.mkv",
+            "\u{202e}Show S01E02\u{fe0f}.mkv",
+            "Show e01-e99999999999999999999999.mkv",
+            long.as_str(),
+        ];
+        let options = CaptureOptions {
+            bare_episode: true,
+            detect_year: true,
+            word_numbers: true,
+            absolute: true,
+            max_len: Some(usize::MAX),
+            ..Default::default()
+        };
+
+        for name in names.iter() {
+            let _ = Capture::new(name.to_string(), &Context::default(), &Default::default());
+            let _ = Capture::new(name.to_string(), &Context::default(), &options);
+            let _ = Inspection::new(name.to_string());
+            let _ = cap_title(name);
+        }
+
+        let cap = |name: &str| {
+            Capture::new(name.to_string(), &Context::default(), &Default::default())
+                .map(|cap| (cap.season, cap.episode))
+        };
+
+        assert_eq!(cap("🎬📺🍿.S01E02.🎞️"), Ok((1, 2)));
+        assert_eq!(
+            cap(&long),
+            Err(CaptureError::TooLong(NameTooLong {
+                len: long.len(),
+                max: crate::utils::DEFAULT_MAX_NAME_LEN
+            }))
+        );
+        assert_eq!(
+            Capture::new(long.clone(), &Context::default(), &options)
+                .map(|cap| (cap.season, cap.episode)),
+            Ok((1, 2))
+        );
+        assert_eq!(cap(".........."), Err(CaptureError::NoEpisodeRegex));
+        assert_eq!(
+            cap("Show S99999999999999999999999E01.mkv"),
+            Err(CaptureError::NoSeasonRegex)
+        );
+        assert_eq!(
+            Inspection::new("Show S99999999999999999999999".to_string()).seasons[0].value,
+            None
+        );
     }
 }
@@ -1,16 +1,207 @@
 //! Utility items
 
-use regex::Regex;
-use rocket::http::Status;
+use once_cell::sync::{Lazy, OnceCell};
+use regex::{Regex, RegexBuilder};
+#[cfg(feature = "server")]
+use rocket::data::{self, Data, FromData, Transform, Transformed};
+#[cfg(feature = "server")]
+use rocket::http::{ContentType, Status};
+#[cfg(feature = "server")]
 use rocket::request::Request;
+#[cfg(feature = "server")]
 use rocket::response::{self, Responder, Response};
-use rocket_contrib::json::Json;
-use serde::Serialize;
-use std::fmt;
+#[cfg(feature = "server")]
+use rocket::Outcome;
+#[cfg(feature = "server")]
+use rocket_contrib::json::{Json, JsonError};
+use serde::ser::{self, SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+#[cfg(feature = "server")]
+use std::io::Cursor;
+use std::str::FromStr;
+use std::{env, fmt, fs};
 
 /// Rgex pattern for converting special characters to spaces
 const TO_SPACE_REGEX: &str = r"(\.|-| )+";
 
+/// Offset basis of the 64-bit FNV-1a hash used by [etag]
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Prime of the 64-bit FNV-1a hash used by [etag]
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Maximum compiled size in bytes of user-supplied regexes, see
+/// [compile_user_regex]
+const USER_REGEX_SIZE_LIMIT: usize = 1 << 18;
+
+/// Maximum lazy DFA cache size in bytes of user-supplied regexes, see
+/// [compile_user_regex]
+const USER_REGEX_DFA_SIZE_LIMIT: usize = 1 << 18;
+
+/// Regex for dashes flanked by whitespace, kept as intentional separators by
+/// [DashMode::Preserve]
+const SPACED_DASH_REGEX: &str = r"\s+[-–—]\s+";
+
+/// Regex for capturing a standalone release year from 1900 to 2099
+const YEAR_REGEX: &str = r"\b(19|20)[0-9]{2}\b";
+
+/// Regex for capturing a year in parentheses or brackets such as `(2019)`, which
+/// is preferred over [YEAR_REGEX] as it's less ambiguous
+const BRACKETED_YEAR_REGEX: &str = r"[(\[]((?:19|20)[0-9]{2})[)\]]";
+
+/// Regex for an episode marker directly before a bare year from [YEAR_REGEX],
+/// meaning it's an episode number such as `Episode 2019` instead of a year
+const YEAR_EPISODE_REGEX: &str = r"(?i)\be(p(isode)?)?[ ._-]*$";
+
+/// Regex for capturing an airdate as year, month and day such as `2021.03.14`
+/// or `2021-03-14`, as used by daily shows
+const AIRDATE_REGEX: &str = r"\b((?:19|20)[0-9]{2})[.\-_ ]([0-9]{2})[.\-_ ]([0-9]{2})\b";
+
+/// Regex for capturing a bracketed CRC32 checksum such as `[A1B2C3D4]`,
+/// requiring exactly 8 hex digits
+const CRC_REGEX: &str = r"\[([0-9A-Fa-f]{8})\]";
+
+/// Regex for the contents of a single `[...]` or `(...)` block, which can't be
+/// nested, used by [parse_brackets]
+const BRACKET_REGEX: &str = r"\[([^\[\]]*)\]|\(([^()]*)\)";
+
+/// Regex for a standalone `sample` or `trailer` token marking junk files inside
+/// of release folders, such as `Show.S01E02.sample.mkv`
+const SAMPLE_REGEX: &str = r"(?i)(?:^|[^a-z0-9])(sample|trailer)(?:[^a-z0-9]|$)";
+
+/// Regex for capturing a disc number such as `CD2`, `Disc 1` or `(Disc 1 of 2)`
+/// alongside an optional total disc count, case insensitive
+const DISC_REGEX: &str =
+    r"(?i)[\[(]?\b(?:disc|disk|cd)[ ._-]*([0-9]{1,2})(?:[ ._-]*of[ ._-]*([0-9]{1,2}))?\b[\])]?";
+
+/// Regex for capturing an audio channel layout such as the `5.1` of `DDP5.1` or
+/// the `2.0` of `AAC2.0`, allowing any codec directly before it
+const AUDIO_CHANNELS_REGEX: &str = r"(?:^|[^0-9])([2567])[._ ]([01])(?:[^0-9]|$)";
+
+/// Regex for capturing the abbreviation of a streaming platform such as the `NF`
+/// of `Show.S01E02.NF.WEB-DL` alongside one separator before it, case sensitive
+/// as these are always uppercase in releases. See [PLATFORMS]
+const PLATFORM_REGEX: &str =
+    r"[ ._\-\[(]?\b(NF|AMZN|DSNP|HMAX|ATVP|HULU|PCOK|PMTP|CR|iP|STAN)\b[\])]?";
+
+/// Regex for Dolby Atmos, which is given alongside any channel layout by
+/// [cap_audio_channels], case insensitive
+const ATMOS_REGEX: &str = r"(?i)\batmos\b";
+
+/// Regex for the watermark prefix of a download site such as the
+/// `www.Site.com - ` of `www.Site.com - Show.S01E02.mkv`, optionally bracketed
+/// like `[www.Site.com]`, used in [DEFAULT_SITE_PREFIXES]
+const SITE_PREFIX_REGEX: &str = r"(?i)^\s*(?:[\[(]\s*www\.[a-z0-9-]+(?:\.[a-z0-9-]+)+\s*[\])]|www\.[a-z0-9-]+(?:\.[a-z0-9-]+)+\s+[-–—])\s*";
+
+/// Every built-in regex of this module, compiled by [ready](crate::ready) to
+/// check that none are broken
+pub(crate) const REGEXES: &[&str] = &[
+    TO_SPACE_REGEX,
+    SPACED_DASH_REGEX,
+    YEAR_REGEX,
+    BRACKETED_YEAR_REGEX,
+    YEAR_EPISODE_REGEX,
+    AIRDATE_REGEX,
+    CRC_REGEX,
+    BRACKET_REGEX,
+    SAMPLE_REGEX,
+    DISC_REGEX,
+    AUDIO_CHANNELS_REGEX,
+    ATMOS_REGEX,
+    PLATFORM_REGEX,
+    SITE_PREFIX_REGEX,
+];
+
+/// Maximum width numbers may be padded to in [render_template], so templates
+/// can't allocate huge strings
+const MAX_PAD_WIDTH: usize = 32;
+
+/// Environment variable which may contain a path to a newline-delimited file of
+/// quality tags, overriding [DEFAULT_QUALITY_TAGS] when set
+pub const QUALITY_TAGS_ENV: &str = "TAGZEN_QUALITY_TAGS";
+
+/// Built-in quality tags for resolutions, sources and codecs, used when
+/// [QUALITY_TAGS_ENV] is unset
+const DEFAULT_QUALITY_TAGS: &[&str] = &[
+    "2160p", "1080p", "720p", "576p", "480p", "4K", "UHD", "HDR", "WEB", "WEB-DL", "WEBRip",
+    "BluRay", "BDRip", "BRRip", "DVDRip", "HDTV", "x264", "x265", "H264", "H265", "HEVC", "AVC",
+    "XviD", "AAC", "AC3", "DTS", "FLAC",
+];
+
+/// Video codec quality tags in uppercase alongside their canonical name, used by
+/// [cap_codecs]
+const VIDEO_CODECS: &[(&str, &str)] = &[
+    ("X264", "H.264"),
+    ("H264", "H.264"),
+    ("AVC", "H.264"),
+    ("X265", "H.265"),
+    ("H265", "H.265"),
+    ("HEVC", "H.265"),
+    ("XVID", "XviD"),
+    ("AV1", "AV1"),
+    ("VP9", "VP9"),
+];
+
+/// Audio codec quality tags in uppercase alongside their canonical name, used by
+/// [cap_codecs]
+const AUDIO_CODECS: &[(&str, &str)] = &[
+    ("AAC", "AAC"),
+    ("AC3", "AC3"),
+    ("EAC3", "E-AC3"),
+    ("DTS", "DTS"),
+    ("TRUEHD", "TrueHD"),
+    ("FLAC", "FLAC"),
+    ("MP3", "MP3"),
+    ("OPUS", "Opus"),
+];
+
+/// Streaming platform abbreviations found by [PLATFORM_REGEX] alongside the name
+/// of the platform, used by [cap_platform]
+const PLATFORMS: &[(&str, &str)] = &[
+    ("NF", "Netflix"),
+    ("AMZN", "Amazon"),
+    ("DSNP", "Disney+"),
+    ("HMAX", "HBO Max"),
+    ("ATVP", "Apple TV+"),
+    ("HULU", "Hulu"),
+    ("PCOK", "Peacock"),
+    ("PMTP", "Paramount+"),
+    ("CR", "Crunchyroll"),
+    ("iP", "BBC iPlayer"),
+    ("STAN", "Stan"),
+];
+
+/// Video container extensions recognised by [cap_container], without the dot
+const VIDEO_CONTAINERS: &[&str] = &[
+    "mkv", "mp4", "m4v", "avi", "mov", "wmv", "webm", "flv", "ts", "m2ts", "mpg", "mpeg", "ogm",
+];
+
+/// Quality tags recognised by [cap_tags], loaded once using [load_quality_tags]
+/// from the path inside of [QUALITY_TAGS_ENV] if any
+pub static QUALITY_TAGS: Lazy<HashSet<String>> = Lazy::new(|| {
+    load_quality_tags(env::var(QUALITY_TAGS_ENV).ok()).expect("Could not read quality tags file")
+});
+
+/// Environment variable which may contain a path to a newline-delimited file of
+/// regexes for site prefixes, overriding [DEFAULT_SITE_PREFIXES] when set
+pub const SITE_PREFIXES_ENV: &str = "TAGZEN_SITE_PREFIXES";
+
+/// Built-in regexes for watermark prefixes of download sites, used when
+/// [SITE_PREFIXES_ENV] is unset
+const DEFAULT_SITE_PREFIXES: &[&str] = &[SITE_PREFIX_REGEX];
+
+/// Site prefixes removed by [strip_site_prefix], set once at startup from the
+/// `site_prefixes` of the server's config. If never set, such as when used
+/// without a server, they're loaded using [load_site_prefixes] from the path
+/// inside of [SITE_PREFIXES_ENV] if any on first use, see [site_prefixes]
+pub static SITE_PREFIXES: OnceCell<Vec<Regex>> = OnceCell::new();
+
+/// Version of the json shape responded with, given as `schema_version` in every
+/// [ResponseModel] and bumped whenever fields are renamed or removed so clients
+/// can tell breaking changes apart
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// A template to respond to requests with, includes status code and message,
 /// along with an optional `body` key that may contain anything (but should
 /// ideally be as standard as possible)
@@ -19,6 +210,23 @@ pub struct ResponseModel<T: Serialize> {
     pub status: u16,
     pub msg: String,
     pub body: Option<T>,
+
+    /// Inputs of the request echoed back so logged responses are self-contained,
+    /// which is only serialized when set using [ResponseModel::echo]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<serde_json::Value>,
+
+    /// Always [SCHEMA_VERSION], kept as a field so it's serialized with the rest
+    pub schema_version: u32,
+
+    /// Omits `null` fields entirely when responding, see [compact_json]
+    #[serde(skip)]
+    pub compact: bool,
+
+    /// Sets an `ETag` header on successful responses, responding with a 304 if
+    /// it matches the `If-None-Match` header of the request, see [etag]
+    #[serde(skip)]
+    pub etag: bool,
 }
 
 impl<T: Serialize> ResponseModel<T> {
@@ -28,6 +236,10 @@ impl<T: Serialize> ResponseModel<T> {
             status,
             msg: format!("{}", msg),
             body: Some(body),
+            input: None,
+            schema_version: SCHEMA_VERSION,
+            compact: false,
+            etag: false,
         }
     }
 
@@ -37,31 +249,857 @@ impl<T: Serialize> ResponseModel<T> {
             status,
             msg: format!("{}", msg),
             body: None,
+            input: None,
+            schema_version: SCHEMA_VERSION,
+            compact: false,
+            etag: false,
         }
     }
+
+    /// Sets if `null` fields should be omitted when responding
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Echoes the `input` of the request back in the response if `echo` is set,
+    /// such as the query args given for debugging mislabeled files
+    pub fn echo(mut self, echo: bool, input: &impl Serialize) -> Self {
+        if echo {
+            self.input = serde_json::to_value(input).ok();
+        }
+        self
+    }
+
+    /// Sets if an `ETag` should be given so unchanged responses can be cached,
+    /// which should only be used for deterministic responses
+    pub fn etag(mut self, etag: bool) -> Self {
+        self.etag = etag;
+        self
+    }
 }
 
+#[cfg(feature = "server")]
 impl<'r, T: Serialize> Responder<'r> for ResponseModel<T> {
     fn respond_to(self, req: &Request) -> response::Result<'r> {
-        Response::build_from(Json(&self).respond_to(req)?)
+        let json = if self.compact {
+            serde_json::to_string(&compact_json(&self))
+        } else {
+            serde_json::to_string(&self)
+        }
+        .map_err(|_| Status::InternalServerError)?;
+        let mut response = Response::build();
+
+        if self.etag && self.status == 200 {
+            let tag = etag(&json);
+            response.raw_header("ETag", tag.clone());
+
+            if etag_matches(req.headers().get_one("If-None-Match"), &tag) {
+                return response.status(Status::NotModified).ok();
+            }
+        }
+
+        response
             .status(Status::from_code(self.status).unwrap())
+            .header(ContentType::JSON)
+            .sized_body(Cursor::new(json))
             .ok()
     }
 }
 
+/// Json request body like rocket's [Json] guard, but failing with a 400 which
+/// is given in the [ResponseModel] shape with the parse error by [bad_request]
+#[cfg(feature = "server")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct JsonBody<T>(pub T);
+
+#[cfg(feature = "server")]
+impl<T> JsonBody<T> {
+    /// Consumes this body, giving the deserialized value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Detail of why a [JsonBody] couldn't be parsed, kept in the request-local
+/// cache for [bad_request] to respond with
+#[cfg(feature = "server")]
+struct InvalidJson(Option<String>);
+
+#[cfg(feature = "server")]
+impl<'a, T: Deserialize<'a>> FromData<'a> for JsonBody<T> {
+    type Error = JsonError<'a>;
+    type Owned = String;
+    type Borrowed = str;
+
+    fn transform(req: &Request, data: Data) -> Transform<data::Outcome<String, JsonError<'a>>> {
+        Json::<T>::transform(req, data)
+    }
+
+    fn from_data(
+        req: &Request,
+        outcome: Transformed<'a, Self>,
+    ) -> data::Outcome<Self, Self::Error> {
+        match Json::<T>::from_data(req, outcome) {
+            Outcome::Success(json) => Outcome::Success(JsonBody(json.into_inner())),
+            Outcome::Failure((_, err)) => {
+                let detail = match &err {
+                    JsonError::Io(err) => err.to_string(),
+                    JsonError::Parse(_, err) => err.to_string(),
+                };
+                req.local_cache(|| InvalidJson(Some(detail)));
+
+                Outcome::Failure((Status::BadRequest, err))
+            }
+            Outcome::Forward(data) => Outcome::Forward(data),
+        }
+    }
+}
+
+/// Catches bad requests to respond in the [ResponseModel] shape, including why
+/// a [JsonBody] couldn't be parsed if that was the cause
+#[cfg(feature = "server")]
+#[catch(400)]
+pub fn bad_request(req: &Request) -> ResponseModel<()> {
+    match &req.local_cache(|| InvalidJson(None)).0 {
+        Some(detail) => ResponseModel::basic(400, format!("Invalid JSON: {}", detail)),
+        None => ResponseModel::basic(400, "Bad request"),
+    }
+}
+
+/// Gives a strong `ETag` for a serialized response `body`, quoted as required
+/// by the header. This is a 64-bit FNV-1a hash so tags stay the same across
+/// restarts and rust versions, letting clients keep caching responses
+pub fn etag(body: &str) -> String {
+    let hash = body.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+
+    format!("\"{:016x}\"", hash)
+}
+
+/// Checks if an `If-None-Match` `header` matches the `ETag` given by [etag],
+/// allowing lists of tags, weak tags and `*`
+pub fn etag_matches(header: Option<&str>, tag: &str) -> bool {
+    header.map_or(false, |header| {
+        header.split(',').map(str::trim).any(|candidate| {
+            candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == tag
+        })
+    })
+}
+
+/// Serializes `value` into json with all `null` object fields removed, at any
+/// depth, for compact responses where absent fields are omitted. Fields keep
+/// their serialized order, such as the order chosen for [Selected]
+pub fn compact_json(value: &impl Serialize) -> serde_json::Value {
+    fn strip(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, strip(value)))
+                .collect(),
+            serde_json::Value::Array(items) => items.into_iter().map(strip).collect(),
+            value => value,
+        }
+    }
+
+    strip(serde_json::to_value(value).expect("Could not serialize value"))
+}
+
+/// Value serialized as only the chosen `fields` of its json object, in the order
+/// they were chosen, or in full if no fields were chosen, see [parse_fields]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Selected<T> {
+    /// Value to serialize, which should serialize into a json object
+    pub value: T,
+
+    /// Fields of [Selected::value] to serialize, with missing fields given as
+    /// `null`
+    pub fields: Option<Vec<String>>,
+}
+
+impl<T> Selected<T> {
+    /// Creates a new [Selected] from a `value` and the chosen `fields`
+    pub fn new(value: T, fields: Option<Vec<String>>) -> Self {
+        Self { value, fields }
+    }
+}
+
+impl<T: Serialize> Serialize for Selected<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = match &self.fields {
+            Some(fields) => fields,
+            None => return self.value.serialize(serializer),
+        };
+        let value = serde_json::to_value(&self.value).map_err(ser::Error::custom)?;
+        let mut map = serializer.serialize_map(Some(fields.len()))?;
+
+        for field in fields {
+            map.serialize_entry(field, value.get(field).unwrap_or(&serde_json::Value::Null))?;
+        }
+
+        map.end()
+    }
+}
+
+/// Parses comma separated `fields` such as `title,season,episode` for use in
+/// [Selected], giving an error listing the `valid` fields if any are unknown
+pub fn parse_fields(fields: &str, valid: &[&str]) -> Result<Vec<String>, String> {
+    fields
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            if valid.contains(&field) {
+                Ok(field.to_string())
+            } else {
+                Err(format!(
+                    "Unknown field `{}`, valid fields are {}",
+                    field,
+                    valid.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Escapes the `text` of an xml element or attribute, replacing the five
+/// special characters with their entities
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Escapes a single csv `field`, quoting it if it contains a comma, quote or
+/// newline and doubling any quotes inside as per RFC 4180
+pub fn csv_field(field: &str) -> String {
+    if field.contains(|c| ",\"\r\n".contains(c)) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Serializes `rows` into csv with a header row of `columns`, taking each value
+/// from the matching field of the row's json object. Missing and `null` values
+/// are empty, arrays are joined by spaces and nested objects are kept as json
+pub fn to_csv<T: Serialize>(rows: impl IntoIterator<Item = T>, columns: &[String]) -> String {
+    let cell = |value: Option<&serde_json::Value>| match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|item| match item {
+                serde_json::Value::String(item) => item.clone(),
+                item => item.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        Some(value) => value.to_string(),
+    };
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .map(|cell| csv_field(cell))
+            .collect::<Vec<_>>()
+            .join(",")
+            + "\r\n"
+    };
+
+    rows.into_iter().fold(line(columns.to_vec()), |csv, row| {
+        let row = serde_json::to_value(row).expect("Could not serialize row");
+        csv + &line(columns.iter().map(|column| cell(row.get(column))).collect())
+    })
+}
+
+/// Serializes `rows` into newline-delimited json, with each row as a json object
+/// on its own line so they may be parsed independently
+pub fn to_ndjson<T: Serialize>(rows: impl IntoIterator<Item = T>) -> String {
+    rows.into_iter().fold(String::new(), |ndjson, row| {
+        ndjson + &serde_json::to_string(&row).expect("Could not serialize row") + "\n"
+    })
+}
+
+/// File extensions used for subtitles, which may have a language sub-extension
+/// before them as used in [cap_sub_language]
+const SUBTITLE_EXTS: &[&str] = &[".srt", ".ass", ".ssa", ".sub", ".idx", ".vtt"];
+
+/// Common ISO 639-1 and ISO 639-2 language codes accepted by [cap_sub_language]
+const LANGUAGE_CODES: &[&str] = &[
+    "ar", "bg", "cs", "da", "de", "el", "en", "es", "et", "fa", "fi", "fr", "he", "hi", "hr", "hu",
+    "id", "is", "it", "ja", "ko", "lt", "lv", "ms", "nb", "nl", "no", "pl", "pt", "ro", "ru", "sk",
+    "sl", "sr", "sv", "th", "tr", "uk", "vi", "zh", "ara", "chi", "cze", "dan", "deu", "dut",
+    "eng", "fin", "fra", "fre", "ger", "gre", "heb", "hin", "hun", "ita", "jpn", "kor", "nld",
+    "nor", "pol", "por", "rum", "rus", "spa", "swe", "tha", "tur", "ukr", "vie", "zho",
+];
+
+/// How dashes are handled by [format_name_with], as filenames mix scene-style
+/// hyphens with intentional `Artist - Title` separators
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DashMode {
+    /// Treats all hyphens as word separators like dots, which is the default
+    Collapse,
+
+    /// Keeps dashes flanked by spaces such as ` - ` or ` — ` as separators,
+    /// collapsing everything between them as usual
+    Preserve,
+}
+
+impl Default for DashMode {
+    fn default() -> Self {
+        DashMode::Collapse
+    }
+}
+
+/// Letter case and word separation to apply to a rendered name, as used in the
+/// `case` query arg of rendering endpoints
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Case {
+    /// Leaves the rendered name as-is, which is the default
+    Original,
+
+    /// Capitalises the first letter of each word, e.g. `Show Name S01e02`
+    Title,
+
+    /// Lowercase words joined by hyphens, e.g. `show-name-s01e02`
+    Kebab,
+
+    /// Lowercase words joined by underscores, e.g. `show_name_s01e02`
+    Snake,
+}
+
+impl Default for Case {
+    fn default() -> Self {
+        Case::Original
+    }
+}
+
+impl Case {
+    /// Applies this case to a `render`, with [Case::Kebab] and [Case::Snake]
+    /// also removing non-alphanumeric characters for filesystem friendliness
+    pub fn apply(self, render: &str) -> String {
+        let words = || {
+            render.split_whitespace().filter_map(|word| {
+                let word: String = word.chars().filter(|c| c.is_alphanumeric()).collect();
+
+                if word.is_empty() {
+                    None
+                } else {
+                    Some(word.to_lowercase())
+                }
+            })
+        };
+
+        match self {
+            Case::Original => render.to_string(),
+            Case::Title => render
+                .split(' ')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first
+                            .to_uppercase()
+                            .chain(chars.flat_map(char::to_lowercase))
+                            .collect(),
+                        None => String::new(),
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(" "),
+            Case::Kebab => words().collect::<Vec<_>>().join("-"),
+            Case::Snake => words().collect::<Vec<_>>().join("_"),
+        }
+    }
+}
+
+/// Error for an unknown [Case] given in a query arg
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnknownCase(pub String);
+
+impl fmt::Display for UnknownCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unknown case `{}`, expected one of kebab, snake, title or original",
+            self.0
+        )
+    }
+}
+
+impl FromStr for Case {
+    type Err = UnknownCase;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "original" => Ok(Case::Original),
+            "title" => Ok(Case::Title),
+            "kebab" => Ok(Case::Kebab),
+            "snake" => Ok(Case::Snake),
+            _ => Err(UnknownCase(s.to_string())),
+        }
+    }
+}
+
+/// Parses an optional `case` query arg, defaulting to [Case::Original]
+pub fn parse_case(case: Option<String>) -> Result<Case, UnknownCase> {
+    match case {
+        Some(case) => case.parse(),
+        None => Ok(Case::default()),
+    }
+}
+
+/// Single page of a larger list of items, made from the optional `offset` and
+/// `limit` query args of an endpoint
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Page<T: Serialize> {
+    /// Total number of items before paginating
+    pub total: usize,
+
+    /// Number of items skipped before this page
+    pub offset: usize,
+
+    /// Maximum number of items in this page, defaulting to [Page::total]
+    pub limit: usize,
+
+    /// Items contained in this page
+    pub items: Vec<T>,
+}
+
+impl<T: Serialize> Page<T> {
+    /// Creates a new [Page] from all `items`, returning everything if both
+    /// `offset` and `limit` aren't given
+    pub fn new(items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> Self {
+        let total = items.len();
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(total);
+
+        Self {
+            total,
+            offset,
+            limit,
+            items: items.into_iter().skip(offset).take(limit).collect(),
+        }
+    }
+}
+
+/// Value of a placeholder which may be substituted into a template using
+/// [render_template], with missing values rendering as empty
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplateValue {
+    /// Textual value such as a name
+    Text(Option<String>),
+
+    /// Numeric value which may be zero-padded, e.g. `{episode:02}`
+    Number(Option<usize>),
+
+    /// Number kept exactly as written such as the `1` of `S1E2`, accepting but
+    /// ignoring any padding format so templates work either way
+    Verbatim(Option<String>),
+}
+
+/// Error enum encapsulating errors that may arrise whilst rendering a template
+/// using [render_template]
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplateError {
+    /// Placeholder inside of the template isn't a known field
+    UnknownPlaceholder(String),
+
+    /// Format given after the colon of a placeholder is invalid, e.g. padding a
+    /// text placeholder or an unknown format
+    InvalidFormat(String),
+
+    /// Opening brace of a placeholder was never closed
+    Unclosed,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name) => {
+                write!(f, "Unknown placeholder `{{{}}}` in template", name)
+            }
+            TemplateError::InvalidFormat(placeholder) => {
+                write!(
+                    f,
+                    "Invalid format for placeholder `{{{}}}` in template",
+                    placeholder
+                )
+            }
+            TemplateError::Unclosed => write!(f, "Unclosed placeholder brace in template"),
+        }
+    }
+}
+
+/// Renders a `template` such as `{artist} - {track:02} - {name}{ext}` by
+/// substituting placeholders with values from `lookup`, which should return
+/// [None] for unknown placeholders. Numbers may be zero-padded using a format
+/// of `0` followed by the width, e.g. `{track:02}`
+pub fn render_template(
+    template: &str,
+    lookup: impl Fn(&str) -> Option<TemplateValue>,
+) -> Result<String, TemplateError> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+
+        let end = rest[start..].find('}').ok_or(TemplateError::Unclosed)? + start;
+        let placeholder = &rest[start + 1..end];
+        let mut split = placeholder.splitn(2, ':');
+        let name = split.next().unwrap_or_default();
+        let format = split.next();
+
+        let value =
+            lookup(name).ok_or_else(|| TemplateError::UnknownPlaceholder(name.to_string()))?;
+        output.push_str(&match (value, format) {
+            (TemplateValue::Text(text), None) | (TemplateValue::Verbatim(text), None) => {
+                text.unwrap_or_default()
+            }
+            (TemplateValue::Number(num), None) => num.map(|n| n.to_string()).unwrap_or_default(),
+            (value, Some(format))
+                if format.starts_with('0')
+                    && format[1..]
+                        .parse::<usize>()
+                        .map_or(false, |width| width <= MAX_PAD_WIDTH) =>
+            {
+                let width = format[1..].parse().unwrap();
+                match value {
+                    TemplateValue::Number(num) => num.map(|n| pad_num(n, width)),
+                    TemplateValue::Verbatim(text) => text,
+                    TemplateValue::Text(_) => {
+                        return Err(TemplateError::InvalidFormat(placeholder.to_string()))
+                    }
+                }
+                .unwrap_or_default()
+            }
+            _ => return Err(TemplateError::InvalidFormat(placeholder.to_string())),
+        });
+
+        rest = &rest[end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Zero-pads a `num` to at least the given `width`, e.g. `2` to `02`
+pub fn pad_num(num: usize, width: usize) -> String {
+    format!("{:0width$}", num, width = width)
+}
+
+/// Default maximum length in characters of names given to [Capture::new] and
+/// [SingleSong::new], see [check_name_len]
+///
+/// [Capture::new]: crate::tv::Capture::new
+/// [SingleSong::new]: crate::music::SingleSong::new
+pub const DEFAULT_MAX_NAME_LEN: usize = 1024;
+
+/// Error from a name longer than the allowed maximum, see [check_name_len]
+#[derive(Debug, PartialEq, Clone)]
+pub struct NameTooLong {
+    /// Length of the name in characters
+    pub len: usize,
+
+    /// Maximum allowed length in characters
+    pub max: usize,
+}
+
+impl fmt::Display for NameTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Name is {} characters long, over the maximum of {}",
+            self.len, self.max
+        )
+    }
+}
+
+/// Checks that a `name` is at most `max` characters long or the
+/// [DEFAULT_MAX_NAME_LEN] if not given, so no regex is ran over huge inputs
+pub fn check_name_len(name: &str, max: Option<usize>) -> Result<(), NameTooLong> {
+    let max = max.unwrap_or(DEFAULT_MAX_NAME_LEN);
+
+    if name.len() <= max {
+        return Ok(());
+    }
+
+    match name.chars().count() {
+        len if len > max => Err(NameTooLong { len, max }),
+        _ => Ok(()),
+    }
+}
+
+/// Cleans up a raw `file_path` given by a client by removing any ASCII control
+/// characters such as a trailing `\r\n` and trimming surrounding whitespace
+pub fn clean_input(file_path: impl AsRef<str>) -> String {
+    file_path
+        .as_ref()
+        .chars()
+        .filter(|c| !c.is_ascii_control())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Normalizes fullwidth forms commonly found in East Asian filenames such as
+/// `Ｅ０５` into their ASCII equivalents, including the ideographic space
+pub fn normalize_fullwidth(filename: impl AsRef<str>) -> String {
+    filename
+        .as_ref()
+        .chars()
+        .map(|c| match c {
+            '\u{ff01}'..='\u{ff5e}' => std::char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
 /// Attempts to capture `filename` used and `ext` used from a given `file_path`
-/// by splitting
+/// by splitting on the last dot. Names without a dot, dotfiles such as
+/// `.hidden` and names ending in a dot have no extension, nor do names ending in
+/// a decimal number such as the `5.1` audio channels of `Show.S01E02.DD5.1`
 pub fn cap_filename_ext(file_path: impl AsRef<str>) -> (String, Option<String>) {
-    let split: Vec<&str> = file_path.as_ref().split('.').collect();
+    let file_path = file_path.as_ref();
+    let is_decimal = |ind: usize| {
+        file_path[..ind].ends_with(|c: char| c.is_ascii_digit())
+            && file_path[ind + 1..].chars().all(|c| c.is_ascii_digit())
+    };
 
-    (
-        split[..split.len() - 1].join(".").to_string(),
-        if split.len() > 1 {
-            Some(format!(".{}", split.last().unwrap()))
-        } else {
-            None
-        },
-    )
+    match file_path.rfind('.') {
+        Some(ind) if ind > 0 && ind < file_path.len() - 1 && !is_decimal(ind) => (
+            file_path[..ind].to_string(),
+            Some(file_path[ind..].to_string()),
+        ),
+        _ => (file_path.to_string(), None),
+    }
+}
+
+/// Extensions which may be stacked before the last one alongside the
+/// [LANGUAGE_CODES], as captured by [cap_filename_exts]
+const STACKED_EXTS: &[&str] = &["tar", "forced", "sdh"];
+
+/// Attempts to capture `filename` used and every stacked extension used from a
+/// given `file_path`, such as `["en", "srt"]` from `Show.en.srt`, without their
+/// leading dots. The last extension is found by [cap_filename_ext], with segments
+/// before it only counted if they're [STACKED_EXTS] or [LANGUAGE_CODES]
+pub fn cap_filename_exts(file_path: impl AsRef<str>) -> (String, Vec<String>) {
+    let (mut filename, ext) = cap_filename_ext(file_path);
+    let mut exts = match ext {
+        Some(ext) => vec![ext[1..].to_string()],
+        None => return (filename, vec![]),
+    };
+
+    while let Some(ind) = filename.rfind('.') {
+        let segment = filename[ind + 1..].to_lowercase();
+        if !STACKED_EXTS.contains(&segment.as_str()) && !LANGUAGE_CODES.contains(&segment.as_str())
+        {
+            break;
+        }
+
+        exts.insert(0, filename[ind + 1..].to_string());
+        filename.truncate(ind);
+    }
+
+    (filename, exts)
+}
+
+/// Gives the `ext` found by [cap_filename_ext] if any, otherwise falling back to
+/// a `default` extension with a leading dot added if missing. Found extensions
+/// always take precedence, and empty defaults are ignored
+pub fn ext_or_default(ext: Option<String>, default: Option<&str>) -> Option<String> {
+    ext.or_else(|| match default.map(str::trim) {
+        Some(default) if default.starts_with('.') && default.len() > 1 => Some(default.to_string()),
+        Some(default) if !default.is_empty() && !default.starts_with('.') => {
+            Some(format!(".{}", default))
+        }
+        _ => None,
+    })
+}
+
+/// Extension of a name, as given by the [ext] path
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Extension {
+    /// Extension including the leading dot, see [cap_filename_ext]
+    pub ext: Option<String>,
+}
+
+/// Gives help for how to use the [ext] path
+#[cfg(feature = "server")]
+#[get("/ext")]
+pub fn ext_help() -> &'static str {
+    "ENDPOINT GET /ext?<name>\n\n\nAbout\n    Gives just the extension of the given `name` as `{\"ext\": \".mkv\"}`, using\n    the same rules as all other endpoints. Only the last extension is given for\n    names with many dots, and dotfiles such as `.hidden` or names without a dot\n    give a `null` extension."
+}
+
+/// Extension of a single name, see [cap_filename_ext]
+#[cfg(feature = "server")]
+#[get("/ext?<name>")]
+pub fn ext(name: String) -> Json<Extension> {
+    Json(Extension {
+        ext: cap_filename_ext(name).1,
+    })
+}
+
+/// Attempts to capture a subtitle language sub-extension such as the `en` in
+/// `Show.en.srt` from a `filename` given by [cap_filename_ext], returning the
+/// filename without the language alongside the language found. This is only
+/// done for [SUBTITLE_EXTS] and languages must be one of [LANGUAGE_CODES], with
+/// an optional region like `pt-BR`
+pub fn cap_sub_language(filename: String, ext: Option<&str>) -> (String, Option<String>) {
+    let is_subtitle = match ext {
+        Some(ext) => SUBTITLE_EXTS.contains(&ext.to_lowercase().as_str()),
+        None => false,
+    };
+    let split = match filename.rfind('.') {
+        Some(ind) if is_subtitle => ind,
+        _ => return (filename, None),
+    };
+
+    let language = &filename[split + 1..];
+    let mut parts = language.splitn(2, '-');
+    let code = parts.next().unwrap_or_default().to_lowercase();
+    let region_valid = match parts.next() {
+        Some(region) => region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic()),
+        None => true,
+    };
+
+    if region_valid && LANGUAGE_CODES.contains(&code.as_str()) {
+        let language = language.to_string();
+        (filename[..split].to_string(), Some(language))
+    } else {
+        (filename, None)
+    }
+}
+
+/// Compiles a user-supplied regex `pattern` with strict size limits, so that
+/// pathological patterns are refused rather than using large amounts of memory.
+/// Matching itself is always linear time, so backtracking isn't a concern
+pub fn compile_user_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(USER_REGEX_SIZE_LIMIT)
+        .dfa_size_limit(USER_REGEX_DFA_SIZE_LIMIT)
+        .build()
+}
+
+/// Attempts to capture a release year from a `filename` between 1900 and 2099,
+/// preferring years in parentheses such as `(2019)` over the first bare year.
+/// Bare years must be a whole word and not follow an episode marker, so
+/// `E2019` or `Episode 2019` aren't years
+pub fn cap_year(filename: impl AsRef<str>) -> Option<usize> {
+    let filename = filename.as_ref();
+
+    if let Some(captures) = Regex::new(BRACKETED_YEAR_REGEX)
+        .expect("Could not make bracketed year regex")
+        .captures(filename)
+    {
+        return captures[1].parse().ok();
+    }
+
+    let episode = Regex::new(YEAR_EPISODE_REGEX).expect("Could not make year episode regex");
+    Regex::new(YEAR_REGEX)
+        .expect("Could not make year regex")
+        .find_iter(filename)
+        .find(|year| !episode.is_match(&filename[..year.start()]))
+        .and_then(|year| year.as_str().parse().ok())
+}
+
+/// Airdate of a daily show episode, found using [cap_airdate]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Airdate {
+    /// Year aired, from 1900 to 2099
+    pub year: usize,
+
+    /// Month aired, from 1 to 12
+    pub month: usize,
+
+    /// Day of the month aired, from 1 to 31
+    pub day: usize,
+
+    /// Date in ISO 8601 format such as `2021-03-14`
+    pub iso: String,
+}
+
+impl Airdate {
+    /// Creates a new airdate if `month` and `day` are a valid date of `year`
+    pub fn new(year: usize, month: usize, day: usize) -> Option<Self> {
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return None,
+        };
+
+        if day == 0 || day > days {
+            return None;
+        }
+
+        Some(Self {
+            year,
+            month,
+            day,
+            iso: format!(
+                "{}-{}-{}",
+                pad_num(year, 4),
+                pad_num(month, 2),
+                pad_num(day, 2)
+            ),
+        })
+    }
+}
+
+/// Attempts to capture the first valid airdate from a `filename` written as
+/// year, month then day, see [AIRDATE_REGEX]
+pub fn cap_airdate(filename: impl AsRef<str>) -> Option<Airdate> {
+    Regex::new(AIRDATE_REGEX)
+        .expect("Could not make airdate regex")
+        .captures_iter(filename.as_ref())
+        .find_map(|captures| {
+            Airdate::new(
+                captures[1].parse().ok()?,
+                captures[2].parse().ok()?,
+                captures[3].parse().ok()?,
+            )
+        })
+}
+
+/// Levenshtein edit distance between `a` and `b`, being the number of single
+/// character insertions, deletions or substitutions to turn one into the other
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + if a == *b { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Similarity of `a` and `b` from `0.0` to `1.0` using [levenshtein] relative
+/// to the longest of the two, compared case insensitively
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    let len = a.chars().count().max(b.chars().count());
+
+    if len == 0 {
+        1.0
+    } else {
+        1.0 - levenshtein(&a, &b) as f64 / len as f64
+    }
 }
 
 /// Formats name by elimintating non alphanumeric characters with the use of
@@ -72,3 +1110,945 @@ pub fn format_name(name: impl AsRef<str>) -> String {
         .replace_all(name.as_ref(), " ")
         .as_ref().trim().to_string()
 }
+
+/// Formats name like [format_name] but with dashes handled according to `mode`
+pub fn format_name_with(name: impl AsRef<str>, mode: DashMode) -> String {
+    let name = name.as_ref();
+
+    match mode {
+        DashMode::Collapse => format_name(name),
+        DashMode::Preserve => {
+            let dash_regex = Regex::new(SPACED_DASH_REGEX).expect("Could not make dash regex");
+            let mut formatted = String::new();
+            let mut last = 0;
+
+            for dash in dash_regex.find_iter(name) {
+                let part = format_name(&name[last..dash.start()]);
+
+                if !part.is_empty() {
+                    formatted.push_str(&part);
+                    formatted.push(' ');
+                    formatted.push_str(dash.as_str().trim());
+                    formatted.push(' ');
+                }
+
+                last = dash.end();
+            }
+
+            let part = format_name(&name[last..]);
+
+            if part.is_empty() {
+                formatted
+                    .trim_end_matches(|c| " -–—".contains(c))
+                    .to_string()
+            } else {
+                formatted + &part
+            }
+        }
+    }
+}
+
+/// Loads a set of uppercased quality tags from a newline-delimited file at
+/// `path`, falling back to [DEFAULT_QUALITY_TAGS] if no path is given
+pub fn load_quality_tags(path: Option<impl AsRef<str>>) -> std::io::Result<HashSet<String>> {
+    match path {
+        Some(path) => Ok(fs::read_to_string(path.as_ref())?
+            .lines()
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_uppercase)
+            .collect()),
+        None => Ok(DEFAULT_QUALITY_TAGS
+            .iter()
+            .map(|tag| tag.to_uppercase())
+            .collect()),
+    }
+}
+
+/// Reads the uncompiled regexes of site prefixes from a newline-delimited file
+/// at `path`, falling back to [DEFAULT_SITE_PREFIXES] if no path is given
+pub fn read_site_prefixes(path: Option<impl AsRef<str>>) -> std::io::Result<Vec<String>> {
+    match path {
+        Some(path) => Ok(fs::read_to_string(path.as_ref())?
+            .lines()
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(String::from)
+            .collect()),
+        None => Ok(DEFAULT_SITE_PREFIXES
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect()),
+    }
+}
+
+/// Compiles the regexes of site prefixes such as from [read_site_prefixes], each
+/// only matching at the start of a filename
+pub fn compile_site_prefixes(patterns: &[String]) -> Result<Vec<Regex>, regex::Error> {
+    patterns
+        .iter()
+        .map(|pattern| Regex::new(&format!("^(?:{})", pattern)))
+        .collect()
+}
+
+/// Loads the regexes of site prefixes from a newline-delimited file at `path`,
+/// falling back to [DEFAULT_SITE_PREFIXES] if no path is given. Each regex is
+/// only matched at the start of a filename, and invalid regexes give an error
+pub fn load_site_prefixes(path: Option<impl AsRef<str>>) -> std::io::Result<Vec<Regex>> {
+    compile_site_prefixes(&read_site_prefixes(path)?)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+/// Gives the [SITE_PREFIXES], loading them from [SITE_PREFIXES_ENV] if they
+/// were never set
+pub fn site_prefixes() -> &'static [Regex] {
+    SITE_PREFIXES.get_or_init(|| {
+        load_site_prefixes(env::var(SITE_PREFIXES_ENV).ok())
+            .expect("Could not read site prefixes file")
+    })
+}
+
+/// Removes any watermark prefix of a download site from the start of a
+/// `filename` such as the `www.Site.com - ` of `www.Site.com - Show.S01E02`
+/// using the [SITE_PREFIXES], so it doesn't pollute the title
+pub fn strip_site_prefix(filename: String) -> String {
+    match site_prefixes()
+        .iter()
+        .find_map(|regex| regex.find(&filename).filter(|found| found.end() > 0))
+    {
+        Some(found) => filename[found.end()..].to_string(),
+        None => filename,
+    }
+}
+
+/// Captures all known [QUALITY_TAGS] from a `filename` in order of appearance,
+/// keeping their original casing. Hyphenated tokens such as `H264-GROUP` are
+/// also checked piece by piece if the whole token isn't a tag
+pub fn cap_tags(filename: impl AsRef<str>) -> Vec<String> {
+    let mut tags = Vec::new();
+
+    for token in filename
+        .as_ref()
+        .split(|c: char| c == '.' || c == ' ' || c == '_' || "[](){}".contains(c))
+    {
+        if QUALITY_TAGS.contains(&token.to_uppercase()) {
+            tags.push(token.to_string());
+        } else {
+            tags.extend(
+                token
+                    .split('-')
+                    .filter(|part| QUALITY_TAGS.contains(&part.to_uppercase()))
+                    .map(str::to_string),
+            );
+        }
+    }
+
+    tags
+}
+
+/// Finds the canonical video and audio codec in that order from quality `tags`
+/// given by [cap_tags], such as `H.265` for `x265` or `HEVC`, with the first of
+/// each winning. See [VIDEO_CODECS] and [AUDIO_CODECS]
+pub fn cap_codecs(tags: &[String]) -> (Option<String>, Option<String>) {
+    let find = |codecs: &[(&str, &str)]| {
+        tags.iter().find_map(|tag| {
+            let tag = tag.to_uppercase();
+            codecs
+                .iter()
+                .find(|(known, _)| *known == tag)
+                .map(|(_, name)| name.to_string())
+        })
+    };
+
+    (find(VIDEO_CODECS), find(AUDIO_CODECS))
+}
+
+/// Finds the audio channel layout of a `filename` such as `5.1` for `DDP5.1`,
+/// suffixed with `Atmos` if present like `7.1 Atmos`, or just `Atmos` if no
+/// layout was found. See [AUDIO_CHANNELS_REGEX] and [ATMOS_REGEX]
+pub fn cap_audio_channels(filename: &str) -> Option<String> {
+    let layout = Regex::new(AUDIO_CHANNELS_REGEX)
+        .expect("Could not make audio channels regex")
+        .captures(filename)
+        .map(|captures| format!("{}.{}", &captures[1], &captures[2]));
+    let atmos = Regex::new(ATMOS_REGEX)
+        .expect("Could not make atmos regex")
+        .is_match(filename);
+
+    match (layout, atmos) {
+        (Some(layout), true) => Some(format!("{} Atmos", layout)),
+        (Some(layout), false) => Some(layout),
+        (None, true) => Some("Atmos".to_string()),
+        (None, false) => None,
+    }
+}
+
+/// Gives the video container of an `ext` from [cap_filename_ext] in lowercase
+/// without the dot, such as `mkv`, if it's one of [VIDEO_CONTAINERS]
+pub fn cap_container(ext: Option<&str>) -> Option<String> {
+    let container = ext?.trim_start_matches('.').to_lowercase();
+
+    if VIDEO_CONTAINERS.contains(&container.as_str()) {
+        Some(container)
+    } else {
+        None
+    }
+}
+
+/// Captures a scene release group suffixed onto the last quality tag of a
+/// `filename` such as the `GROUP` in `Show.S01E02.1080p.H264-GROUP`, requiring
+/// the tag so names like `Show S01E01-E03` don't give a group
+pub fn cap_group(filename: impl AsRef<str>) -> Option<String> {
+    let is_tag = |token: &str| QUALITY_TAGS.contains(&token.to_uppercase());
+    let token = filename
+        .as_ref()
+        .rsplit(|c: char| c == '.' || c == ' ' || c == '_' || "[](){}".contains(c))
+        .next()?;
+
+    if is_tag(token) {
+        return None;
+    }
+
+    let split = token.rfind('-')?;
+    let (before, group) = (&token[..split], &token[split + 1..]);
+    let last_before = before.rsplit('-').next().unwrap_or(before);
+
+    if !group.is_empty() && !is_tag(group) && (is_tag(before) || is_tag(last_before)) {
+        Some(group.to_string())
+    } else {
+        None
+    }
+}
+
+/// Attempts to capture the streaming platform a `filename` was ripped from such
+/// as `Netflix` for `Show.S01E02.NF.WEB-DL`, returning the filename without the
+/// abbreviation alongside the name of the platform, see [PLATFORMS]
+pub fn cap_platform(filename: String) -> (String, Option<String>) {
+    let found = Regex::new(PLATFORM_REGEX)
+        .expect("Could not make platform regex")
+        .captures(&filename)
+        .and_then(|captures| {
+            let platform = PLATFORMS
+                .iter()
+                .find(|(abbreviation, _)| *abbreviation == &captures[1])?;
+            Some((captures.get(0)?.range(), platform.1.to_string()))
+        });
+
+    match found {
+        Some((range, platform)) => (
+            format!("{}{}", &filename[..range.start], &filename[range.end..]),
+            Some(platform),
+        ),
+        None => (filename, None),
+    }
+}
+
+/// Attempts to capture a disc number and optional total disc count from a
+/// `filename`, returning the filename without them alongside the numbers found.
+/// Used for both music discs and movies split into parts such as `Movie.CD1`
+pub fn cap_disc(filename: String) -> (String, Option<usize>, Option<usize>) {
+    let captures = match Regex::new(DISC_REGEX)
+        .expect("Could not make disc regex")
+        .captures(&filename)
+    {
+        Some(captures) => captures,
+        None => return (filename, None, None),
+    };
+    let found = captures.get(0).unwrap();
+    let rest = format!(
+        "{} {}",
+        filename[..found.start()].trim_end(),
+        filename[found.end()..].trim_start()
+    );
+
+    (
+        rest.trim_matches(|c| " -_.".contains(c)).to_string(),
+        captures[1].parse().ok(),
+        captures
+            .get(2)
+            .and_then(|total| total.as_str().parse().ok()),
+    )
+}
+
+/// Checks if a `file_path` is a sample or trailer such as `Show.S01E02.sample.mkv`
+/// or `Sample/show.mkv` by name alone, see [SAMPLE_REGEX]
+pub fn is_sample(file_path: &str) -> bool {
+    Regex::new(SAMPLE_REGEX)
+        .expect("Could not make sample regex")
+        .is_match(file_path)
+}
+
+/// Gives the trimmed contents of every `[...]` and `(...)` block of a `filename`
+/// in order of appearance, such as `Group`, `BD 1080p` and `A1B2C3D4` from
+/// `[Group] Show - 05 (BD 1080p) [A1B2C3D4]`. Empty blocks are skipped
+pub fn parse_brackets(filename: &str) -> Vec<String> {
+    Regex::new(BRACKET_REGEX)
+        .expect("Could not make bracket regex")
+        .captures_iter(filename)
+        .filter_map(|captures| captures.get(1).or_else(|| captures.get(2)))
+        .map(|block| block.as_str().trim().to_string())
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+/// Captures a release group from a `[...]` block at the very start of a
+/// `filename` as used by anime releases such as `[Group] Show - 05`, returning
+/// the filename without the block alongside the group. Blocks containing a
+/// checksum, year or quality tag are metadata rather than a group so are skipped
+pub fn cap_leading_group(filename: &str) -> Option<(String, String)> {
+    let rest = filename.trim_start().strip_prefix('[')?;
+    let end = rest.find(']')?;
+    let group = rest[..end].trim();
+    let is_metadata = Regex::new(&format!("^{}$", CRC_REGEX))
+        .expect("Could not make crc regex")
+        .is_match(&format!("[{}]", group))
+        || Regex::new(YEAR_REGEX)
+            .expect("Could not make year regex")
+            .is_match(group)
+        || !cap_tags(group).is_empty();
+
+    if group.is_empty() || group.contains('[') || is_metadata {
+        None
+    } else {
+        Some((rest[end + 1..].trim().to_string(), group.to_string()))
+    }
+}
+
+/// Captures a bracketed CRC32 checksum commonly suffixed onto anime releases
+/// such as the `A1B2C3D4` in `Show - 05 [A1B2C3D4]`, returning the filename
+/// without the checksum alongside the checksum found, see [CRC_REGEX]
+pub fn cap_crc(filename: String) -> (String, Option<String>) {
+    let found = Regex::new(CRC_REGEX)
+        .expect("Could not make crc regex")
+        .captures(&filename)
+        .map(|captures| (captures.get(0).unwrap().range(), captures[1].to_string()));
+
+    match found {
+        Some((range, crc)) => {
+            let removed = format!("{}{}", &filename[..range.start], &filename[range.end..]);
+            (removed.trim().to_string(), Some(crc))
+        }
+        None => (filename, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_slice() {
+        let page = Page::new(vec![1, 2, 3, 4, 5], Some(1), Some(2));
+
+        assert_eq!(page.total, 5);
+        assert_eq!(page.offset, 1);
+        assert_eq!(page.limit, 2);
+        assert_eq!(page.items, vec![2, 3]);
+    }
+
+    #[test]
+    fn page_default() {
+        let page = Page::new(vec![1, 2, 3], None, None);
+
+        assert_eq!(page.offset, 0);
+        assert_eq!(page.limit, 3);
+        assert_eq!(page.items, vec![1, 2, 3]);
+        assert!(Page::new(vec![1, 2, 3], Some(5), None).items.is_empty());
+    }
+
+    #[test]
+    fn sub_language_simple() {
+        assert_eq!(
+            cap_sub_language("Show.S01E02.en".to_string(), Some(".srt")),
+            ("Show.S01E02".to_string(), Some("en".to_string()))
+        );
+        assert_eq!(
+            cap_sub_language("movie.pt-BR".to_string(), Some(".srt")),
+            ("movie".to_string(), Some("pt-BR".to_string()))
+        );
+    }
+
+    #[test]
+    fn sub_language_none() {
+        assert_eq!(
+            cap_sub_language("Show.en".to_string(), Some(".mkv")),
+            ("Show.en".to_string(), None)
+        );
+        assert_eq!(
+            cap_sub_language("Show.S01E02".to_string(), Some(".srt")),
+            ("Show.S01E02".to_string(), None)
+        );
+        assert_eq!(
+            cap_sub_language("Show.xx".to_string(), Some(".srt")),
+            ("Show.xx".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn year_simple() {
+        assert_eq!(cap_year("Show 2019 S01E02"), Some(2019));
+        assert_eq!(cap_year("Movie.1999.1080p"), Some(1999));
+        assert_eq!(cap_year("Show 12019 S01E02"), None);
+        assert_eq!(cap_year("Show S01E02"), None);
+    }
+
+    #[test]
+    fn year_bracketed() {
+        assert_eq!(cap_year("Show (2019) S01E02"), Some(2019));
+        assert_eq!(cap_year("Show [2019] S01E02"), Some(2019));
+        assert_eq!(cap_year("1984 (2019).mkv"), Some(2019));
+        assert_eq!(cap_year("Show 2001 (1999)"), Some(1999));
+    }
+
+    #[test]
+    fn year_not_episode() {
+        assert_eq!(cap_year("Show.S01E2019.mkv"), None);
+        assert_eq!(cap_year("Show Episode 2019.mkv"), None);
+        assert_eq!(cap_year("Show - Ep.2000 - 2019.mkv"), Some(2019));
+        assert_eq!(cap_year("Show 20190 Episodes"), None);
+    }
+
+    #[test]
+    fn airdates() {
+        let airdate = cap_airdate("Show.2021.03.14.720p").unwrap();
+
+        assert_eq!((airdate.year, airdate.month, airdate.day), (2021, 3, 14));
+        assert_eq!(airdate.iso, "2021-03-14");
+        assert_eq!(
+            cap_airdate("Show 2020-02-29").map(|airdate| airdate.iso),
+            Some("2020-02-29".to_string())
+        );
+        assert_eq!(cap_airdate("Show 2021-02-29"), None);
+        assert_eq!(cap_airdate("Show 2021.13.01"), None);
+        assert_eq!(cap_airdate("Show 2021 S01E02"), None);
+    }
+
+    #[test]
+    fn user_regex_limits() {
+        assert!(compile_user_regex(r"(?i)ch(apter)? *[0-9]+").is_ok());
+        assert!(matches!(
+            compile_user_regex(r"(\w{100}){100}"),
+            Err(regex::Error::CompiledTooBig(_))
+        ));
+        assert!(matches!(
+            compile_user_regex(r"("),
+            Err(regex::Error::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn case_each() {
+        let render = "Artist — Some title (Live)";
+
+        assert_eq!(Case::Original.apply(render), render);
+        assert_eq!(Case::Title.apply(render), "Artist — Some Title (live)");
+        assert_eq!(Case::Kebab.apply(render), "artist-some-title-live");
+        assert_eq!(Case::Snake.apply(render), "artist_some_title_live");
+    }
+
+    #[test]
+    fn case_parse() {
+        assert_eq!(parse_case(None), Ok(Case::Original));
+        assert_eq!(parse_case(Some("KEBAB".to_string())), Ok(Case::Kebab));
+        assert_eq!(
+            parse_case(Some("camel".to_string())),
+            Err(UnknownCase("camel".to_string()))
+        );
+    }
+
+    fn lookup(name: &str) -> Option<TemplateValue> {
+        match name {
+            "name" => Some(TemplateValue::Text(Some("Title".to_string()))),
+            "album" => Some(TemplateValue::Text(None)),
+            "track" => Some(TemplateValue::Number(Some(3))),
+            "disc" => Some(TemplateValue::Verbatim(Some("1".to_string()))),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn clean_control() {
+        assert_eq!(clean_input("Show S01E02.mkv\r\n"), "Show S01E02.mkv");
+        assert_eq!(clean_input(" \tShow\u{7f} S01E02.mkv "), "Show S01E02.mkv");
+        assert_eq!(clean_input("Show — S01E02.mkv"), "Show — S01E02.mkv");
+    }
+
+    #[test]
+    fn compact_nulls() {
+        let model = ResponseModel::new(200, "Success", vec![Some(1), None]);
+
+        assert_eq!(
+            compact_json(&ResponseModel::<()>::basic(404, "Not found")).to_string(),
+            r#"{"status":404,"msg":"Not found","schema_version":1}"#
+        );
+        assert_eq!(
+            compact_json(&model).to_string(),
+            r#"{"status":200,"msg":"Success","body":[1,null],"schema_version":1}"#
+        );
+    }
+
+    #[test]
+    fn selected_fields() {
+        let valid = ["a", "b", "c"];
+        let value = serde_json::json!({"a": 1, "b": null, "c": 3});
+        let fields = parse_fields("c, a,b", &valid).unwrap();
+
+        assert_eq!(fields, vec!["c", "a", "b"]);
+        assert_eq!(
+            serde_json::to_string(&Selected::new(&value, Some(fields))).unwrap(),
+            r#"{"c":3,"a":1,"b":null}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Selected::new(&value, None)).unwrap(),
+            r#"{"a":1,"b":null,"c":3}"#
+        );
+        assert_eq!(
+            parse_fields("a,d", &valid),
+            Err("Unknown field `d`, valid fields are a, b, c".to_string())
+        );
+    }
+
+    #[test]
+    fn xml_escaping() {
+        assert_eq!(escape_xml("Plain"), "Plain");
+        assert_eq!(
+            escape_xml(r#"Tom & Jerry's <"Show">"#),
+            "Tom &amp; Jerry&apos;s &lt;&quot;Show&quot;&gt;"
+        );
+    }
+
+    #[test]
+    fn csv_escaping() {
+        let columns = vec!["title".to_string(), "tags".to_string(), "year".to_string()];
+        let rows = vec![
+            serde_json::json!({"title": "Plain", "tags": ["720p", "x264"], "year": 2019}),
+            serde_json::json!({"title": "Comma, \"Quoted\"", "tags": [], "year": null}),
+        ];
+
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        assert_eq!(
+            to_csv(rows, &columns),
+            "title,tags,year\r\nPlain,720p x264,2019\r\n\"Comma, \"\"Quoted\"\"\",,\r\n"
+        );
+    }
+
+    #[test]
+    fn name_lengths() {
+        assert_eq!(
+            check_name_len(&"a".repeat(DEFAULT_MAX_NAME_LEN), None),
+            Ok(())
+        );
+        assert_eq!(
+            check_name_len(&"a".repeat(DEFAULT_MAX_NAME_LEN + 1), None),
+            Err(NameTooLong {
+                len: DEFAULT_MAX_NAME_LEN + 1,
+                max: DEFAULT_MAX_NAME_LEN
+            })
+        );
+        assert_eq!(check_name_len("日本語", Some(3)), Ok(()));
+        assert_eq!(
+            check_name_len("Show S01E02.mkv", Some(4)),
+            Err(NameTooLong { len: 15, max: 4 })
+        );
+    }
+
+    #[test]
+    fn ndjson_lines() {
+        let rows = vec![
+            serde_json::json!({"title": "Multi\nLine", "season": 1}),
+            serde_json::json!({"title": "Other", "season": null}),
+        ];
+        let ndjson = to_ndjson(rows.clone());
+
+        assert_eq!(ndjson.lines().count(), 2);
+        assert!(ndjson.ends_with('\n'));
+        for (line, row) in ndjson.lines().zip(rows) {
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(line).unwrap(),
+                row
+            );
+        }
+        assert_eq!(to_ndjson(Vec::<()>::new()), "");
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("Breakin Bad", "Breaking Bad"), 1);
+        assert_eq!(levenshtein("日本", "日本語"), 1);
+        assert!((similarity("ABC", "abc") - 1.0).abs() < f64::EPSILON);
+        assert!((similarity("", "") - 1.0).abs() < f64::EPSILON);
+        assert!((similarity("abcd", "abxd") - 0.75).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn filename_exts() {
+        let cap = |name: &str, filename: &str, exts: &[&str]| {
+            assert_eq!(
+                cap_filename_exts(name),
+                (
+                    filename.to_string(),
+                    exts.iter().map(|ext| ext.to_string()).collect()
+                )
+            )
+        };
+
+        cap("Show.S01E02.en.srt", "Show.S01E02", &["en", "srt"]);
+        cap(".en.srt", "", &["en", "srt"]);
+        cap("Show.mkv", "Show", &["mkv"]);
+        cap("Movie.x264.mkv", "Movie.x264", &["mkv"]);
+        cap("backup.TAR.gz", "backup", &["TAR", "gz"]);
+        cap("noext", "noext", &[]);
+    }
+
+    #[test]
+    fn filename_ext() {
+        let cap = |name: &str| cap_filename_ext(name);
+
+        assert_eq!(
+            cap("a.b.mkv"),
+            ("a.b".to_string(), Some(".mkv".to_string()))
+        );
+        assert_eq!(cap(".hidden"), (".hidden".to_string(), None));
+        assert_eq!(cap("noext"), ("noext".to_string(), None));
+        assert_eq!(cap("trailing."), ("trailing.".to_string(), None));
+        assert_eq!(
+            cap("Show.S01E02.DD5.1.mkv"),
+            ("Show.S01E02.DD5.1".to_string(), Some(".mkv".to_string()))
+        );
+        assert_eq!(
+            cap("Show.S01E02.DD5.1"),
+            ("Show.S01E02.DD5.1".to_string(), None)
+        );
+        assert_eq!(
+            cap("archive.001"),
+            ("archive".to_string(), Some(".001".to_string()))
+        );
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn ext_route() {
+        let client = rocket::local::Client::new(rocket::ignite().mount("/", routes![ext]))
+            .expect("Could not make client");
+        let get = |name: &str| {
+            client
+                .get(format!("/ext?name={}", name))
+                .dispatch()
+                .body_string()
+                .unwrap()
+        };
+
+        assert_eq!(get("a.b.mkv"), r#"{"ext":".mkv"}"#);
+        assert_eq!(get(".hidden"), r#"{"ext":null}"#);
+        assert_eq!(get("noext"), r#"{"ext":null}"#);
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(
+            cap_group("Show.S01E02.1080p.WEB.H264-GROUP"),
+            Some("GROUP".to_string())
+        );
+        assert_eq!(
+            cap_group("Show.S01E02.1080p.WEB-DL-GRP"),
+            Some("GRP".to_string())
+        );
+        assert_eq!(cap_group("Show.S01E02.1080p.WEB-DL"), None);
+        assert_eq!(cap_group("Show S01E01-E03"), None);
+        assert_eq!(cap_group("Show S01E02"), None);
+    }
+
+    #[test]
+    fn fullwidth() {
+        assert_eq!(normalize_fullwidth("Ｅ０５"), "E05");
+        assert_eq!(
+            normalize_fullwidth("番組　Ｓ０１Ｅ０５．ｍｋｖ"),
+            "番組 S01E05.mkv"
+        );
+        assert_eq!(normalize_fullwidth("Show S01E05"), "Show S01E05");
+    }
+
+    #[test]
+    fn default_exts() {
+        assert_eq!(ext_or_default(None, Some(".mkv")), Some(".mkv".to_string()));
+        assert_eq!(ext_or_default(None, Some("mkv")), Some(".mkv".to_string()));
+        assert_eq!(
+            ext_or_default(Some(".mp4".to_string()), Some(".mkv")),
+            Some(".mp4".to_string())
+        );
+        assert_eq!(ext_or_default(None, Some("")), None);
+        assert_eq!(ext_or_default(None, Some(".")), None);
+        assert_eq!(ext_or_default(None, None), None);
+    }
+
+    #[test]
+    fn etags() {
+        let tag = etag(r#"{"status":200}"#);
+
+        assert_eq!(tag, etag(r#"{"status":200}"#));
+        assert_ne!(tag, etag(r#"{"status":400}"#));
+        assert!(tag.starts_with('"') && tag.ends_with('"'));
+        assert!(etag_matches(Some(&tag), &tag));
+        assert!(etag_matches(Some(&format!("\"other\", W/{}", tag)), &tag));
+        assert!(etag_matches(Some("*"), &tag));
+        assert!(!etag_matches(Some("\"other\""), &tag));
+        assert!(!etag_matches(None, &tag));
+    }
+
+    #[test]
+    fn etags_fixed() {
+        assert_eq!(etag(""), "\"cbf29ce484222325\"");
+        assert_eq!(etag("a"), "\"af63dc4c8601ec8c\"");
+    }
+
+    #[test]
+    fn crcs() {
+        assert_eq!(
+            cap_crc("Show - 05 [A1B2C3D4]".to_string()),
+            ("Show - 05".to_string(), Some("A1B2C3D4".to_string()))
+        );
+        assert_eq!(
+            cap_crc("[Group] Show - 05 [1080p][a1b2c3d4]".to_string()),
+            (
+                "[Group] Show - 05 [1080p]".to_string(),
+                Some("a1b2c3d4".to_string())
+            )
+        );
+        assert_eq!(
+            cap_crc("Show - 05 [1080p]".to_string()),
+            ("Show - 05 [1080p]".to_string(), None)
+        );
+        assert_eq!(cap_crc("Show [A1B2C3D4E]".to_string()).1, None);
+        assert_eq!(cap_crc("Show [G1B2C3D4]".to_string()).1, None);
+    }
+
+    #[test]
+    fn dash_modes() {
+        assert_eq!(
+            format_name_with("Artist - Title", DashMode::Collapse),
+            "Artist Title"
+        );
+        assert_eq!(
+            format_name_with("Artist - Title", DashMode::Preserve),
+            "Artist - Title"
+        );
+        assert_eq!(
+            format_name_with("Some.Artist  —  Some-Title", DashMode::Preserve),
+            "Some Artist — Some Title"
+        );
+        assert_eq!(format_name_with("Artist - ", DashMode::Preserve), "Artist");
+        assert_eq!(format_name_with(" - Title", DashMode::Preserve), "Title");
+    }
+
+    #[test]
+    fn pad_simple() {
+        assert_eq!(pad_num(2, 2), "02");
+        assert_eq!(pad_num(102, 2), "102");
+        assert_eq!(pad_num(0, 3), "000");
+    }
+
+    #[test]
+    fn template_simple() {
+        assert_eq!(
+            render_template("{track:02} - {name}{album}.mp3", lookup),
+            Ok("03 - Title.mp3".to_string())
+        );
+        assert_eq!(render_template("{track}", lookup), Ok("3".to_string()));
+        assert_eq!(
+            render_template("{disc:02}-{disc}", lookup),
+            Ok("1-1".to_string())
+        );
+        assert_eq!(render_template("plain", lookup), Ok("plain".to_string()));
+    }
+
+    #[test]
+    fn template_errors() {
+        assert_eq!(
+            render_template("{nope}", lookup),
+            Err(TemplateError::UnknownPlaceholder("nope".to_string()))
+        );
+        assert_eq!(
+            render_template("{name:02}", lookup),
+            Err(TemplateError::InvalidFormat("name:02".to_string()))
+        );
+        assert_eq!(
+            render_template("{name", lookup),
+            Err(TemplateError::Unclosed)
+        );
+        assert_eq!(
+            render_template("{track:099999999999}", lookup),
+            Err(TemplateError::InvalidFormat(
+                "track:099999999999".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn pathological_names() {
+        let long = "a.".repeat(100_000);
+
+        for name in &["", ".", "..........", "🎬📺.🍿", "日本語", long.as_str()] {
+            let _ = cap_filename_ext(name);
+            let _ = cap_tags(name);
+            let _ = cap_group(name);
+            let _ = cap_year(name);
+            let _ = cap_airdate(name);
+            let _ = clean_input(name);
+            let _ = format_name_with(name, DashMode::Preserve);
+        }
+
+        assert_eq!(
+            cap_filename_ext(".........."),
+            ("..........".to_string(), None)
+        );
+        assert_eq!(
+            cap_filename_ext("🎬📺.🍿"),
+            ("🎬📺".to_string(), Some(".🍿".to_string()))
+        );
+        assert_eq!(cap_filename_ext(""), (String::new(), None));
+    }
+
+    #[test]
+    fn tags_scene() {
+        assert_eq!(
+            cap_tags("The.Show.S01E02.1080p.WEB-DL.H264-GROUP"),
+            vec!["1080p", "WEB-DL", "H264"]
+        );
+        assert_eq!(cap_tags("Show [720p] (x265)"), vec!["720p", "x265"]);
+        assert!(cap_tags("Show S01E02").is_empty());
+    }
+
+    #[test]
+    fn brackets() {
+        let name = "[Group] Show - 05 (BD 1080p) [A1B2C3D4]";
+
+        assert_eq!(parse_brackets(name), vec!["Group", "BD 1080p", "A1B2C3D4"]);
+        assert!(parse_brackets("Show [] ( ) S01E02").is_empty());
+        assert_eq!(
+            cap_leading_group(name),
+            Some((
+                "Show - 05 (BD 1080p) [A1B2C3D4]".to_string(),
+                "Group".to_string()
+            ))
+        );
+        assert_eq!(cap_leading_group("[1080p] Show - 05"), None);
+        assert_eq!(cap_leading_group("[A1B2C3D4] Show - 05"), None);
+        assert_eq!(cap_leading_group("Show [Group] - 05"), None);
+    }
+
+    #[test]
+    fn codecs() {
+        let tags = cap_tags("Show.S01E02.1080p.BluRay.x265.DTS");
+
+        assert_eq!(
+            cap_codecs(&tags),
+            (Some("H.265".to_string()), Some("DTS".to_string()))
+        );
+        assert_eq!(
+            cap_codecs(&["HEVC".to_string(), "aac".to_string()]),
+            (Some("H.265".to_string()), Some("AAC".to_string()))
+        );
+        assert_eq!(cap_codecs(&["1080p".to_string()]), (None, None));
+        assert_eq!(cap_container(Some(".MKV")), Some("mkv".to_string()));
+        assert_eq!(cap_container(Some(".srt")), None);
+        assert_eq!(cap_container(None), None);
+    }
+
+    #[test]
+    fn platforms() {
+        assert_eq!(
+            cap_platform("Show.S01E02.NF.WEB-DL.1080p".to_string()),
+            (
+                "Show.S01E02.WEB-DL.1080p".to_string(),
+                Some("Netflix".to_string())
+            )
+        );
+        assert_eq!(
+            cap_platform("Show S01E02 AMZN WEBRip".to_string()),
+            ("Show S01E02 WEBRip".to_string(), Some("Amazon".to_string()))
+        );
+        assert_eq!(
+            cap_platform("Show [DSNP] S01E02".to_string()).1,
+            Some("Disney+".to_string())
+        );
+        assert_eq!(
+            cap_platform("Infinity S01E02 nf".to_string()),
+            ("Infinity S01E02 nf".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn audio_channels() {
+        let channels = |name: &str| cap_audio_channels(name);
+
+        assert_eq!(channels("Show.S01E02.DD5.1"), Some("5.1".to_string()));
+        assert_eq!(
+            channels("Show.S01E02.DDP5.1.Atmos"),
+            Some("5.1 Atmos".to_string())
+        );
+        assert_eq!(channels("Show S01E02 AAC2.0 x264"), Some("2.0".to_string()));
+        assert_eq!(channels("Show.S01E02.TrueHD.7.1"), Some("7.1".to_string()));
+        assert_eq!(channels("Show S01E02 Atmos"), Some("Atmos".to_string()));
+        assert_eq!(channels("Show S01E02 x264"), None);
+        assert_eq!(channels("Show S01E02 1080p H.264"), None);
+    }
+
+    #[test]
+    fn site_prefix_strip() {
+        assert_eq!(
+            strip_site_prefix("www.Torrenting.com - Show.S01E02".to_string()),
+            "Show.S01E02"
+        );
+        assert_eq!(
+            strip_site_prefix("[ www.site.co.uk ]Movie (2019)".to_string()),
+            "Movie (2019)"
+        );
+        assert_eq!(
+            strip_site_prefix("www.site.com.Show.S01E02".to_string()),
+            "www.site.com.Show.S01E02"
+        );
+        assert_eq!(
+            strip_site_prefix("Show - www.site.com - S01E02".to_string()),
+            "Show - www.site.com - S01E02"
+        );
+    }
+
+    #[test]
+    fn site_prefixes_custom_file() {
+        let path = env::temp_dir().join("tagzen_site_prefixes_test.txt");
+        fs::write(&path, "(?i)rarbg[ ._-]+\n\n").unwrap();
+
+        let prefixes = load_site_prefixes(path.to_str()).unwrap();
+        fs::write(&path, "(unclosed").unwrap();
+        let invalid = load_site_prefixes(path.to_str());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(prefixes.len(), 1);
+        assert!(prefixes[0].is_match("RARBG.Show.S01E02"));
+        assert!(!prefixes[0].is_match("Show.RARBG.S01E02"));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn tags_custom_file() {
+        let path = env::temp_dir().join("tagzen_quality_tags_test.txt");
+        fs::write(&path, "REMUX\n\n dv \n").unwrap();
+
+        let tags = load_quality_tags(path.to_str()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains("REMUX"));
+        assert!(tags.contains("DV"));
+    }
+
+    #[test]
+    fn tags_default() {
+        let tags = load_quality_tags(None::<&str>).unwrap();
+
+        assert!(tags.contains("1080P"));
+        assert!(tags.contains("WEB-DL"));
+    }
+}
@@ -0,0 +1,16 @@
+//! Bindings of the pure tagging functions for use from javascript, built for
+//! `wasm32-unknown-unknown` with the `wasm` feature and without `server`
+
+use crate::tv::{Capture, CaptureOptions, Context};
+
+use wasm_bindgen::prelude::*;
+
+/// Captures a tv episode `name` using the default options, giving the same
+/// object as the `/tv/episode` route or `null` if nothing could be captured
+#[wasm_bindgen]
+pub fn capture_tv(name: String) -> JsValue {
+    Capture::new(name, &Context::default(), &CaptureOptions::default())
+        .ok()
+        .and_then(|cap| JsValue::from_serde(&cap).ok())
+        .unwrap_or(JsValue::NULL)
+}